@@ -826,7 +826,7 @@ fn decode_hex_val(val: u8) -> Option<u16> {
 
 // Lookup table of bytes that must be escaped. A value of true at index i means
 // that byte i requires an escape sequence in the input.
-static ESCAPE: [bool; 256] = {
+pub(super) static ESCAPE: [bool; 256] = {
     const CT: bool = true; // control character \x00..=\x1F
     const QU: bool = true; // quote \x22
     const BS: bool = true; // backslash \x5C
@@ -876,16 +876,16 @@ static HEX: [u8; 256] = {
     ]
 };
 
-struct KeyCh;
+pub(super) struct KeyCh;
 
 impl KeyCh {
-    const NOT_ALLOWED: u8 = 255;
-    const ALLOWED: u8 = 0;
-    const EQ_SIGN: u8 = 1;
-    const UNICODE: u8 = 2;
+    pub(super) const NOT_ALLOWED: u8 = 255;
+    pub(super) const ALLOWED: u8 = 0;
+    pub(super) const EQ_SIGN: u8 = 1;
+    pub(super) const UNICODE: u8 = 2;
 }
 
-static KEY: [u8; 256] = {
+pub(super) static KEY: [u8; 256] = {
     const NA: u8 = KeyCh::NOT_ALLOWED;
     const __: u8 = KeyCh::ALLOWED;
     const EQ: u8 = KeyCh::EQ_SIGN;
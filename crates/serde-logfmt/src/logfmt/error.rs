@@ -1,6 +1,6 @@
 use std::{error, fmt};
 
-use serde::de;
+use serde::{de, ser};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -24,6 +24,11 @@ pub enum Error {
     Custom(String),
     InvalidUtf8(std::str::Utf8Error),
     NotImplemented,
+    ExpectedMap,
+    KeyMustBeAString,
+    InvalidKey,
+    UnsupportedType,
+    Io(String),
 }
 
 impl fmt::Display for Error {
@@ -47,6 +52,11 @@ impl fmt::Display for Error {
             Self::Custom(msg) => f.write_str(msg),
             Self::InvalidUtf8(err) => write!(f, "invalid utf-8: {}", err),
             Self::NotImplemented => f.write_str("not implemented"),
+            Self::ExpectedMap => f.write_str("expected map or struct"),
+            Self::KeyMustBeAString => f.write_str("key must be a string"),
+            Self::InvalidKey => f.write_str("invalid key"),
+            Self::UnsupportedType => f.write_str("unsupported type"),
+            Self::Io(err) => write!(f, "io error: {}", err),
         }
     }
 }
@@ -74,6 +84,13 @@ impl de::Error for Error {
     }
 }
 
+impl ser::Error for Error {
+    #[cold]
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Self::Custom(msg.to_string())
+    }
+}
+
 struct Unexpected<'a>(de::Unexpected<'a>);
 
 impl<'a> fmt::Display for Unexpected<'a> {
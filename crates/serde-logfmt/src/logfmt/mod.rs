@@ -1,7 +1,9 @@
 pub mod de;
 pub mod error;
 pub mod raw;
+pub mod ser;
 
 pub use de::{from_slice, from_str};
+pub use ser::{to_string, to_vec, to_writer};
 #[allow(unused_imports)]
 pub use error::Error;
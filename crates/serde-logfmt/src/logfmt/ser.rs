@@ -0,0 +1,754 @@
+use std::{fmt::Display, io, str};
+
+use serde::ser::{self, Impossible, Serialize};
+
+use super::{
+    de::{ESCAPE, KEY, KeyCh},
+    error::{Error, Result},
+    raw::TOKEN,
+};
+
+#[inline]
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: io::Write,
+    T: ?Sized + Serialize,
+{
+    let mut serializer = Serializer::new(writer);
+    value.serialize(&mut serializer)
+}
+
+#[inline]
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>>
+where
+    T: ?Sized + Serialize,
+{
+    let mut writer = Vec::with_capacity(128);
+    to_writer(&mut writer, value)?;
+    Ok(writer)
+}
+
+#[inline]
+pub fn to_string<T>(value: &T) -> Result<String>
+where
+    T: ?Sized + Serialize,
+{
+    let vec = to_vec(value)?;
+    // Serializer produces valid UTF-8 only.
+    Ok(unsafe { String::from_utf8_unchecked(vec) })
+}
+
+// ---
+
+/// Serializes maps and structs as a line of space-separated key-value pairs.
+///
+/// Fields of nested maps and structs are flattened using dot-separated keys.
+/// Fields with `None` values are omitted.
+pub struct Serializer<W> {
+    writer: W,
+    prefix: String,
+    first: bool,
+}
+
+impl<W: io::Write> Serializer<W> {
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        Serializer {
+            writer,
+            prefix: String::new(),
+            first: true,
+        }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_key(&mut self, key: &str) -> Result<()> {
+        validate_key(key)?;
+        if !self.first {
+            self.write(b" ")?;
+        }
+        self.first = false;
+        self.writer.write_all(self.prefix.as_bytes()).map_err(io_error)?;
+        self.write(key.as_bytes())?;
+        self.write(b"=")
+    }
+
+    fn write_display<T: Display>(&mut self, value: T) -> Result<()> {
+        write!(self.writer, "{}", value).map_err(io_error)
+    }
+
+    fn write_str(&mut self, value: &str) -> Result<()> {
+        if !needs_quoting(value) {
+            return self.write(value.as_bytes());
+        }
+
+        self.write(b"\"")?;
+        let bytes = value.as_bytes();
+        let mut start = 0;
+        for (i, &byte) in bytes.iter().enumerate() {
+            if !ESCAPE[byte as usize] {
+                continue;
+            }
+            self.write(&bytes[start..i])?;
+            match byte {
+                b'"' => self.write(b"\\\"")?,
+                b'\\' => self.write(b"\\\\")?,
+                b'\n' => self.write(b"\\n")?,
+                b'\r' => self.write(b"\\r")?,
+                b'\t' => self.write(b"\\t")?,
+                b'\x08' => self.write(b"\\b")?,
+                b'\x0c' => self.write(b"\\f")?,
+                _ => write!(self.writer, "\\u{:04x}", byte).map_err(io_error)?,
+            }
+            start = i + 1;
+        }
+        self.write(&bytes[start..])?;
+        self.write(b"\"")
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer.write_all(bytes).map_err(io_error)
+    }
+}
+
+impl<'a, W: io::Write> ser::Serializer for &'a mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Compound<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_bool(self, _: bool) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_char(self, _: char) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_str(self, _: &str) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, _variant: &'static str) -> Result<()> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::ExpectedMap)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(Compound::new(self))
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        if name == TOKEN {
+            return Err(Error::ExpectedMap);
+        }
+        Ok(Compound::new(self))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::ExpectedMap)
+    }
+}
+
+// ---
+
+/// Serializes fields of a map or a struct, possibly nested.
+pub enum Compound<'a, W> {
+    Map {
+        ser: &'a mut Serializer<W>,
+        key: Option<String>,
+        prefix: usize,
+    },
+    Raw {
+        ser: &'a mut Serializer<W>,
+        key: String,
+    },
+}
+
+impl<'a, W: io::Write> Compound<'a, W> {
+    #[inline]
+    fn new(ser: &'a mut Serializer<W>) -> Self {
+        let prefix = ser.prefix.len();
+        Compound::Map { ser, key: None, prefix }
+    }
+}
+
+impl<'a, W: io::Write> ser::SerializeMap for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        match self {
+            Compound::Map { key: pending, .. } => {
+                *pending = Some(key.serialize(MapKeySerializer)?);
+                Ok(())
+            }
+            Compound::Raw { .. } => unreachable!(),
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        match self {
+            Compound::Map { ser, key, .. } => {
+                let key = key.take().ok_or(Error::ExpectedKey)?;
+                value.serialize(ValueSerializer {
+                    ser: &mut **ser,
+                    key: &key,
+                })
+            }
+            Compound::Raw { .. } => unreachable!(),
+        }
+    }
+
+    fn end(self) -> Result<()> {
+        ser::SerializeStruct::end(self)
+    }
+}
+
+impl<'a, W: io::Write> ser::SerializeStruct for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        match self {
+            Compound::Map { ser, .. } => value.serialize(ValueSerializer { ser: &mut **ser, key }),
+            Compound::Raw { ser, key } => {
+                let raw = value.serialize(MapKeySerializer)?;
+                ser.write_key(key)?;
+                ser.write(raw.as_bytes())
+            }
+        }
+    }
+
+    fn end(self) -> Result<()> {
+        if let Compound::Map { ser, prefix, .. } = self {
+            ser.prefix.truncate(prefix);
+        }
+        Ok(())
+    }
+}
+
+// ---
+
+/// Serializes a value of a field with the given key.
+struct ValueSerializer<'a, 'k, W> {
+    ser: &'a mut Serializer<W>,
+    key: &'k str,
+}
+
+impl<'a, 'k, W: io::Write> ValueSerializer<'a, 'k, W> {
+    fn nested(self) -> Result<Compound<'a, W>> {
+        validate_key(self.key)?;
+        let prefix = self.ser.prefix.len();
+        self.ser.prefix.push_str(self.key);
+        self.ser.prefix.push('.');
+        Ok(Compound::Map {
+            ser: self.ser,
+            key: None,
+            prefix,
+        })
+    }
+}
+
+impl<'a, 'k, W: io::Write> ser::Serializer for ValueSerializer<'a, 'k, W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Compound<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_bool(self, value: bool) -> Result<()> {
+        self.ser.write_key(self.key)?;
+        self.ser.write(if value { b"true" } else { b"false" })
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<()> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<()> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<()> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<()> {
+        self.ser.write_key(self.key)?;
+        self.ser.write_display(value)
+    }
+
+    fn serialize_i128(self, value: i128) -> Result<()> {
+        self.ser.write_key(self.key)?;
+        self.ser.write_display(value)
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<()> {
+        self.serialize_u64(value.into())
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<()> {
+        self.serialize_u64(value.into())
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<()> {
+        self.serialize_u64(value.into())
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<()> {
+        self.ser.write_key(self.key)?;
+        self.ser.write_display(value)
+    }
+
+    fn serialize_u128(self, value: u128) -> Result<()> {
+        self.ser.write_key(self.key)?;
+        self.ser.write_display(value)
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<()> {
+        self.serialize_f64(value.into())
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<()> {
+        self.ser.write_key(self.key)?;
+        self.ser.write_display(value)
+    }
+
+    fn serialize_char(self, value: char) -> Result<()> {
+        self.serialize_str(value.encode_utf8(&mut [0_u8; 4]))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<()> {
+        self.ser.write_key(self.key)?;
+        self.ser.write_str(value)
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<()> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        self.ser.write_key(self.key)?;
+        self.ser.write(b"null")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<()> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        self.nested()
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        if name == TOKEN {
+            return Ok(Compound::Raw {
+                ser: self.ser,
+                key: self.key.to_owned(),
+            });
+        }
+        self.nested()
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::UnsupportedType)
+    }
+}
+
+// ---
+
+/// Serializes map keys, which must be strings or values that can be naturally represented as strings.
+struct MapKeySerializer;
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = String;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_bool(self, value: bool) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_f32(self, _value: f32) -> Result<String> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_f64(self, _value: f64) -> Result<String> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_char(self, value: char) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_str(self, value: &str) -> Result<String> {
+        Ok(value.to_owned())
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<String> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_none(self) -> Result<String> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_unit(self) -> Result<String> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<String> {
+        Ok(variant.to_owned())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::KeyMustBeAString)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::KeyMustBeAString)
+    }
+}
+
+// ---
+
+/// Checks that the key consists only of characters accepted by the deserializer.
+fn validate_key(key: &str) -> Result<()> {
+    if key.is_empty() {
+        return Err(Error::InvalidKey);
+    }
+    match key
+        .bytes()
+        .all(|c| matches!(KEY[c as usize], KeyCh::ALLOWED | KeyCh::UNICODE))
+    {
+        true => Ok(()),
+        false => Err(Error::InvalidKey),
+    }
+}
+
+/// Checks whether the value cannot be represented unquoted.
+///
+/// Strings looking like numbers, booleans or null are quoted as well, so that they are not taken for them.
+fn needs_quoting(value: &str) -> bool {
+    value.is_empty()
+        || value
+            .bytes()
+            .any(|c| c <= b' ' || c == b'"' || c == b'=' || c == b'\x7f')
+        || matches!(value, "true" | "false" | "null")
+        || looks_like_number(value)
+}
+
+fn looks_like_number(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    digits.starts_with(|c: char| c.is_ascii_digit()) && value.parse::<f64>().is_ok()
+}
+
+#[inline]
+fn io_error(err: io::Error) -> Error {
+    Error::Io(err.to_string())
+}
+
+// ---
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,130 @@
+use super::{super::de::from_str, super::raw::RawValue, *};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+#[test]
+fn test_struct() {
+    #[derive(Serialize)]
+    struct Test {
+        int: i32,
+        str1: &'static str,
+        str2: &'static str,
+        flag: bool,
+    }
+
+    let value = Test {
+        int: -42,
+        str1: "a",
+        str2: "b c",
+        flag: true,
+    };
+    assert_eq!(to_string(&value).unwrap(), r#"int=-42 str1=a str2="b c" flag=true"#);
+}
+
+#[test]
+fn test_escape() {
+    let mut value = BTreeMap::new();
+    value.insert("a", "b=c");
+    value.insert("b", "x\n\"y\"\\");
+    value.insert("c", "\x1b[3m");
+    value.insert("d", "");
+    value.insert("e", "ü");
+    assert_eq!(
+        to_string(&value).unwrap(),
+        r#"a="b=c" b="x\n\"y\"\\" c="\u001b[3m" d="" e=ü"#
+    );
+}
+
+#[test]
+fn test_ambiguous_strings() {
+    let mut value = BTreeMap::new();
+    value.insert("a", "42");
+    value.insert("b", "-1.5e3");
+    value.insert("c", "true");
+    value.insert("d", "null");
+    value.insert("e", "42x");
+    value.insert("f", "nan");
+    assert_eq!(
+        to_string(&value).unwrap(),
+        r#"a="42" b="-1.5e3" c="true" d="null" e=42x f=nan"#
+    );
+}
+
+#[test]
+fn test_nested() {
+    #[derive(Serialize)]
+    struct Inner {
+        x: u8,
+        y: Option<u8>,
+    }
+
+    #[derive(Serialize)]
+    struct Test {
+        a: Inner,
+        b: BTreeMap<&'static str, Inner>,
+        c: f64,
+    }
+
+    let value = Test {
+        a: Inner { x: 1, y: None },
+        b: BTreeMap::from([("k", Inner { x: 2, y: Some(3) })]),
+        c: 0.5,
+    };
+    assert_eq!(to_string(&value).unwrap(), "a.x=1 b.k.x=2 b.k.y=3 c=0.5");
+}
+
+#[test]
+fn test_raw() {
+    #[derive(Serialize, Deserialize)]
+    struct Test<'a> {
+        int: i32,
+        #[serde(borrow)]
+        raw: &'a RawValue,
+    }
+
+    let j = r#"int=1 raw="b \nc""#;
+    let parsed: Test = from_str(j).unwrap();
+    assert_eq!(to_string(&parsed).unwrap(), j);
+}
+
+#[test]
+fn test_round_trip() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Test {
+        int: u32,
+        str1: String,
+        str2: String,
+        str3: String,
+        opt: Option<String>,
+    }
+
+    let value = Test {
+        int: 42,
+        str1: "a".to_string(),
+        str2: "b=c \"d\"".to_string(),
+        str3: "\u{1}\t\u{7f}".to_string(),
+        opt: None,
+    };
+    let s = to_string(&value).unwrap();
+    assert_eq!(from_str::<Test>(&s).unwrap(), value);
+
+    let value = HashMap::from([("k.1".to_string(), "v 1".to_string())]);
+    let s = to_string(&value).unwrap();
+    assert_eq!(from_str::<HashMap<String, String>>(&s).unwrap(), value);
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(to_string(&42), Err(Error::ExpectedMap));
+    assert_eq!(to_string(&HashMap::from([("a b", 1)])), Err(Error::InvalidKey));
+    assert_eq!(to_string(&HashMap::from([("", 1)])), Err(Error::InvalidKey));
+    assert_eq!(to_string(&HashMap::from([((1, 2), 1)])), Err(Error::KeyMustBeAString));
+    assert_eq!(to_string(&HashMap::from([("a", vec![1])])), Err(Error::UnsupportedType));
+}
+
+#[test]
+fn test_to_writer() {
+    let mut buf = Vec::new();
+    to_writer(&mut buf, &BTreeMap::from([("a", 1), ("b", 2)])).unwrap();
+    assert_eq!(buf, b"a=1 b=2");
+}
@@ -0,0 +1,153 @@
+// third-party imports
+use chrono::{DateTime, FixedOffset};
+use thiserror::Error;
+
+// ---
+
+const TIMESTAMP_FORMAT: &str = "%d/%b/%Y:%H:%M:%S %z";
+
+// ---
+
+/// Error is an error which may occur when parsing an access log entry.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    #[error("invalid utf-8 sequence")]
+    InvalidUnicode,
+    #[error("unexpected end of entry")]
+    UnexpectedEnd,
+    #[error("invalid timestamp")]
+    InvalidTimestamp,
+    #[error("invalid quoted string")]
+    InvalidQuotedString,
+    #[error("invalid status")]
+    InvalidStatus,
+    #[error("invalid size")]
+    InvalidSize,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// ---
+
+/// Entry is an access log entry in Common or Combined Log Format split into parts.
+///
+/// Values equal to `-` are considered missing, quoted values are provided without the enclosing quotes
+/// and with escape sequences kept as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry<'a> {
+    pub remote_addr: &'a str,
+    pub ident: Option<&'a str>,
+    pub user: Option<&'a str>,
+    pub timestamp: &'a str,
+    pub time: DateTime<FixedOffset>,
+    pub request: Option<&'a str>,
+    pub status: &'a str,
+    pub bytes: Option<&'a str>,
+    pub referer: Option<&'a str>,
+    pub user_agent: Option<&'a str>,
+}
+
+impl<'a> Entry<'a> {
+    /// Parses an access log entry.
+    ///
+    /// Referer and user agent are optional, anything following them is ignored.
+    pub fn parse(line: &'a [u8]) -> Result<Self> {
+        let line = std::str::from_utf8(line).map_err(|_| Error::InvalidUnicode)?;
+        let mut rest = line.trim_end_matches(['\r', '\n']);
+
+        let remote_addr = Self::token(&mut rest)?;
+        let ident = Self::token(&mut rest)?;
+        let user = Self::token(&mut rest)?;
+
+        let tail = rest.strip_prefix('[').ok_or(Error::InvalidTimestamp)?;
+        let (timestamp, tail) = tail.split_once(']').ok_or(Error::InvalidTimestamp)?;
+        let time = DateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).map_err(|_| Error::InvalidTimestamp)?;
+        rest = tail.trim_start_matches(' ');
+
+        let request = Self::quoted(&mut rest)?.ok_or(Error::InvalidQuotedString)?;
+
+        let status = Self::token(&mut rest)?;
+        if status.len() != 3 || !status.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::InvalidStatus);
+        }
+
+        let bytes = Self::token(&mut rest)?;
+        if bytes != "-" && !bytes.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::InvalidSize);
+        }
+
+        let referer = Self::quoted(&mut rest)?;
+        let user_agent = Self::quoted(&mut rest)?;
+
+        Ok(Self {
+            remote_addr,
+            ident: nil(ident),
+            user: nil(user),
+            timestamp,
+            time,
+            request: nil(request),
+            status,
+            bytes: nil(bytes),
+            referer: referer.and_then(nil),
+            user_agent: user_agent.and_then(nil),
+        })
+    }
+
+    /// Returns the status code.
+    pub fn status_code(&self) -> u16 {
+        self.status.parse().unwrap_or_default()
+    }
+
+    /// Returns method, path and protocol of the request if it is well-formed.
+    ///
+    /// Protocol is missing in HTTP/0.9 requests.
+    pub fn request_parts(&self) -> Option<(&'a str, &'a str, Option<&'a str>)> {
+        let mut parts = self.request?.split(' ').filter(|part| !part.is_empty());
+        let method = parts.next()?;
+        let path = parts.next()?;
+        let protocol = parts.next();
+        if parts.next().is_some() || !method.bytes().all(|b| b.is_ascii_uppercase()) {
+            return None;
+        }
+        Some((method, path, protocol))
+    }
+
+    fn token(rest: &mut &'a str) -> Result<&'a str> {
+        let (token, tail) = rest.split_once(' ').unwrap_or((rest, ""));
+        if token.is_empty() {
+            return Err(Error::UnexpectedEnd);
+        }
+        *rest = tail.trim_start_matches(' ');
+        Ok(token)
+    }
+
+    fn quoted(rest: &mut &'a str) -> Result<Option<&'a str>> {
+        let Some(tail) = rest.strip_prefix('"') else {
+            return Ok(None);
+        };
+
+        let mut bytes = tail.bytes().enumerate();
+        while let Some((i, b)) = bytes.next() {
+            match b {
+                b'\\' => {
+                    bytes.next();
+                }
+                b'"' => {
+                    *rest = tail[i + 1..].trim_start_matches(' ');
+                    return Ok(Some(&tail[..i]));
+                }
+                _ => {}
+            }
+        }
+
+        Err(Error::InvalidQuotedString)
+    }
+}
+
+#[inline]
+fn nil(value: &str) -> Option<&str> {
+    Some(value).filter(|&value| value != "-")
+}
+
+#[cfg(test)]
+mod tests;
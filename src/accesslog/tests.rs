@@ -0,0 +1,75 @@
+use super::*;
+
+#[test]
+fn test_common() {
+    let entry =
+        Entry::parse(br#"127.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] "GET /apache_pb.gif HTTP/1.0" 200 2326"#)
+            .unwrap();
+    assert_eq!(entry.remote_addr, "127.0.0.1");
+    assert_eq!(entry.ident, None);
+    assert_eq!(entry.user, Some("frank"));
+    assert_eq!(entry.timestamp, "10/Oct/2000:13:55:36 -0700");
+    assert_eq!(entry.time.to_rfc3339(), "2000-10-10T13:55:36-07:00");
+    assert_eq!(entry.request, Some("GET /apache_pb.gif HTTP/1.0"));
+    assert_eq!(entry.request_parts(), Some(("GET", "/apache_pb.gif", Some("HTTP/1.0"))));
+    assert_eq!(entry.status, "200");
+    assert_eq!(entry.status_code(), 200);
+    assert_eq!(entry.bytes, Some("2326"));
+    assert_eq!(entry.referer, None);
+    assert_eq!(entry.user_agent, None);
+}
+
+#[test]
+fn test_combined() {
+    let entry = Entry::parse(
+        br#"10.0.0.1 - - [25/Jan/2024:18:00:01 +0000] "POST /api/v1/items?id=1 HTTP/1.1" 503 - "https://example.com/" "curl/8.0 \"x\"" 0.012"#,
+    )
+    .unwrap();
+    assert_eq!(entry.remote_addr, "10.0.0.1");
+    assert_eq!(entry.user, None);
+    assert_eq!(
+        entry.request_parts(),
+        Some(("POST", "/api/v1/items?id=1", Some("HTTP/1.1")))
+    );
+    assert_eq!(entry.status_code(), 503);
+    assert_eq!(entry.bytes, None);
+    assert_eq!(entry.referer, Some("https://example.com/"));
+    assert_eq!(entry.user_agent, Some(r#"curl/8.0 \"x\""#));
+}
+
+#[test]
+fn test_malformed_request() {
+    let entry = Entry::parse(br#"10.0.0.1 - - [25/Jan/2024:18:00:01 +0000] "\x16\x03\x01" 400 157 "-" "-""#).unwrap();
+    assert_eq!(entry.request, Some(r#"\x16\x03\x01"#));
+    assert_eq!(entry.request_parts(), None);
+    assert_eq!(entry.referer, None);
+    assert_eq!(entry.user_agent, None);
+
+    let entry = Entry::parse(br#"10.0.0.1 - - [25/Jan/2024:18:00:01 +0000] "-" 408 0"#).unwrap();
+    assert_eq!(entry.request, None);
+    assert_eq!(entry.request_parts(), None);
+}
+
+#[test]
+fn test_invalid() {
+    let parse = |s: &str| Entry::parse(s.as_bytes()).err();
+
+    assert_eq!(parse("10.0.0.1 -"), Some(Error::UnexpectedEnd));
+    assert_eq!(
+        parse(r#"10.0.0.1 - - [25/Jan/2024:18:00:01] "GET / HTTP/1.1" 200 1"#),
+        Some(Error::InvalidTimestamp)
+    );
+    assert_eq!(
+        parse(r#"10.0.0.1 - - [25/Jan/2024:18:00:01 +0000] "GET / HTTP/1.1 200 1"#),
+        Some(Error::InvalidQuotedString)
+    );
+    assert_eq!(
+        parse(r#"10.0.0.1 - - [25/Jan/2024:18:00:01 +0000] "GET / HTTP/1.1" ok 1"#),
+        Some(Error::InvalidStatus)
+    );
+    assert_eq!(
+        parse(r#"10.0.0.1 - - [25/Jan/2024:18:00:01 +0000] "GET / HTTP/1.1" 200 1k"#),
+        Some(Error::InvalidSize)
+    );
+    assert_eq!(parse("key=value"), Some(Error::UnexpectedEnd));
+}
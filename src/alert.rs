@@ -0,0 +1,189 @@
+// std imports
+use std::{
+    cmp::max,
+    time::{Duration, Instant},
+};
+
+// third-party imports
+use serde::Serialize;
+
+// local imports
+use crate::{
+    level::Level,
+    model::{RawValue, Record},
+};
+
+// ---
+
+/// Snapshot of a record that matched the alert query, it outlives the buffer the record was parsed from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Match {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<Level>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logger: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub fields: json::Map<String, json::Value>,
+}
+
+impl Match {
+    pub fn new(record: &Record) -> Self {
+        Self {
+            timestamp: record.ts.as_ref().map(|ts| ts.raw().into()),
+            level: record.level,
+            logger: record.logger.map(|logger| logger.into()),
+            message: record.message.as_ref().map(text),
+            fields: record
+                .fields()
+                .map(|(key, value)| ((*key).into(), json_value(value)))
+                .collect(),
+        }
+    }
+}
+
+// ---
+
+/// Alert is the first record of a burst of matching records along with the number of records in the burst.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alert {
+    pub count: usize,
+    #[serde(flatten)]
+    pub record: Match,
+}
+
+impl Alert {
+    /// Returns the environment variables describing the alert for the command run on it.
+    ///
+    /// Fields are passed as `HL_FIELD_<KEY>` with the key upper-cased and other characters
+    /// than ASCII letters and digits replaced with underscores.
+    pub fn vars(&self) -> Vec<(String, String)> {
+        let record = &self.record;
+        let mut vars = vec![("HL_COUNT".into(), self.count.to_string())];
+        let mut add = |key: &str, value: Option<&str>| {
+            if let Some(value) = value {
+                vars.push((key.into(), value.into()));
+            }
+        };
+        add("HL_TIMESTAMP", record.timestamp.as_deref());
+        add("HL_LEVEL", record.level.as_ref().map(|level| level.as_ref()));
+        add("HL_LOGGER", record.logger.as_deref());
+        add("HL_MESSAGE", record.message.as_deref());
+        for (key, value) in &record.fields {
+            let key: String = key
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() {
+                        c.to_ascii_uppercase()
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            let value = match value {
+                json::Value::String(s) => s.clone(),
+                value => value.to_string(),
+            };
+            vars.push((format!("HL_FIELD_{key}"), value));
+        }
+        vars
+    }
+}
+
+// ---
+
+/// Alerter collapses bursts of matching records into alerts and limits the rate of the alerts.
+///
+/// An alert is due when no more matching records came for the `debounce` period,
+/// or when the burst lasts longer than both `debounce` and `interval`.
+/// Alerts are never issued more often than once per `interval`.
+pub struct Alerter {
+    debounce: Duration,
+    interval: Duration,
+    pending: Option<Pending>,
+    issued: Option<Instant>,
+}
+
+impl Alerter {
+    pub fn new(debounce: Duration, interval: Duration) -> Self {
+        Self {
+            debounce,
+            interval,
+            pending: None,
+            issued: None,
+        }
+    }
+
+    /// Adds a matching record noticed at the instant `now`.
+    pub fn add(&mut self, record: Match, now: Instant) {
+        match &mut self.pending {
+            Some(pending) => {
+                pending.alert.count += 1;
+                pending.last = now;
+            }
+            None => {
+                self.pending = Some(Pending {
+                    alert: Alert { count: 1, record },
+                    first: now,
+                    last: now,
+                })
+            }
+        }
+    }
+
+    /// Returns the instant the pending alert is due, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        let pending = self.pending.as_ref()?;
+        let quiet = pending.last + self.debounce;
+        let exhausted = pending.first + max(self.debounce, self.interval);
+        let due = quiet.min(exhausted);
+        Some(match self.issued {
+            Some(issued) => due.max(issued + self.interval),
+            None => due,
+        })
+    }
+
+    /// Returns the pending alert if it is due at the instant `now`.
+    pub fn poll(&mut self, now: Instant) -> Option<Alert> {
+        if self.deadline()? > now {
+            return None;
+        }
+        self.issued = Some(now);
+        self.pending.take().map(|pending| pending.alert)
+    }
+
+    /// Returns the pending alert regardless of whether it is due, it is called when no more records are expected.
+    pub fn flush(&mut self) -> Option<Alert> {
+        self.pending.take().map(|pending| pending.alert)
+    }
+}
+
+struct Pending {
+    alert: Alert,
+    first: Instant,
+    last: Instant,
+}
+
+// ---
+
+fn text(value: &RawValue) -> String {
+    let mut buf = Vec::new();
+    value.format_readable(&mut buf);
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+fn json_value(value: &RawValue) -> json::Value {
+    match value {
+        RawValue::String(_) => json::Value::String(text(value)),
+        RawValue::Null => json::Value::Null,
+        RawValue::Boolean(value) => json::Value::Bool(*value),
+        RawValue::Number(_) | RawValue::Object(_) | RawValue::Array(_) => {
+            json::from_str(value.raw_str()).unwrap_or_else(|_| json::Value::String(value.raw_str().into()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,99 @@
+use super::*;
+
+use crate::model::{Parser, ParserSettings, RawRecord};
+
+fn parse(s: &str) -> Match {
+    let raw = RawRecord::parser().parse(s.as_bytes()).next().unwrap().unwrap().record;
+    let parser = Parser::new(ParserSettings::default());
+    Match::new(&parser.parse(&raw))
+}
+
+fn record(message: &str) -> Match {
+    Match {
+        timestamp: None,
+        level: None,
+        logger: None,
+        message: Some(message.into()),
+        fields: json::Map::new(),
+    }
+}
+
+fn alert(count: usize, message: &str) -> Option<Alert> {
+    Some(Alert {
+        count,
+        record: record(message),
+    })
+}
+
+#[test]
+fn test_match() {
+    let record = parse(
+        r#"{"ts":"2024-01-01T00:00:00Z","level":"error","msg":"failed \"x\"","code":5,"ok":false,"ctx":{"id":"a"},"user.name":"b"}"#,
+    );
+    assert_eq!(record.timestamp.as_deref(), Some("2024-01-01T00:00:00Z"));
+    assert_eq!(record.level, Some(Level::Error));
+    assert_eq!(record.message.as_deref(), Some(r#"failed "x""#));
+
+    let alert = Alert { count: 2, record };
+    assert_eq!(
+        json::to_value(&alert).unwrap(),
+        json::json!({
+            "count": 2,
+            "timestamp": "2024-01-01T00:00:00Z",
+            "level": "error",
+            "message": "failed \"x\"",
+            "fields": {"code": 5, "ok": false, "ctx": {"id": "a"}, "user.name": "b"},
+        })
+    );
+
+    let vars = alert.vars();
+    let var = |key: &str| vars.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+    assert_eq!(var("HL_COUNT"), Some("2"));
+    assert_eq!(var("HL_LEVEL"), Some("error"));
+    assert_eq!(var("HL_MESSAGE"), Some(r#"failed "x""#));
+    assert_eq!(var("HL_LOGGER"), None);
+    assert_eq!(var("HL_FIELD_CODE"), Some("5"));
+    assert_eq!(var("HL_FIELD_CTX"), Some(r#"{"id":"a"}"#));
+    assert_eq!(var("HL_FIELD_USER_NAME"), Some("b"));
+}
+
+#[test]
+fn test_debounce() {
+    let s = Duration::from_secs(1);
+    let t0 = Instant::now();
+    let mut alerter = Alerter::new(s, Duration::ZERO);
+    assert_eq!(alerter.deadline(), None);
+    assert_eq!(alerter.poll(t0), None);
+
+    alerter.add(record("a"), t0);
+    alerter.add(record("b"), t0 + s / 2);
+    assert_eq!(alerter.deadline(), Some(t0 + s));
+    assert_eq!(alerter.poll(t0 + s / 2), None);
+
+    // a burst lasting longer than the debounce period is not postponed forever
+    alerter.add(record("c"), t0 + s * 9 / 10);
+    assert_eq!(alerter.poll(t0 + s), alert(3, "a"));
+    assert_eq!(alerter.poll(t0 + s * 2), None);
+
+    alerter.add(record("d"), t0 + s * 2);
+    assert_eq!(alerter.poll(t0 + s * 2), None);
+    assert_eq!(alerter.flush(), alert(1, "d"));
+    assert_eq!(alerter.deadline(), None);
+}
+
+#[test]
+fn test_interval() {
+    let s = Duration::from_secs(1);
+    let t0 = Instant::now();
+    let mut alerter = Alerter::new(s, s * 10);
+
+    alerter.add(record("a"), t0);
+    assert_eq!(alerter.poll(t0 + s), alert(1, "a"));
+
+    // the next alert waits until the interval passes since the previous one
+    alerter.add(record("b"), t0 + s * 2);
+    assert_eq!(alerter.deadline(), Some(t0 + s * 11));
+    assert_eq!(alerter.poll(t0 + s * 5), None);
+    alerter.add(record("c"), t0 + s * 6);
+    assert_eq!(alerter.poll(t0 + s * 11), alert(2, "b"));
+}
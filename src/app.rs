@@ -1,7 +1,8 @@
 // std imports
 use std::{
+    cell::Cell,
     cmp::{Reverse, max},
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque, vec_deque},
     convert::{TryFrom, TryInto},
     fs,
     io::{BufWriter, Write},
@@ -10,8 +11,8 @@ use std::{
     path::PathBuf,
     rc::Rc,
     str,
-    sync::Arc,
-    time::{Duration, Instant},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant, SystemTime},
 };
 
 // unix-only std imports
@@ -19,6 +20,7 @@ use std::{
 use std::os::unix::fs::MetadataExt;
 
 // third-party imports
+use chrono::{DateTime, Offset, TimeZone};
 use closure::closure;
 use crossbeam_channel::{self as channel, Receiver, RecvTimeoutError, Sender};
 use crossbeam_utils::thread;
@@ -32,22 +34,44 @@ use unicode_width::UnicodeWidthStr;
 // local imports
 use crate::{
     ExactIncludeExcludeKeyFilter, IncludeExcludeKeyFilter,
+    alert::{Alert, Alerter, Match},
     appdirs::AppDirs,
+    command::Command,
+    container::Wrapper,
     datefmt::{DateTimeFormat, DateTimeFormatter},
     error::*,
+    extsort::{Entry, SortKey, Sorted, Sorter},
     filtering::{MatchOptions, NoNormalizing},
     fmtx::{Adjustment, Alignment, Padding, aligned},
     formatting::{
         DynRecordWithSourceFormatter, Expansion, RawRecordFormatter, RecordFormatterBuilder, RecordWithSourceFormatter,
+        StructuredRecordFormatter, Template,
     },
-    fsmon::{self, EventKind},
+    fsmon::{self, EventKind, Flow, ModifyKind},
     help,
-    index::{Indexer, IndexerSettings, Timestamp},
-    input::{BlockEntry, Input, InputHolder, InputReference},
-    model::{Filter, Parser, ParserSettings, RawRecord, Record, RecordFilter, RecordWithSourceConstructor},
+    histogram::{Histogram, HistogramFormatter},
+    index::{
+        Indexer, IndexerSettings, SourceBlock, Timestamp,
+        cache::{Cache as IndexCache, Limits as IndexCacheLimits},
+    },
+    input::{BlockEntry, IndexedInput, Input, InputDir, InputHolder, InputPath, InputReference, Stream},
+    listen::{Connection, Endpoint, Listener},
+    model::{
+        EmbeddedJsonExpansion, FieldMembership, FieldValueExtractor, Filter, IndexedFields, Parser, ParserSettings,
+        RawRecord, RawRecordParser, Record, RecordFilter, RecordWithSource, RecordWithSourceConstructor,
+    },
     query::Query,
-    scanning::{BufFactory, Delimit, Delimiter, Newline, Scanner, SearchExt, Segment, SegmentBuf, SegmentBufFactory},
-    settings::{AsciiMode, ExpansionMode, FieldShowOption, Fields, Formatting, InputInfo, ResolvedPunctuation},
+    resume::{Progress, Resume},
+    scanning::{
+        BufFactory, Delimit, Delimiter, Newline, PartialPlacement, Scanner, SearchExt, Segment, SegmentBuf,
+        SegmentBufFactory,
+    },
+    settings::{
+        AsciiMode, ExpansionMode, FieldShowOption, Fields, Formatting, InputInfo, ResolvedPunctuation, StatusLevels,
+    },
+    signal,
+    stats::{Summary, SummaryFormatter},
+    textformat::TextFormat,
     theme::{Element, StylingPush, SyncIndicatorPack, Theme},
     themecfg,
     timezone::Tz,
@@ -77,16 +101,37 @@ pub struct Options {
     pub max_message_size: NonZeroUsize,
     pub concurrency: usize,
     pub filter: Arc<AdvancedFilter>,
+    pub context: ContextOptions,
+    pub correlate_by: Option<String>,
+    pub stats: Option<StatsOptions>,
+    pub histogram: Option<HistogramOptions>,
     pub fields: FieldOptions,
     pub formatting: Formatting,
     pub time_zone: Tz,
     pub hide_empty_fields: bool,
     pub sort: bool,
+    pub reverse: bool,
+    pub max_count: Option<usize>,
+    pub last: Option<usize>,
+    pub sort_by: Option<SortBy>,
     pub follow: bool,
+    pub follow_dirs: Vec<InputDir>,
+    pub listen: Vec<Endpoint>,
+    pub restart: bool,
+    pub resume: Option<String>,
+    pub alert: Option<AlertOptions>,
     pub sync_interval: Duration,
     pub input_info: InputInfoSet,
     pub input_format: Option<InputFormat>,
+    pub status_levels: StatusLevels,
+    pub text_formats: Arc<[TextFormat]>,
+    pub unwrap: Option<Wrapper>,
+    pub embedded_json: EmbeddedJsonExpansion,
+    pub output_format: Option<OutputFormat>,
+    pub template: Option<Arc<Template>>,
     pub dump_index: bool,
+    pub indexed_fields: Vec<String>,
+    pub index_cache_limits: IndexCacheLimits,
     pub app_dirs: Option<AppDirs>,
     pub tail: u64,
     pub delimiter: Delimiter,
@@ -123,11 +168,72 @@ impl Options {
         Self { sort, ..self }
     }
 
+    #[cfg(test)]
+    fn with_reverse(self, reverse: bool) -> Self {
+        Self { reverse, ..self }
+    }
+
+    #[cfg(test)]
+    fn with_sort_by(self, key: &str, descending: bool) -> Self {
+        Self {
+            sort_by: Some(SortBy {
+                key: key.to_owned(),
+                descending,
+            }),
+            ..self
+        }
+    }
+
     #[cfg(test)]
     fn with_filter(self, filter: Arc<AdvancedFilter>) -> Self {
         Self { filter, ..self }
     }
 
+    #[cfg(test)]
+    fn with_context(self, context: ContextOptions) -> Self {
+        Self { context, ..self }
+    }
+
+    #[cfg(test)]
+    fn with_correlate_by(self, key: &str) -> Self {
+        Self {
+            correlate_by: Some(key.to_owned()),
+            ..self
+        }
+    }
+
+    #[cfg(test)]
+    fn with_output_format(self, output_format: OutputFormat) -> Self {
+        Self {
+            output_format: Some(output_format),
+            ..self
+        }
+    }
+
+    #[cfg(test)]
+    fn with_stats(self, stats: StatsOptions) -> Self {
+        Self {
+            stats: Some(stats),
+            ..self
+        }
+    }
+
+    #[cfg(test)]
+    fn with_histogram(self, histogram: HistogramOptions) -> Self {
+        Self {
+            histogram: Some(histogram),
+            ..self
+        }
+    }
+
+    #[cfg(test)]
+    fn with_template(self, template: &str) -> Self {
+        Self {
+            template: Some(Arc::new(Template::parse(template).unwrap())),
+            ..self
+        }
+    }
+
     #[cfg(test)]
     fn with_input_info(self, input_info: InputInfoSet) -> Self {
         Self { input_info, ..self }
@@ -160,6 +266,11 @@ impl RecordFilter for AdvancedFilter {
     fn apply(&self, record: &Record) -> bool {
         self.basic.apply(record) && self.query.apply(record)
     }
+
+    #[inline]
+    fn may_match(&self, fields: &dyn FieldMembership) -> bool {
+        self.basic.may_match(fields) && self.query.may_match(fields)
+    }
 }
 
 impl From<&Arc<AdvancedFilter>> for Query {
@@ -184,6 +295,61 @@ impl From<Filter> for Arc<AdvancedFilter> {
     }
 }
 
+/// Number of entries rejected by the filter to display around each matching entry.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContextOptions {
+    pub before: usize,
+    pub after: usize,
+}
+
+impl ContextOptions {
+    pub fn is_empty(&self) -> bool {
+        self.before == 0 && self.after == 0
+    }
+}
+
+/// Defines what is shown in the summary printed instead of entries.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct StatsOptions {
+    /// Keys of the fields to show top values for.
+    pub fields: Vec<String>,
+    /// Maximum number of top loggers and top values of each field to show.
+    pub top: usize,
+}
+
+/// Defines how the histogram printed instead of entries looks like.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct HistogramOptions {
+    /// Time span of each bar, chosen automatically if not specified.
+    pub bucket: Option<Duration>,
+    /// Desired total width of the histogram in columns.
+    pub width: usize,
+}
+
+/// Defines what is done when entries matching the query appear in follow mode.
+#[derive(Clone)]
+pub struct AlertOptions {
+    /// Query the displayed entries are matched against.
+    pub query: Query,
+    /// Command to run with the first matching entry of a burst passed in its environment and as JSON on its stdin.
+    pub command: Option<Command>,
+    /// Ring the terminal bell.
+    pub bell: bool,
+    /// Period without more matching entries after which a burst is considered finished.
+    pub debounce: Duration,
+    /// Minimum interval between alerts.
+    pub interval: Duration,
+}
+
+/// Defines the field to sort entries by instead of their timestamps.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct SortBy {
+    /// Key of the field, addressed in the same way as in field filters.
+    pub key: String,
+    /// Sort entries with greater values first.
+    pub descending: bool,
+}
+
 #[derive(Default)]
 pub struct FieldOptions {
     pub filter: Arc<IncludeExcludeKeyFilter>,
@@ -194,6 +360,14 @@ pub struct FieldOptions {
 pub enum InputFormat {
     Json,
     Logfmt,
+    Syslog,
+    AccessLog,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum OutputFormat {
+    Json,
+    Logfmt,
 }
 
 // ---
@@ -263,32 +437,113 @@ pub fn list_themes(
 
 // ---
 
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum IndexCacheAction {
+    List,
+    Prune,
+    Clear,
+}
+
+/// Lists cached indexes or removes them.
+pub fn manage_index_cache(dirs: &AppDirs, action: IndexCacheAction, mut output: impl Write) -> Result<()> {
+    let cache = IndexCache::new(dirs.cache_dir.clone());
+    let removed = match action {
+        IndexCacheAction::List => {
+            let entries = cache.entries()?;
+            for entry in &entries {
+                let used: DateTime<chrono::Local> = entry.used.into();
+                let source = match entry.source() {
+                    Some(path) => path.display().to_string(),
+                    None => "<invalid>".into(),
+                };
+                writeln!(
+                    output,
+                    "{:>10}  {}  {}",
+                    bytefmt::format(entry.size),
+                    used.format("%Y-%m-%d %H:%M"),
+                    source
+                )?;
+            }
+            let total = entries.iter().map(|entry| entry.size).sum();
+            writeln!(output, "{} indexes, {} total", entries.len(), bytefmt::format(total))?;
+            return Ok(());
+        }
+        IndexCacheAction::Prune => cache.prune()?,
+        IndexCacheAction::Clear => cache.clear()?,
+    };
+    writeln!(
+        output,
+        "removed {} indexes, {}",
+        removed.count,
+        bytefmt::format(removed.size)
+    )?;
+    Ok(())
+}
+
+// ---
+
 pub struct App {
     options: Options,
     punctuation: Arc<ResolvedPunctuation>,
     formatter: DynRecordWithSourceFormatter,
+    context_formatter: Option<DynRecordWithSourceFormatter>,
+    filter: Query,
+    indexed_fields: IndexedFields,
 }
 
 impl App {
+    /// Size of formatted entries kept in memory when sorting by a field before they are spilled to a temporary file.
+    const SORT_RUN_SIZE: usize = 64 << 20;
+
     pub fn new(mut options: Options) -> Self {
         if options.raw && options.input_info.intersects(InputInfo::None | InputInfo::Auto) {
             options.input_info = InputInfo::None.into()
         }
+        // structured output has to consist of the records only, so there is no place for input badges
+        if options.output_format.is_some() {
+            options.input_info = InputInfo::None.into()
+        }
         options.input_info = InputInfo::resolve(options.input_info);
 
         let punctuation = Arc::new(options.formatting.punctuation.resolve(options.ascii));
 
-        let formatter = Self::new_formatter(&options, punctuation.clone());
+        let formatter = Self::new_formatter(&options, options.theme.clone(), punctuation.clone());
+        let context_formatter = (!options.context.is_empty() && !options.filter.is_empty())
+            .then(|| Self::new_formatter(&options, Arc::new(options.theme.faded()), punctuation.clone()));
+
+        let filter = Query::from(&options.filter);
+        let filter = match &options.correlate_by {
+            Some(key) => {
+                // without a preliminary pass, values are collected as entries go, possibly for days in follow mode
+                let preliminary = options.stats.is_some()
+                    || options.histogram.is_some()
+                    || (options.sort_by.is_none() && !options.follow && options.sort);
+                let limit = (!preliminary).then_some(MAX_CORRELATED_VALUES);
+                Query::new(CorrelationFilter::new(filter, FieldValueExtractor::new(key), limit))
+            }
+            None => filter,
+        };
+
+        let indexed_fields = IndexedFields::new(&options.indexed_fields);
 
         Self {
             options,
             punctuation,
             formatter,
+            context_formatter,
+            filter,
+            indexed_fields,
         }
     }
 
     pub fn run(&self, inputs: Vec<InputHolder>, output: &mut Output) -> Result<()> {
-        if self.options.follow {
+        if let Some(stats) = &self.options.stats {
+            self.stats(inputs, output, stats)
+        } else if let Some(histogram) = &self.options.histogram {
+            self.histogram(inputs, output, histogram)
+        } else if let Some(sort_by) = &self.options.sort_by {
+            self.sort_by(inputs, output, sort_by)
+        } else if self.options.follow {
             self.follow(inputs.into_iter().map(|x| x.reference).collect(), output)
         } else if self.options.sort {
             self.sort(inputs, output)
@@ -300,12 +555,17 @@ impl App {
     fn cat(&self, inputs: Vec<InputHolder>, output: &mut Output) -> Result<()> {
         let input_badges = self.input_badges(inputs.iter().map(|x| &x.reference));
 
+        let resume = self.resume()?;
         let inputs = inputs
             .into_iter()
-            .map(|x| x.open())
+            .enumerate()
+            .map(|(i, x)| -> std::io::Result<Input> {
+                let (input, _) = self.tracked(i, x.open()?, resume.as_ref())?;
+                Ok(input.unwrapped(self.options.unwrap, false))
+            })
             .collect::<std::io::Result<Vec<_>>>()?;
 
-        let n = self.options.concurrency;
+        let n = self.processing_threads();
         let sfi = Arc::new(SegmentBufFactory::new(self.options.buffer_size.into()));
         let bfo = BufFactory::new(self.options.buffer_size.into());
         let parser = self.parser();
@@ -313,7 +573,9 @@ impl App {
             // prepare receive/transmit channels for input data
             let (txi, rxi): (Vec<_>, Vec<_>) = (0..n).map(|_| channel::bounded(1)).unzip();
             // prepare receive/transmit channels for output data
-            let (txo, rxo): (Vec<_>, Vec<_>) = (0..n).map(|_| channel::bounded::<(usize, SegmentBuf)>(1)).unzip();
+            let (txo, rxo): (Vec<_>, Vec<_>) = (0..n)
+                .map(|_| channel::bounded::<(usize, SegmentBuf, RecordEnds)>(1))
+                .unzip();
             // spawn reader thread
             let reader = scope.spawn(closure!(clone sfi, |_| -> Result<()> {
                 let mut tx = StripedSender::new(txi);
@@ -336,14 +598,16 @@ impl App {
                         match segment {
                             Segment::Complete(segment) => {
                                 let mut buf = bfo.new_buf();
-                                processor.process(segment.data(), &mut buf, prefix, None, &mut RecordIgnorer{});
+                                let mut ends = RecordEnds::default();
+                                processor.switch_input(i);
+                                processor.process(segment.data(), &mut buf, prefix, None, &mut ends);
                                 sfi.recycle(segment);
-                                if txo.send((i, buf.into())).is_err() {
+                                if txo.send((i, buf.into(), ends)).is_err() {
                                     break;
                                 };
                             }
                             Segment::Incomplete(segment, _) => {
-                                if txo.send((i, segment)).is_err() {
+                                if txo.send((i, segment, RecordEnds::default())).is_err() {
                                     break;
                                 }
                             }
@@ -353,7 +617,35 @@ impl App {
             }
             // spawn writer thread
             let writer = scope.spawn(closure!(ref bfo, |_| -> Result<()> {
-                for (_, buf) in StripedReceiver::new(rxo) {
+                let mut remaining = self.options.max_count.unwrap_or(usize::MAX);
+                // context entries after the last counted entry may continue in the following segments of its input
+                let trailing = matches!(self.context_mode(), ContextMode::Inline { options, .. } if options.after != 0);
+                let mut last = None;
+                for (i, buf, ends) in StripedReceiver::new(rxo) {
+                    if let Some(input) = last {
+                        let Some(end) = ends.leading_context.filter(|_| i == input) else {
+                            break;
+                        };
+                        output.write_all(&buf.data()[..end])?;
+                        output.write_all(self.options.output_delimiter.as_bytes())?;
+                        if !ends.ends.is_empty() {
+                            break;
+                        }
+                        continue;
+                    }
+                    if ends.ends.len() >= remaining {
+                        // dropping the receiver stops the other threads as well
+                        if let Some(k) = remaining.checked_sub(1) {
+                            output.write_all(&buf.data()[..ends.ends[k]])?;
+                            output.write_all(self.options.output_delimiter.as_bytes())?;
+                            if trailing && k + 1 == ends.ends.len() {
+                                last = Some(i);
+                                continue;
+                            }
+                        }
+                        break;
+                    }
+                    remaining -= ends.ends.len();
                     output.write_all(buf.data())?;
                     bfo.recycle(buf.into_inner());
                 }
@@ -366,42 +658,131 @@ impl App {
         })
         .unwrap()?;
 
+        if let Some(resume) = &resume {
+            resume.finish()?;
+        }
+
         Ok(())
     }
 
-    fn sort(&self, inputs: Vec<InputHolder>, output: &mut Output) -> Result<()> {
-        let mut output = BufWriter::new(output);
-        let indexer_settings = IndexerSettings {
-            buffer_size: self.options.buffer_size.try_into()?,
-            max_message_size: self.options.max_message_size.try_into()?,
-            fields: &self.options.fields.settings.predefined,
-            delimiter: self.options.delimiter.clone(),
-            allow_prefix: self.options.allow_prefix,
-            unix_ts_unit: self.options.unix_ts_unit,
-            format: self.options.input_format,
-            ..IndexerSettings::with_fs(LocalFileSystem)
-        };
-        let param_hash = hex::encode(indexer_settings.hash()?);
-        let cache_dir = self
-            .options
-            .app_dirs
-            .as_ref()
-            .map(|dirs| dirs.cache_dir.clone())
-            .unwrap_or_else(|| PathBuf::from(".cache"))
-            .join(param_hash);
-        fs::create_dir_all(&cache_dir)?;
-
-        let indexer = Indexer::new(self.options.concurrency, cache_dir, indexer_settings);
+    fn sort_by(&self, inputs: Vec<InputHolder>, output: &mut Output, sort_by: &SortBy) -> Result<()> {
         let input_badges = self.input_badges(inputs.iter().map(|x| &x.reference));
 
         let inputs = inputs
             .into_iter()
-            .map(|x| x.index(&indexer, self.options.delimiter.clone()))
-            .collect::<Result<Vec<_>>>()?;
+            .map(|x| x.open().map(|input| input.unwrapped(self.options.unwrap, false)))
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        let n = self.processing_threads();
+        let sfi = Arc::new(SegmentBufFactory::new(self.options.buffer_size.into()));
+        let bfo = BufFactory::new(self.options.buffer_size.into());
+        let parser = self.parser();
+        let extractor = &FieldValueExtractor::new(&sort_by.key);
+        let sorted = thread::scope(|scope| -> Result<Sorted> {
+            // prepare receive/transmit channels for input data
+            let (txi, rxi): (Vec<_>, Vec<_>) = (0..n).map(|_| channel::bounded(1)).unzip();
+            // prepare receive/transmit channels for output data
+            let (txo, rxo): (Vec<_>, Vec<_>) = (0..n).map(|_| channel::bounded(1)).unzip();
+            // spawn reader thread
+            let reader = scope.spawn(closure!(clone sfi, |_| -> Result<()> {
+                let mut tx = StripedSender::new(txi);
+                let scanner = Scanner::new(sfi, &self.options.delimiter);
+                for (i, mut input) in inputs.into_iter().enumerate() {
+                    let items = scanner
+                        .items(&mut input.stream.as_sequential())
+                        .with_max_segment_size(self.options.max_message_size.into());
+                    for item in items {
+                        if tx.send((i, item?)).is_none() {
+                            break;
+                        }
+                    }
+                }
+                Ok(())
+            }));
+            // spawn processing threads
+            for (rxi, txo) in izip!(rxi, txo) {
+                scope.spawn(closure!(ref bfo, ref parser, ref sfi, ref input_badges, |_| {
+                    let mut processor = self.new_segment_processor(parser);
+                    for (i, segment) in rxi.iter() {
+                        let mut buf = bfo.new_buf();
+                        let (items, placement) = match segment {
+                            Segment::Complete(segment) => {
+                                let prefix = input_badges.as_ref().map(|b| b[i].as_str()).unwrap_or("");
+                                let mut collector = SortKeyCollector::new(extractor);
+                                processor.switch_input(i);
+                                processor.process(segment.data(), &mut buf, prefix, None, &mut collector);
+                                sfi.recycle(segment);
+                                (collector.items, None)
+                            }
+                            Segment::Incomplete(segment, placement) => {
+                                buf.extend_from_slice(segment.data());
+                                sfi.recycle(segment);
+                                (Vec::new(), Some(placement))
+                            }
+                        };
+                        if txo.send((buf, items, placement)).is_err() {
+                            break;
+                        };
+                    }
+                }));
+            }
+            // spawn collector thread
+            let collector = scope.spawn(closure!(ref bfo, |_| -> Result<Sorted> {
+                let mut sorter = Sorter::new(sort_by.descending, Self::SORT_RUN_SIZE, std::env::temp_dir());
+                let delim = self.options.delimiter.clone().into_searcher();
+                let mut partial = Vec::new();
+                for (buf, items, placement) in StripedReceiver::new(rxo) {
+                    for (key, ts, location) in items {
+                        let data = buf[location].to_vec();
+                        sorter.push(Entry { key, ts, data })?;
+                    }
+                    // incomplete segments are not parsed, so their lines are output as is with no sort key
+                    if let Some(placement) = placement {
+                        partial.extend_from_slice(&buf);
+                        if placement == PartialPlacement::Last {
+                            Self::push_unparsed(&mut sorter, &delim, &partial)?;
+                            partial.clear();
+                        }
+                    }
+                    bfo.recycle(buf);
+                }
+                Self::push_unparsed(&mut sorter, &delim, &partial)?;
+                Ok(sorter.finish()?)
+            }));
+            // collect errors from reader and collector threads
+            reader.join().unwrap()?;
+            collector.join().unwrap()
+        })
+        .unwrap()?;
+
+        for entry in sorted.take(self.options.max_count.unwrap_or(usize::MAX)) {
+            output.write_all(&entry?.data)?;
+            output.write_all(self.options.output_delimiter.as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Pushes the lines of the unparsed data with no sort key, so that they go last.
+    fn push_unparsed<S: SearchExt>(sorter: &mut Sorter, delim: &S, data: &[u8]) -> Result<()> {
+        for line in delim.split(data).filter(|line| !line.is_empty()) {
+            sorter.push(Entry {
+                key: None,
+                ts: None,
+                data: line.to_vec(),
+            })?;
+        }
+        Ok(())
+    }
+
+    fn sort(&self, inputs: Vec<InputHolder>, output: &mut Output) -> Result<()> {
+        let mut output = BufWriter::new(output);
+        let input_badges = self.input_badges(inputs.iter().map(|x| &x.reference));
+        let inputs = self.index(inputs)?;
 
         if self.options.dump_index {
             for input in inputs {
-                for block in input.into_blocks().sorted() {
+                for block in input.blocks().sorted() {
                     writeln!(output, "block at {} with size {}", block.offset(), block.size())?;
                     writeln!(output, "{:#?}", block.source_block())?;
                     let block_offset = block.offset();
@@ -421,6 +802,17 @@ impl App {
 
         let n = self.options.concurrency;
         let parser = self.parser();
+        if self.options.correlate_by.is_some() {
+            self.collect_correlated_values(&inputs, &parser)?;
+        }
+
+        // blocks with no matching entries may still contain context or correlated entries
+        let prune = self.context_formatter.is_none() && self.options.correlate_by.is_none();
+        // the last entries are selected in the opposite order and then written reversed
+        let last = self.options.last.is_some();
+        let reverse = self.options.reverse != last;
+        let limit = self.options.last.or(self.options.max_count);
+
         thread::scope(|scope| -> Result<()> {
             // prepare transmit/receive channels for data produced by pusher thread
             let (txp, rxp): (Vec<_>, Vec<_>) = (0..n).map(|_| channel::bounded(1)).unzip();
@@ -433,41 +825,31 @@ impl App {
                 let mut blocks: Vec<_> = inputs
                     .into_iter()
                     .enumerate()
-                    .flat_map(|(i, input)| input.into_blocks().map(move |block| (block, i)))
+                    .flat_map(|(i, input)| input.blocks().map(move |block| (block, i)))
                     .filter_map(|(block, i)| {
                         let src = block.source_block();
                         if src.stat.entries_valid == 0 {
                             return None;
                         }
-                        if let Some((ts_min, ts_max)) = src.stat.ts_min_max {
-                            if let Some(until) = self.options.filter.basic.until {
-                                if ts_min > until.into() {
-                                    return None;
-                                }
-                            }
-                            if let Some(since) = self.options.filter.basic.since {
-                                if ts_max < since.into() {
-                                    return None;
-                                }
-                            }
-                            if let Some(level) = self.options.filter.basic.level {
-                                if !src.match_level(level) {
-                                    return None;
-                                }
-                            }
-                            let offset = block.offset();
-                            Some((block, ts_min, ts_max, i, offset))
-                        } else {
-                            None
+                        let (ts_min, ts_max) = src.stat.ts_min_max?;
+                        if prune && !self.block_may_match(src) {
+                            return None;
                         }
+                        let offset = block.offset();
+                        Some((block, ts_min, ts_max, i, offset))
                     })
                     .collect();
 
-                blocks.sort_by(|a, b| (a.1, a.2, a.3, a.4).partial_cmp(&(b.1, b.2, b.3, b.4)).unwrap());
+                if reverse {
+                    blocks.sort_by(|a, b| (b.2, b.1, b.3, b.4).partial_cmp(&(a.2, a.1, a.3, a.4)).unwrap());
+                } else {
+                    blocks.sort_by(|a, b| (a.1, a.2, a.3, a.4).partial_cmp(&(b.1, b.2, b.3, b.4)).unwrap());
+                }
 
                 let mut output = StripedSender::new(txp);
-                for (j, (block, ts_min, _, i, _)) in blocks.into_iter().enumerate() {
-                    if output.send((block, ts_min, i, j)).is_none() {
+                for (j, (block, ts_min, ts_max, i, _)) in blocks.into_iter().enumerate() {
+                    let bound = if reverse { ts_max } else { ts_min };
+                    if output.send((block, bound, i, j)).is_none() {
                         break;
                     }
                 }
@@ -478,36 +860,23 @@ impl App {
             for (rxp, txw) in izip!(rxp, txw) {
                 workers.push(scope.spawn(closure!(ref parser, ref input_badges, |_| -> Result<()> {
                     let mut processor = self.new_segment_processor(parser);
-                    for (block, ts_min, i, j) in rxp.iter() {
+                    for (block, bound, i, j) in rxp.iter() {
                         let mut buf = Vec::with_capacity(2 * usize::try_from(block.size())?);
-                        let mut items = Vec::with_capacity(2 * usize::try_from(block.entries_valid())?);
+                        let mut items = OutputBlockItems::new(2 * usize::try_from(block.entries_valid())?);
                         for line in block.into_entries()? {
                             if line.is_empty() {
                                 continue;
                             }
                             let prefix = input_badges.as_ref().map(|b| b[i].as_str()).unwrap_or("");
-                            processor.process(
-                                line.bytes(),
-                                &mut buf,
-                                prefix,
-                                Some(1),
-                                &mut |record: &Record, location: Range<usize>| {
-                                    if let Some(ts) = &record.ts {
-                                        if let Some(unix_ts) = ts.unix_utc() {
-                                            items.push((unix_ts.into(), location));
-                                        } else {
-                                            log::warn!(
-                                                "skipped a message because its timestamp could not be parsed: {:#?}",
-                                                ts.raw()
-                                            )
-                                        }
-                                    }
-                                },
-                            );
+                            processor.process(line.bytes(), &mut buf, prefix, Some(1), &mut items);
                         }
 
                         let buf = Arc::new(buf);
-                        if txw.send((OutputBlock { ts_min, buf, items }, i, j)).is_err() {
+                        let mut items = items.items;
+                        if reverse {
+                            items.reverse();
+                        }
+                        if txw.send((OutputBlock { bound, buf, items }, i, j)).is_err() {
                             break;
                         }
                     }
@@ -520,16 +889,50 @@ impl App {
                 let (mut tsi, mut tso) = (None, None);
                 let mut workspace = Vec::new();
                 let mut done = false;
+                let context_options = if last {
+                    ContextOptions {
+                        before: self.options.context.after,
+                        after: self.options.context.before,
+                    }
+                } else {
+                    self.options.context
+                };
+                let mut context = self
+                    .context_formatter
+                    .as_ref()
+                    .map(|_| ContextWindow::new(context_options));
+                let delim = self.options.output_delimiter.as_bytes();
+                let mut remaining = limit.unwrap_or(usize::MAX);
+                let mut held = Vec::new();
+                let mut write = |data: &[u8]| -> std::io::Result<()> {
+                    if last {
+                        held.push(data.to_vec());
+                    } else {
+                        output.write_all(data)?;
+                        output.write_all(delim)?;
+                    }
+                    Ok(())
+                };
 
                 // Workspace rules
                 // 1. Can process messages up to max `ts_min` of the blocks in workspace
                 // 2. Can process any messages if workspace is complete (has all remaining blocks)
                 // 3. Should be sorted by (head (next line timestamp), input, block number, offset)
+                // In reverse order, the rules are mirrored, so `ts_max` of the blocks is used as the bound
+                // and messages are processed down to it starting from the latest head.
+                let behind = |tso: Option<Timestamp>, tsi: Option<Timestamp>| {
+                    if reverse { tso <= tsi } else { tso >= tsi }
+                };
 
                 loop {
-                    while tso >= tsi || workspace.is_empty() {
+                    // context entries after the last counted entry are displayed up to the next matching entry
+                    if remaining == 0 && !context.as_ref().is_some_and(|context| context.pending()) {
+                        break;
+                    }
+
+                    while behind(tso, tsi) || workspace.is_empty() {
                         if let Some((block, i, j)) = input.next() {
-                            tsi = Some(block.ts_min);
+                            tsi = Some(block.bound);
                             tso = tso.or(tsi);
                             let mut tail = block.into_lines();
                             let head = tail.next();
@@ -546,22 +949,60 @@ impl App {
                         break;
                     }
 
-                    workspace.sort_by_key(|v| Reverse(((v.0).0, v.2, v.3, (v.0).1.offset())));
+                    if reverse {
+                        workspace.sort_by_key(|v| ((v.0).0, v.2, v.3, (v.0).1.offset()));
+                    } else {
+                        workspace.sort_by_key(|v| Reverse(((v.0).0, v.2, v.3, (v.0).1.offset())));
+                    }
                     let k = workspace.len() - 1;
                     let item = &mut workspace[k];
                     let ts = (item.0).0;
                     tso = Some(ts);
-                    if tso >= tsi && !done {
+                    if behind(tso, tsi) && !done {
                         continue;
                     }
-                    output.write_all((item.0).1.bytes())?;
-                    output.write_all(self.options.output_delimiter.as_bytes())?;
+                    let (_, line, is_context) = &item.0;
+                    match &mut context {
+                        None => {
+                            write(line.bytes())?;
+                            remaining -= 1;
+                        }
+                        Some(_) if remaining == 0 && !*is_context => {
+                            break;
+                        }
+                        Some(context) if *is_context => match context.rejected() {
+                            Some(ContextPlacement::After) => {
+                                write(line.bytes())?;
+                            }
+                            Some(ContextPlacement::Before) => {
+                                context.hold(line.clone());
+                            }
+                            None => {}
+                        },
+                        Some(context) => {
+                            let (separate, lines) = context.matched();
+                            if let Some(separator) = self.context_separator().filter(|_| separate) {
+                                let prefix = input_badges.as_ref().map(|b| b[item.2].as_str()).unwrap_or("");
+                                write(&[prefix.as_bytes(), separator.as_bytes()].concat())?;
+                            }
+                            for line in lines {
+                                write(line.bytes())?;
+                            }
+                            write(line.bytes())?;
+                            remaining -= 1;
+                        }
+                    }
                     match item.1.next() {
                         Some(head) => item.0 = head,
                         None => drop(workspace.swap_remove(k)),
                     }
                 }
 
+                for data in held.iter().rev() {
+                    output.write_all(data)?;
+                    output.write_all(delim)?;
+                }
+
                 Ok(())
             });
 
@@ -578,8 +1019,329 @@ impl App {
         Ok(())
     }
 
+    /// Runs the filter over all entries that may match it, so that the correlation filter
+    /// knows all correlated values before the entries are processed in the sorted order.
+    fn collect_correlated_values(&self, inputs: &[Arc<IndexedInput>], parser: &Parser) -> Result<()> {
+        let n = self.options.concurrency;
+        thread::scope(|scope| -> Result<()> {
+            let (tx, rx) = channel::bounded(n);
+            let workers = (0..n)
+                .map(|_| {
+                    scope.spawn(closure!(clone rx, |_| -> Result<()> {
+                        let mut buf = Vec::new();
+                        for block in rx.iter() {
+                            for entry in block.into_entries()? {
+                                let mut stream = self.raw_parser().parse(entry.bytes());
+                                while let Some(Ok(ar)) = stream.next() {
+                                    let record = self.options.embedded_json.expand(ar.record, &mut buf);
+                                    self.filter.apply(&parser.parse(&record));
+                                }
+                            }
+                        }
+                        Ok(())
+                    }))
+                })
+                .collect_vec();
+            drop(rx);
+
+            for block in inputs.iter().flat_map(|input| input.blocks()) {
+                let src = block.source_block();
+                if src.stat.entries_valid == 0 || !self.block_may_match(src) {
+                    continue;
+                }
+                if tx.send(block).is_err() {
+                    break;
+                }
+            }
+            drop(tx);
+
+            for worker in workers {
+                worker.join().unwrap()?;
+            }
+
+            Ok(())
+        })
+        .unwrap()
+    }
+
+    /// Checks whether the block may contain entries matching the filter, judging by its index.
+    fn block_may_match(&self, src: &SourceBlock) -> bool {
+        let filter = &self.options.filter.basic;
+        if let Some((ts_min, ts_max)) = src.stat.ts_min_max {
+            if let Some(until) = filter.until {
+                if ts_min > until.into() {
+                    return false;
+                }
+            }
+            if let Some(since) = filter.since {
+                if ts_max < since.into() {
+                    return false;
+                }
+            }
+        }
+        if let Some(level) = filter.level {
+            if !src.match_level(level) {
+                return false;
+            }
+        }
+        // values of fields found in embedded JSON are not indexed
+        if matches!(self.options.embedded_json, EmbeddedJsonExpansion::Disabled)
+            && !self.options.filter.may_match(&src.fields(&self.indexed_fields))
+        {
+            return false;
+        }
+        true
+    }
+
+    /// Builds indexes of the inputs and prints a line for each of them.
+    pub fn build_indexes(&self, inputs: Vec<InputHolder>, output: &mut Output) -> Result<()> {
+        for input in self.index(inputs)? {
+            let source = input.index.source();
+            writeln!(
+                output,
+                "{}: {} entries in {} blocks",
+                input.reference.description(),
+                source.stat.entries_valid,
+                source.blocks.len()
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Builds or loads indexes of the inputs.
+    fn index(&self, inputs: Vec<InputHolder>) -> Result<Vec<Arc<IndexedInput>>> {
+        let indexer_settings = IndexerSettings {
+            buffer_size: self.options.buffer_size.try_into()?,
+            max_message_size: self.options.max_message_size.try_into()?,
+            fields: &self.options.fields.settings.predefined,
+            delimiter: self.options.delimiter.clone(),
+            allow_prefix: self.options.allow_prefix,
+            unix_ts_unit: self.options.unix_ts_unit,
+            format: self.options.input_format,
+            status_levels: self.options.status_levels,
+            text_formats: self.options.text_formats.clone(),
+            unwrap: self.options.unwrap,
+            indexed_fields: self.indexed_fields.clone(),
+            ..IndexerSettings::with_fs(LocalFileSystem)
+        };
+        let param_hash = hex::encode(indexer_settings.hash()?);
+        let cache_root = self
+            .options
+            .app_dirs
+            .as_ref()
+            .map(|dirs| dirs.cache_dir.clone())
+            .unwrap_or_else(|| PathBuf::from(".cache"));
+        match IndexCache::new(cache_root.clone()).shrink(&self.options.index_cache_limits, SystemTime::now()) {
+            Ok(removed) if removed.count != 0 => {
+                log::debug!(
+                    "removed {} least recently used indexes, {} bytes",
+                    removed.count,
+                    removed.size
+                )
+            }
+            Ok(_) => {}
+            Err(err) => log::warn!("failed to shrink index cache: {err}"),
+        }
+        let cache_dir = cache_root.join(param_hash);
+        fs::create_dir_all(&cache_dir)?;
+
+        let indexer = Indexer::new(self.options.concurrency, cache_dir, indexer_settings);
+
+        inputs
+            .into_iter()
+            .map(|x| -> Result<_> {
+                let input = x.open()?.unwrapped(self.options.unwrap, false);
+                Ok(Arc::new(input.indexed(&indexer, self.options.delimiter.clone())?))
+            })
+            .collect()
+    }
+
+    /// Prints a summary of the entries matching the filter instead of the entries.
+    fn stats(&self, inputs: Vec<InputHolder>, output: &mut Output, options: &StatsOptions) -> Result<()> {
+        let inputs = self.index(inputs)?;
+        let parser = self.parser();
+        if self.options.correlate_by.is_some() {
+            self.collect_correlated_values(&inputs, &parser)?;
+        }
+
+        let fields = options
+            .fields
+            .iter()
+            .map(|key| FieldValueExtractor::new(key))
+            .collect_vec();
+        let summary = self
+            .accumulate(&inputs, &parser, || SummaryAccumulator {
+                summary: Summary::new(fields.len()),
+                fields: &fields,
+            })?
+            .summary;
+
+        let formatter = SummaryFormatter::new(
+            DateTimeFormatter::new(self.options.time_format.clone(), self.options.time_zone),
+            options.top,
+            &options.fields,
+        );
+        let mut output = BufWriter::new(output);
+        formatter.format(&mut output, &summary)?;
+        output.flush()?;
+
+        Ok(())
+    }
+
+    /// Prints a histogram of the entries matching the filter instead of the entries.
+    ///
+    /// Unless the bucket size is specified, it is chosen based on the time span of the blocks that may match the filter.
+    fn histogram(&self, inputs: Vec<InputHolder>, output: &mut Output, options: &HistogramOptions) -> Result<()> {
+        let inputs = self.index(inputs)?;
+        let parser = self.parser();
+        if self.options.correlate_by.is_some() {
+            self.collect_correlated_values(&inputs, &parser)?;
+        }
+
+        // blocks with no matching entries may still contain correlated entries
+        let prune = self.options.correlate_by.is_none();
+        let filter = &self.options.filter.basic;
+        let span = inputs
+            .iter()
+            .flat_map(|input| input.blocks())
+            .filter_map(|block| {
+                let src = block.source_block();
+                (src.stat.entries_valid != 0 && (!prune || self.block_may_match(src)))
+                    .then_some(src.stat.ts_min_max)
+                    .flatten()
+            })
+            .reduce(|(min1, max1), (min2, max2)| (min1.min(min2), max1.max(max2)))
+            .map(|(ts_min, ts_max)| {
+                (
+                    filter.since.map(|since| max(ts_min, since.into())).unwrap_or(ts_min),
+                    filter.until.map(|until| ts_max.min(until.into())).unwrap_or(ts_max),
+                )
+            });
+
+        let bucket = options.bucket.unwrap_or_else(|| {
+            Histogram::auto_bucket(match span {
+                Some((ts_min, ts_max)) if ts_min < ts_max => ts_max - ts_min,
+                _ => Duration::ZERO,
+            })
+        });
+        let offset = span
+            .and_then(|(ts_min, _)| DateTime::from_timestamp(ts_min.sec, 0))
+            .map(|ts| {
+                self.options
+                    .time_zone
+                    .offset_from_utc_datetime(&ts.naive_utc())
+                    .fix()
+                    .local_minus_utc()
+            })
+            .unwrap_or(0);
+
+        let histogram = self.accumulate(&inputs, &parser, || Histogram::new(bucket, offset))?;
+
+        let formatter = HistogramFormatter::new(
+            self.options.theme.clone(),
+            DateTimeFormatter::new(self.options.time_format.clone(), self.options.time_zone),
+            options.width,
+            self.options.ascii,
+        );
+        let mut output = BufWriter::new(output);
+        formatter.format(&mut output, &histogram)?;
+        output.flush()?;
+
+        Ok(())
+    }
+
+    /// Parses the entries that may match the filter and collects them using accumulators created by `new`, one per worker.
+    ///
+    /// Without filters, blocks having no valid entries are not parsed as their invalid entries are counted by the index.
+    fn accumulate<A, F>(&self, inputs: &[Arc<IndexedInput>], parser: &Parser, new: F) -> Result<A>
+    where
+        A: Accumulator,
+        F: Fn() -> A + Sync,
+    {
+        let unfiltered = self.options.filter.is_empty() && self.options.correlate_by.is_none();
+        // blocks with no matching entries may still contain correlated entries
+        let prune = self.options.correlate_by.is_none();
+        let n = self.options.concurrency;
+
+        thread::scope(|scope| -> Result<A> {
+            let (tx, rx) = channel::bounded(n);
+            let workers = (0..n)
+                .map(|_| {
+                    scope.spawn(closure!(clone rx, ref new, |_| -> Result<A> {
+                        let mut acc = new();
+                        let mut buf = Vec::new();
+                        for block in rx.iter() {
+                            for entry in block.into_entries()? {
+                                let mut stream = self.raw_parser().parse(entry.bytes());
+                                while let Some(item) = stream.next() {
+                                    match item {
+                                        Ok(ar) => {
+                                            let record = self.options.embedded_json.expand(ar.record, &mut buf);
+                                            let record = parser.parse(&record);
+                                            if unfiltered || self.filter.apply(&record) {
+                                                acc.add_valid(&record);
+                                            }
+                                        }
+                                        Err(_) => {
+                                            if unfiltered {
+                                                acc.add_invalid(1);
+                                            }
+                                            break;
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        Ok(acc)
+                    }))
+                })
+                .collect_vec();
+            drop(rx);
+
+            let mut acc = new();
+            for block in inputs.iter().flat_map(|input| input.blocks()) {
+                let src = block.source_block();
+                if src.stat.entries_valid == 0 {
+                    if unfiltered {
+                        acc.add_invalid(src.stat.entries_invalid);
+                    }
+                    continue;
+                }
+                if prune && !self.block_may_match(src) {
+                    continue;
+                }
+                if tx.send(block).is_err() {
+                    break;
+                }
+            }
+            drop(tx);
+
+            for worker in workers {
+                acc.merge(worker.join().unwrap()?);
+            }
+
+            Ok(acc)
+        })
+        .unwrap()
+    }
+
     fn prepare_follow_badges<'a, I: IntoIterator<Item = &'a InputReference>>(&self, inputs: I) -> FollowBadges {
-        let si = SyncIndicator::from(&self.options.theme.indicators.sync);
+        FollowBadges {
+            si: self.sync_indicator(),
+            input: RwLock::new(self.follow_input_badges(inputs)),
+        }
+    }
+
+    /// Returns the indicator of entries written out of order, structured output formats have none.
+    fn sync_indicator(&self) -> SyncIndicator {
+        match self.options.output_format {
+            Some(_) => SyncIndicator::default(),
+            None => SyncIndicator::from(&self.options.theme.indicators.sync),
+        }
+    }
+
+    fn follow_input_badges<'a, I: IntoIterator<Item = &'a InputReference>>(&self, inputs: I) -> Option<Vec<String>> {
+        let si = self.sync_indicator();
 
         let mut badges = self.input_badges(inputs);
         if let Some(badges) = &mut badges {
@@ -588,14 +1350,44 @@ impl App {
             }
         }
 
-        FollowBadges { si, input: badges }
+        badges
     }
 
-    fn follow(&self, inputs: Vec<InputReference>, output: &mut Output) -> Result<()> {
+    fn follow(&self, mut inputs: Vec<InputReference>, output: &mut Output) -> Result<()> {
+        // files found in the followed directories are added after the inputs given explicitly
+        let fixed = inputs.len();
+        let active = Mutex::new(HashSet::new());
+        for dir in &self.options.follow_dirs {
+            for path in dir.files()? {
+                let path = InputPath::resolve(path)?;
+                if Self::followed(&inputs, &path).is_none() {
+                    active.lock().unwrap().insert(path.canonical.clone());
+                    inputs.push(InputReference::File(path));
+                }
+            }
+        }
+
+        let listeners = self
+            .options
+            .listen
+            .iter()
+            .map(|endpoint| endpoint.bind())
+            .collect::<std::io::Result<Vec<_>>>()?;
+
         let badges = self.prepare_follow_badges(inputs.iter());
+        let resume = self.resume()?.map(Arc::new);
+        // committed positions are saved when the process is interrupted, as reading may never end in follow mode
+        let _hook = resume.clone().map(|resume| {
+            signal::on_exit(move || {
+                if let Err(err) = resume.save() {
+                    log::warn!("failed to save resume positions: {}", err);
+                }
+            })
+        });
+        let resume = resume.as_deref();
 
-        let m = inputs.len();
-        let n = self.options.concurrency;
+        let inputs = Mutex::new(inputs);
+        let n = self.processing_threads();
         let parser = self.parser();
         let sfi = Arc::new(SegmentBufFactory::new(self.options.buffer_size.into()));
         let bfo = BufFactory::new(self.options.buffer_size.into());
@@ -605,88 +1397,60 @@ impl App {
             let (txi, rxi) = channel::bounded(1);
             // prepare receive/transmit channels for output data
             let (txo, rxo) = channel::bounded(1);
+            // prepare receive/transmit channels for results of the readers, reported in the order they finish
+            let (txe, rxe) = channel::unbounded();
+            // prepare a channel telling the merger and the readers to stop, it is closed when dropped
+            let (txs, rxs) = channel::bounded::<()>(0);
             // spawn reader threads
-            let mut readers = Vec::with_capacity(m);
-            for (i, input_ref) in inputs.into_iter().enumerate() {
-                let delimiter = &self.options.delimiter;
-                let reader = scope.spawn(closure!(clone sfi, clone txi, |_| -> Result<()> {
-                    let scanner = Scanner::new(sfi.clone(), delimiter.clone());
-                    let mut meta = None;
-                    if let InputReference::File(path) = &input_ref {
-                        meta = Some(fs::metadata(&path.canonical)?);
-                    }
-                    let mut input = Some(input_ref.open()?.tail(self.options.tail, delimiter.clone())?);
-                    let is_file = |meta: &Option<fs::Metadata>| meta.as_ref().map(|m|m.is_file()).unwrap_or(false);
-                    let process = |input: &mut Option<Input>, is_file: bool| {
-                        if let Some(input) = input {
-                            for (j, item) in scanner.items(&mut input.stream.as_sequential()).with_max_segment_size(self.options.max_message_size.into()).enumerate() {
-                                if txi.send((i, j, item?)).is_err() {
-                                    break;
-                                }
-                            }
-                            Ok(!is_file)
-                        } else {
-                            Ok(false)
-                        }
-                    };
-                    if let InputReference::File(path) = &input_ref {
-                        if process(&mut input, is_file(&meta))? {
-                            return Ok(())
-                        }
-                        fsmon::run(vec![path.canonical.clone()], |event| {
-                            match event.kind {
-                                EventKind::Modify(_) | EventKind::Create(_) | EventKind::Any | EventKind::Other => {
-                                    if let (Some(old_meta), Ok(new_meta)) = (&meta, fs::metadata(&path.canonical)) {
-                                        if old_meta.len() > new_meta.len() {
-                                            input = None;
-                                        }
-                                        #[cfg(unix)]
-                                        if old_meta.ino() != new_meta.ino() || old_meta.dev() != new_meta.dev() {
-                                            input = None;
-                                        }
-                                        meta = Some(new_meta);
-                                    }
-                                    if input.is_none() {
-                                        input = input_ref.open().ok();
-                                    }
-                                    if process(&mut input, is_file(&meta))? {
-                                        return Ok(())
-                                    }
-                                    Ok(())
-                                }
-                                EventKind::Remove(_) => {
-                                    input = None;
-                                    Ok(())
-                                },
-                                EventKind::Access(_) => Ok(()),
-                            }
-                        })
-                    } else {
-                        process(&mut input, is_file(&meta)).map(|_|())
-                    }
+            let initial = inputs.lock().unwrap().clone();
+            for (i, input_ref) in initial.into_iter().enumerate() {
+                let retire = (i >= fixed).then_some(&active);
+                scope.spawn(closure!(clone sfi, clone txi, clone txe, clone rxs, |_| {
+                    let tail = Some(self.options.tail);
+                    txe.send(self.follow_input(i, &input_ref, tail, retire, resume, &sfi, &txi, &rxs)).ok();
                 }));
-                readers.push(reader);
+            }
+            // spawn a thread discovering new files in the followed directories
+            if !self.options.follow_dirs.is_empty() {
+                scope.spawn(
+                    closure!(clone sfi, clone txi, clone txe, clone rxs, ref inputs, ref active, ref badges, |scope| {
+                        let result =
+                            self.follow_dirs(scope, inputs, fixed, active, badges, resume, sfi, txi, &txe, rxs);
+                        txe.send(result).ok();
+                    }),
+                );
+            }
+            // spawn threads accepting peers on the sockets
+            for listener in listeners {
+                scope.spawn(
+                    closure!(clone sfi, clone txi, clone txe, clone rxs, ref inputs, ref badges, |scope| {
+                        txe.send(self.follow_listener(scope, listener, inputs, badges, sfi, txi, &txe, rxs)).ok();
+                    }),
+                );
             }
             drop(txi);
-
+            drop(txe);
 
             // spawn processing threads
             let mut workers = Vec::with_capacity(n);
             for _ in 0..n {
-                let worker = scope.spawn(closure!(ref bfo, ref parser, ref sfi, ref badges, clone rxi, clone txo, |_| {
-                    self.process_segments(parser, bfo, sfi, badges, rxi, txo);
-                }));
+                let worker = scope.spawn(
+                    closure!(ref bfo, ref parser, ref sfi, ref badges, clone rxi, clone txo, |_| {
+                        self.process_segments(parser, bfo, sfi, badges, rxi, txo);
+                    }),
+                );
                 workers.push(worker);
             }
             drop(txo);
 
             // spawn merger thread
-            let merger = scope.spawn(|_| -> Result<()> {
-                self.merge_segments(&badges, rxo, output, n)
-            });
+            let merger = scope.spawn(|_| -> Result<()> { self.merge_segments(&badges, rxo, rxs, output, n, resume) });
 
-            for reader in readers {
-                reader.join().unwrap()?;
+            // the first failure stops following, the other readers are interrupted as they may wait for data forever
+            if let Some(Err(err)) = rxe.iter().find(|result| result.is_err()) {
+                drop(txs);
+                merger.join().unwrap()?;
+                return Err(err);
             }
 
             for worker in workers {
@@ -699,22 +1463,398 @@ impl App {
         })
         .unwrap()?;
 
+        if let Some(resume) = resume {
+            resume.finish()?;
+        }
+
         Ok(())
     }
 
+    /// Reads the input and sends its segments for processing, then follows the changes of the input file.
+    ///
+    /// If `retire` is specified, the input stops being followed when its file disappears,
+    /// and its canonical path is removed from the set.
+    /// If `resume` is specified, reading starts from the saved position instead of the `tail`,
+    /// or from the beginning if the file appeared after the previous run,
+    /// and the position reached after each segment is remembered to be committed once the segment is written.
+    /// Following stops when the `stop` channel is closed, except for the inputs that are not files,
+    /// such as standard input, they are read until they end as a pending read of them cannot be interrupted.
+    #[allow(clippy::too_many_arguments)]
+    fn follow_input(
+        &self,
+        i: usize,
+        input_ref: &InputReference,
+        tail: Option<u64>,
+        retire: Option<&Mutex<HashSet<PathBuf>>>,
+        resume: Option<&Resume>,
+        sfi: &Arc<SegmentBufFactory>,
+        txi: &Sender<(usize, usize, Segment, bool)>,
+        stop: &Receiver<()>,
+    ) -> Result<()> {
+        if let InputReference::Process(command) = input_ref {
+            return self.follow_command(i, command, sfi, txi, stop);
+        }
+
+        let delimiter = &self.options.delimiter;
+        let scanner = Scanner::new(sfi.clone(), delimiter.clone());
+        let mut meta = None;
+        if let InputReference::File(path) = input_ref {
+            meta = Some(fs::metadata(&path.canonical)?);
+        }
+        let (mut input, resumed) = self.tracked(i, input_ref.open()?, resume)?;
+        let new = resume.is_some_and(Resume::continued) && matches!(input_ref, InputReference::File(_));
+        if let Some(tail) = tail.filter(|_| !resumed && !new) {
+            input = input.tail(tail, delimiter.clone())?;
+        }
+        let mut input = Some(input.unwrapped(self.options.unwrap, true));
+        let is_file = |meta: &Option<fs::Metadata>| meta.as_ref().map(|m| m.is_file()).unwrap_or(false);
+        // segments are numbered across reopening of the file, so that the merger can tell which of them are written
+        let segments = Cell::new(0);
+        // the data found in a file when it is opened first is not live, so it does not trigger alerts
+        let process = |input: &mut Option<Input>, is_file: bool, live: bool| {
+            if let Some(input) = input {
+                for item in scanner
+                    .items(&mut input.stream.as_sequential())
+                    .with_max_segment_size(self.options.max_message_size.into())
+                {
+                    let item = item?;
+                    let j = segments.replace(segments.get() + 1);
+                    if let Some(resume) = resume {
+                        resume.reached(i, j);
+                    }
+                    if txi.send((i, j, item, live)).is_err() {
+                        break;
+                    }
+                }
+                Ok(!is_file)
+            } else {
+                Ok(false)
+            }
+        };
+
+        let InputReference::File(path) = input_ref else {
+            return process(&mut input, is_file(&meta), true).map(|_| ());
+        };
+        if process(&mut input, is_file(&meta), !is_file(&meta))? {
+            return Ok(());
+        }
+
+        let retired = || {
+            retire.is_some_and(|active| {
+                let mut active = active.lock().unwrap();
+                !path.canonical.exists() && active.remove(&path.canonical)
+            })
+        };
+
+        fsmon::run(vec![path.canonical.clone()], stop, |event| {
+            match event.kind {
+                EventKind::Modify(_) | EventKind::Create(_) | EventKind::Any | EventKind::Other => {
+                    if let (Some(old_meta), Ok(new_meta)) = (&meta, fs::metadata(&path.canonical)) {
+                        if old_meta.len() > new_meta.len() {
+                            input = None;
+                        }
+                        #[cfg(unix)]
+                        if old_meta.ino() != new_meta.ino() || old_meta.dev() != new_meta.dev() {
+                            input = None;
+                        }
+                        meta = Some(new_meta);
+                    }
+                    if input.is_none() {
+                        input = input_ref
+                            .open()
+                            .and_then(|x| self.tracked(i, x, resume))
+                            .ok()
+                            .map(|(x, _)| x.unwrapped(self.options.unwrap, true));
+                    }
+                    process(&mut input, is_file(&meta), true)?;
+                }
+                EventKind::Remove(_) => {
+                    input = None;
+                }
+                EventKind::Access(_) => {}
+            }
+            if retired() {
+                log::debug!("stopped following removed {}", input_ref.description());
+                return Ok(Flow::Break(()));
+            }
+            Ok(Flow::Continue(()))
+        })
+    }
+
+    /// Runs the command and sends the segments of its output for processing until it exits.
+    ///
+    /// If `restart` option is enabled, the command is run again after it exits,
+    /// otherwise its failure is reported as an error carrying its exit status.
+    /// The command is killed when the `stop` channel is closed.
+    fn follow_command(
+        &self,
+        i: usize,
+        command: &Command,
+        sfi: &Arc<SegmentBufFactory>,
+        txi: &Sender<(usize, usize, Segment, bool)>,
+        stop: &Receiver<()>,
+    ) -> Result<()> {
+        const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+        loop {
+            let (child, output) = command.spawn()?;
+            let child = Mutex::new(child);
+            let completed = interruptible(
+                stop,
+                || {
+                    child.lock().unwrap().kill().ok();
+                },
+                || self.follow_stream(i, Stream::Sequential(Box::new(output)), sfi, txi),
+            )?;
+            let mut child = child.into_inner().unwrap();
+            if !completed || stopped(stop) {
+                child.kill().ok();
+                child.wait()?;
+                return Ok(());
+            }
+
+            let status = child.wait()?;
+            if !self.options.restart {
+                if !status.success() {
+                    return Err(Error::CommandFailed {
+                        command: command.name(),
+                        status,
+                    });
+                }
+                return Ok(());
+            }
+            log::debug!("command {} exited with {}, restarting", command.name(), status);
+            if let Err(RecvTimeoutError::Disconnected) = stop.recv_timeout(RESTART_DELAY) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Sends the segments of the stream for processing until it ends.
+    ///
+    /// Returns false if the processing has stopped before the end of the stream.
+    fn follow_stream(
+        &self,
+        i: usize,
+        mut stream: Stream,
+        sfi: &Arc<SegmentBufFactory>,
+        txi: &Sender<(usize, usize, Segment, bool)>,
+    ) -> Result<bool> {
+        // the stream ends only when its source is finished, so incomplete lines at its end are flushed
+        if let Some(wrapper) = self.options.unwrap {
+            stream = stream.unwrapped(wrapper, false);
+        }
+
+        let scanner = Scanner::new(sfi.clone(), self.options.delimiter.clone());
+        for (j, item) in scanner
+            .items(&mut stream)
+            .with_max_segment_size(self.options.max_message_size.into())
+            .enumerate()
+        {
+            if txi.send((i, j, item?, true)).is_err() {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Watches the followed directories and starts following new matching files as they appear.
+    ///
+    /// Each new file gets the next input number, and the badges are updated to include it.
+    /// A file that reappears after it was removed keeps its input number.
+    #[allow(clippy::too_many_arguments)]
+    fn follow_dirs<'env>(
+        &'env self,
+        scope: &thread::Scope<'env>,
+        inputs: &'env Mutex<Vec<InputReference>>,
+        fixed: usize,
+        active: &'env Mutex<HashSet<PathBuf>>,
+        badges: &'env FollowBadges,
+        resume: Option<&'env Resume>,
+        sfi: Arc<SegmentBufFactory>,
+        txi: Sender<(usize, usize, Segment, bool)>,
+        txe: &Sender<Result<()>>,
+        stop: Receiver<()>,
+    ) -> Result<()> {
+        let dirs = &self.options.follow_dirs;
+
+        fsmon::run_dirs(dirs.iter().map(|dir| dir.path.clone()).collect(), &stop, |event| {
+            let rescan = match event.kind {
+                EventKind::Access(_) => false,
+                // some platforms report changes of directory entries as modification of the directory itself
+                EventKind::Modify(kind) if !matches!(kind, ModifyKind::Name(_)) => {
+                    event.paths.iter().any(|path| path.is_dir())
+                }
+                _ => true,
+            };
+            if !rescan {
+                return Ok(Flow::Continue(()));
+            }
+
+            for dir in dirs {
+                // the directory may be temporarily missing, it is scanned again on the next event
+                let Ok(files) = dir.files() else {
+                    continue;
+                };
+                for path in files {
+                    let Ok(path) = InputPath::resolve(path) else {
+                        continue;
+                    };
+                    let mut active_paths = active.lock().unwrap();
+                    if active_paths.contains(&path.canonical) || !path.canonical.exists() {
+                        continue;
+                    }
+                    let mut inputs = inputs.lock().unwrap();
+                    let i = match Self::followed(&inputs, &path) {
+                        Some(i) if i < fixed => continue,
+                        Some(i) => i,
+                        None => {
+                            inputs.push(InputReference::File(path.clone()));
+                            *badges.input.write().unwrap() = self.follow_input_badges(inputs.iter());
+                            inputs.len() - 1
+                        }
+                    };
+                    active_paths.insert(path.canonical.clone());
+
+                    let input_ref = inputs[i].clone();
+                    log::debug!("following new {}", input_ref.description());
+                    scope.spawn(closure!(clone sfi, clone txi, clone txe, clone stop, |_| {
+                        let result = self.follow_input(i, &input_ref, None, Some(active), resume, &sfi, &txi, &stop);
+                        if let Err(err) = result {
+                            log::warn!("failed to follow {}: {}", input_ref.description(), err);
+                            if !path.canonical.exists() {
+                                active.lock().unwrap().remove(&path.canonical);
+                            }
+                        }
+                        // failures of the files found in the directories do not stop following
+                        txe.send(Ok(())).ok();
+                    }));
+                }
+            }
+
+            Ok(Flow::Continue(()))
+        })
+    }
+
+    /// Accepts peers on the socket and follows the entries sent by each of them as a separate input.
+    ///
+    /// Each new peer gets the next input number, and the badges are updated to include it.
+    /// A peer that comes back with the same address keeps its input number.
+    /// Accepting peers and following them stops when the `stop` channel is closed.
+    #[allow(clippy::too_many_arguments)]
+    fn follow_listener<'env>(
+        &'env self,
+        scope: &thread::Scope<'env>,
+        mut listener: Listener,
+        inputs: &'env Mutex<Vec<InputReference>>,
+        badges: &'env FollowBadges,
+        sfi: Arc<SegmentBufFactory>,
+        txi: Sender<(usize, usize, Segment, bool)>,
+        txe: &Sender<Result<()>>,
+        stop: Receiver<()>,
+    ) -> Result<()> {
+        let address = listener.address();
+        log::debug!("listening on {}", address);
+
+        let waker = listener.waker()?;
+        let wake = || {
+            if let Err(err) = waker.wake() {
+                log::warn!("failed to stop listening on {}: {}", address, err);
+            }
+        };
+        interruptible(&stop, wake, || {
+            loop {
+                let accepted = listener.accept();
+                if stopped(&stop) {
+                    return Ok(());
+                }
+                let Connection { peer, stream } = match accepted {
+                    Ok(connection) => connection,
+                    Err(err) if is_transient(&err) => {
+                        log::warn!("failed to accept a peer on {}: {}", address, err);
+                        continue;
+                    }
+                    Err(err) => return Err(err.into()),
+                };
+                let closer = match stream.closer() {
+                    Ok(closer) => closer,
+                    Err(err) => {
+                        log::warn!("failed to accept a peer on {}: {}", address, err);
+                        continue;
+                    }
+                };
+
+                let input_ref = InputReference::Socket(peer);
+                let i = {
+                    let mut inputs = inputs.lock().unwrap();
+                    match inputs.iter().position(|x| *x == input_ref) {
+                        Some(i) => i,
+                        None => {
+                            inputs.push(input_ref.clone());
+                            *badges.input.write().unwrap() = self.follow_input_badges(inputs.iter());
+                            inputs.len() - 1
+                        }
+                    }
+                };
+
+                log::debug!("following new {}", input_ref.description());
+                scope.spawn(closure!(clone sfi, clone txi, clone txe, clone stop, |_| {
+                    let close = || {
+                        closer.close().ok();
+                    };
+                    let follow = || self.follow_stream(i, Stream::Sequential(Box::new(stream)), &sfi, &txi);
+                    match interruptible(&stop, close, follow) {
+                        Ok(_) => log::debug!("stopped following closed {}", input_ref.description()),
+                        Err(err) => log::warn!("failed to follow {}: {}", input_ref.description(), err),
+                    }
+                    // failures of the peers do not stop following
+                    txe.send(Ok(())).ok();
+                }));
+            }
+        })
+    }
+
+    /// Loads the positions saved by the previous run if `resume` option is specified.
+    fn resume(&self) -> Result<Option<Resume>> {
+        let Some(name) = &self.options.resume else {
+            return Ok(None);
+        };
+        let dirs = self.options.app_dirs.as_ref().ok_or(Error::AppDirs)?;
+        Ok(Some(Resume::load(&dirs.cache_dir, name)?))
+    }
+
+    /// Starts tracking the read position in the input with the given number if `resume` is specified.
+    fn tracked(&self, i: usize, input: Input, resume: Option<&Resume>) -> std::io::Result<(Input, bool)> {
+        match resume {
+            Some(resume) => resume.track(i, input, &self.options.delimiter),
+            None => Ok((input, false)),
+        }
+    }
+
+    fn followed(inputs: &[InputReference], path: &InputPath) -> Option<usize> {
+        inputs
+            .iter()
+            .position(|input| matches!(input, InputReference::File(x) if x.canonical == path.canonical))
+    }
+
+    /// Formats the segments sent by the readers and passes them to the merger along with the timestamps of their entries.
+    ///
+    /// Only the entries of live segments are matched against the alert query,
+    /// so that the entries preloaded from the files at start do not trigger alerts.
     fn process_segments(
         &self,
         parser: &Parser,
         bfo: &BufFactory,
         sfi: &SegmentBufFactory,
         badges: &FollowBadges,
-        rxi: Receiver<(usize, usize, Segment)>,
+        rxi: Receiver<(usize, usize, Segment, bool)>,
         txo: Sender<(usize, Vec<u8>, TimestampIndex)>,
     ) {
         let mut processor = self.new_segment_processor(parser);
-        for (i, j, segment) in rxi.iter() {
-            let prefix = badges
-                .input
+        for (i, j, segment, live) in rxi.iter() {
+            let input_badges = badges.input.read().unwrap();
+            let prefix = input_badges
                 .as_ref()
                 .map(|b| b[i].as_str())
                 .unwrap_or(&badges.si.placeholder);
@@ -722,15 +1862,24 @@ impl App {
                 Segment::Complete(segment) => {
                     let mut buf = bfo.new_buf();
                     let mut index_builder = TimestampIndexBuilder {
+                        alert: self.options.alert.as_ref().filter(|_| live).map(|alert| &alert.query),
                         result: TimestampIndex::new(j),
                     };
+                    processor.switch_input(i);
                     processor.process(segment.data(), &mut buf, prefix, None, &mut index_builder);
                     sfi.recycle(segment);
+                    drop(input_badges);
                     if txo.send((i, buf, index_builder.result)).is_err() {
                         return;
                     };
                 }
-                Segment::Incomplete(_, _) => {}
+                Segment::Incomplete(_, _) => {
+                    // the merger still needs to know the segment is done to track the resume position
+                    drop(input_badges);
+                    if txo.send((i, Vec::new(), TimestampIndex::new(j))).is_err() {
+                        return;
+                    };
+                }
             }
         }
     }
@@ -739,8 +1888,10 @@ impl App {
         &self,
         badges: &FollowBadges,
         rxo: Receiver<(usize, Vec<u8>, TimestampIndex)>,
+        stop: Receiver<()>,
         output: &mut Output,
         concurrency: usize,
+        resume: Option<&Resume>,
     ) -> Result<()> {
         type Key = (Timestamp, usize, usize, usize); // (ts, input, block, offset)
         type Line = (Rc<Vec<u8>>, Range<usize>, Instant); // (buf, location, instant)
@@ -751,14 +1902,29 @@ impl App {
         let mut source_last_ts: HashMap<usize, Timestamp> = HashMap::new();
         let mut mem_usage = 0;
         let mem_limit = concurrency * usize::from(self.options.buffer_size);
+        let mut alerter = self
+            .options
+            .alert
+            .as_ref()
+            .map(|alert| Alerter::new(alert.debounce, alert.interval));
+        // when stopped, all entries in the window are written at once
+        let mut stopped = false;
+        // positions are committed only after all entries before them are written and flushed
+        let mut progress = Progress::default();
+        let mut last_save = Instant::now();
 
         loop {
+            if let Some(alert) = alerter.as_mut().and_then(|alerter| alerter.poll(Instant::now())) {
+                self.alert(alert, false);
+            }
+
             let deadline = Instant::now().checked_sub(self.options.sync_interval);
             while let Some(first) = window.first_key_value() {
-                if deadline.map(|deadline| first.1.2 > deadline).unwrap_or(true) && mem_usage < mem_limit {
+                if !stopped && deadline.map(|deadline| first.1.2 > deadline).unwrap_or(true) && mem_usage < mem_limit {
                     break;
                 }
                 if let Some(entry) = window.pop_first() {
+                    progress.written(entry.0.1, entry.0.2);
                     let sync_indicator = if prev_ts.map(|ts| ts <= entry.0.0).unwrap_or(true) {
                         &badges.si.synced
                     } else {
@@ -771,6 +1937,25 @@ impl App {
                     output.write_all(self.options.output_delimiter.as_bytes())?;
                 }
             }
+            if let Some(resume) = resume {
+                let complete = progress.complete();
+                if !complete.is_empty() {
+                    output.flush()?;
+                    for (i, segment) in complete {
+                        resume.written(i, segment);
+                    }
+                }
+                if stopped || window.is_empty() || last_save.elapsed() >= self.options.sync_interval {
+                    if let Err(err) = resume.save() {
+                        log::warn!("failed to save resume positions: {}", err);
+                    }
+                    last_save = Instant::now();
+                }
+            }
+            if stopped {
+                output.flush()?;
+                break;
+            }
 
             let next_ts = window.first_entry().map(|e| e.get().2);
             let timeout = if let (Some(next_ts), Some(deadline)) = (next_ts, deadline) {
@@ -778,12 +1963,36 @@ impl App {
             } else {
                 None
             };
-            match rxo.recv_timeout(timeout.unwrap_or(std::time::Duration::MAX)) {
+            // a pending alert must not wait for the next segment
+            let wait = match alerter.as_ref().and_then(|alerter| alerter.deadline()) {
+                Some(due) => {
+                    let due = due.saturating_duration_since(Instant::now());
+                    timeout.map_or(due, |timeout| timeout.min(due))
+                }
+                None => timeout.unwrap_or(Duration::MAX),
+            };
+            let received = channel::select! {
+                recv(rxo) -> msg => msg.map_err(RecvTimeoutError::from),
+                recv(stop) -> _ => {
+                    stopped = true;
+                    continue;
+                }
+                default(wait) => Err(RecvTimeoutError::Timeout),
+            };
+            match received {
                 Ok((i, buf, index)) => {
                     let delim_len = self.options.output_delimiter.len();
                     let delim = self.options.output_delimiter.as_bytes();
                     let buf = Rc::new(buf);
                     let now = Instant::now();
+                    let pending = window.len();
+
+                    // Alerts are delayed by the debounce period, so they usually come after the entries are written.
+                    if let Some(alerter) = &mut alerter {
+                        for record in index.matches {
+                            alerter.add(record, now);
+                        }
+                    }
 
                     // Iterate over gaps between indexed lines and insert them
                     // into the window with the timestamp of the next indexed
@@ -835,6 +2044,7 @@ impl App {
                         let value = (buf.clone(), line.location, now);
                         window.insert(key, value);
                     }
+                    progress.received(i, index.block, window.len() - pending);
                 }
                 Err(RecvTimeoutError::Timeout) => {}
                 Err(RecvTimeoutError::Disconnected) => {
@@ -845,9 +2055,82 @@ impl App {
             }
         }
 
+        if let Some(alert) = alerter.as_mut().and_then(|alerter| alerter.flush()) {
+            self.alert(alert, true);
+        }
+
         Ok(())
     }
 
+    /// Rings the bell and runs the command in the background as the alert options say.
+    ///
+    /// If `wait` is true, the command is waited for, otherwise it could be killed by exit of the process.
+    fn alert(&self, alert: Alert, wait: bool) {
+        let Some(options) = &self.options.alert else {
+            return;
+        };
+        if options.bell {
+            std::io::stderr().write_all(b"\x07").ok();
+        }
+        let Some(command) = options.command.clone() else {
+            return;
+        };
+
+        let run = move || {
+            let input = json::to_vec(&alert).unwrap_or_default();
+            match command.run(&alert.vars(), &input) {
+                Ok(status) if !status.success() => {
+                    log::warn!("alert command {} failed: {}", command.name(), status)
+                }
+                Ok(_) => {}
+                Err(err) => log::warn!("{}", err),
+            }
+        };
+        if wait {
+            run();
+        } else {
+            std::thread::spawn(run);
+        }
+    }
+
+    /// Returns the number of segment processing threads.
+    ///
+    /// Context tracking in cat and follow modes requires segments of each input to be processed sequentially.
+    fn processing_threads(&self) -> usize {
+        let inline_context = matches!(self.context_mode(), ContextMode::Inline { .. });
+        // without a preliminary pass, correlated values are collected as entries go, so the order matters
+        let inline_correlation = self.options.correlate_by.is_some() && (self.options.follow || !self.options.sort);
+        if inline_context || inline_correlation {
+            1
+        } else {
+            self.options.concurrency
+        }
+    }
+
+    /// Returns the context mode for segment processors.
+    ///
+    /// In sort mode, entries are reordered after processing, so context entries can only be selected later.
+    fn context_mode(&self) -> ContextMode {
+        if self.context_formatter.is_none() {
+            ContextMode::Off
+        } else if self.options.sort && !self.options.follow {
+            ContextMode::Deferred
+        } else {
+            ContextMode::Inline {
+                options: self.options.context,
+                separator: self.context_separator().map(|separator| separator.to_owned()),
+            }
+        }
+    }
+
+    /// Returns the separator of non-adjacent groups of context entries, structured output formats have none.
+    fn context_separator(&self) -> Option<&str> {
+        match self.options.output_format {
+            Some(_) => None,
+            None => Some(&self.punctuation.context_separator),
+        }
+    }
+
     fn parser(&self) -> Parser {
         Parser::new(ParserSettings::new(
             &self.options.fields.settings.predefined,
@@ -856,10 +2139,22 @@ impl App {
         ))
     }
 
+    /// Returns a parser of raw records configured by the input options.
+    fn raw_parser(&self) -> RawRecordParser<'_> {
+        RawRecord::parser()
+            .allow_prefix(self.options.allow_prefix)
+            .format(self.options.input_format)
+            .status_levels(self.options.status_levels)
+            .text_formats(&self.options.text_formats)
+            .wrapped(self.options.unwrap.is_some())
+    }
+
     fn input_badges<'a, I: IntoIterator<Item = &'a InputReference>>(&self, inputs: I) -> Option<Vec<String>> {
         let name = |input: &InputReference| match input {
             InputReference::Stdin => "<stdin>".to_owned(),
             InputReference::File(path) => path.original.to_string_lossy().to_string(),
+            InputReference::Member(path, member) => format!("{}:{}", path.original.to_string_lossy(), member.name),
+            InputReference::Process(command) => command.name(),
         };
 
         let names = inputs.into_iter().map(name).collect_vec();
@@ -970,48 +2265,71 @@ impl App {
     fn new_segment_processor<'a>(&'a self, parser: &'a Parser) -> impl SegmentProcess + 'a {
         let options = SegmentProcessorOptions {
             allow_prefix: self.options.allow_prefix,
-            allow_unparsed_data: self.options.filter.is_empty() && self.options.input_format.is_none(),
+            // lines that are not records would break structured output
+            allow_unparsed_data: self.options.filter.is_empty()
+                && self.options.input_format.is_none()
+                && self.options.output_format.is_none(),
             delimiter: self.options.delimiter.clone(),
             input_format: self.options.input_format,
+            status_levels: self.options.status_levels,
+            text_formats: self.options.text_formats.clone(),
+            wrapped: self.options.unwrap.is_some(),
+            embedded_json: self.options.embedded_json.clone(),
             output_delimiter: self.options.output_delimiter.clone(),
+            context: self.context_mode(),
         };
 
-        SegmentProcessor::new(
-            parser,
-            self.formatter.clone(),
-            Query::from(&self.options.filter),
-            options,
-        )
+        let processor = SegmentProcessor::new(parser, self.formatter.clone(), self.filter.clone(), options);
+
+        match &self.context_formatter {
+            Some(formatter) => processor.with_context_formatter(formatter.clone()),
+            None => processor,
+        }
     }
 
     /// Creates a formatter based on the provided options.
     ///
     /// Returns either a RawRecordFormatter or a RecordFormatter depending on the options.
-    fn new_formatter(options: &Options, punctuation: Arc<ResolvedPunctuation>) -> DynRecordWithSourceFormatter {
+    fn new_formatter(
+        options: &Options,
+        theme: Arc<Theme>,
+        punctuation: Arc<ResolvedPunctuation>,
+    ) -> DynRecordWithSourceFormatter {
         if options.raw {
             Arc::new(RawRecordFormatter {
                 delimiter: options.output_delimiter.clone(),
             })
-        } else {
-            let predefined_filter = Self::build_predefined_filter(options);
+        } else if let Some(format) = options.output_format {
             Arc::new(
-                RecordFormatterBuilder::new()
-                    .with_theme(options.theme.clone())
-                    .with_timestamp_formatter(DateTimeFormatter::new(options.time_format.clone(), options.time_zone))
+                StructuredRecordFormatter::new(format)
+                    .with_time_zone(options.time_zone)
+                    .with_flatten(options.flatten)
                     .with_empty_fields_hiding(options.hide_empty_fields)
                     .with_field_filter(options.fields.filter.clone())
-                    .with_predefined_field_filter(predefined_filter)
-                    .with_options(options.formatting.clone())
-                    .with_raw_fields(options.raw_fields)
-                    .with_flatten(options.flatten)
-                    .with_ascii(options.ascii)
-                    .with_expansion(Expansion::from(options.formatting.expansion.clone()).with_mode(options.expand))
-                    .with_always_show_time(options.fields.settings.predefined.time.show == FieldShowOption::Always)
-                    .with_always_show_level(options.fields.settings.predefined.level.show == FieldShowOption::Always)
-                    .with_punctuation(punctuation)
-                    .with_expansion(Expansion::from(options.formatting.expansion.clone()).with_mode(options.expand))
-                    .build(),
+                    .with_predefined_field_filter(Self::build_predefined_filter(options, None)),
             )
+        } else {
+            let predefined_filter = Self::build_predefined_filter(options, options.template.as_deref());
+            let builder = RecordFormatterBuilder::new()
+                .with_theme(theme)
+                .with_timestamp_formatter(DateTimeFormatter::new(options.time_format.clone(), options.time_zone))
+                .with_empty_fields_hiding(options.hide_empty_fields)
+                .with_field_filter(options.fields.filter.clone())
+                .with_predefined_field_filter(predefined_filter)
+                .with_options(options.formatting.clone())
+                .with_raw_fields(options.raw_fields)
+                .with_flatten(options.flatten)
+                .with_ascii(options.ascii)
+                .with_expansion(Expansion::from(options.formatting.expansion.clone()).with_mode(options.expand))
+                .with_always_show_time(options.fields.settings.predefined.time.show == FieldShowOption::Always)
+                .with_always_show_level(options.fields.settings.predefined.level.show == FieldShowOption::Always)
+                .with_punctuation(punctuation)
+                .with_expansion(Expansion::from(options.formatting.expansion.clone()).with_mode(options.expand));
+            let builder = match &options.template {
+                Some(template) => builder.with_template(template.clone()),
+                None => builder,
+            };
+            Arc::new(builder.build())
         }
     }
 
@@ -1020,11 +2338,17 @@ impl App {
     /// This filter is used to silently exclude nested predefined fields from formatting
     /// without triggering the "..." hidden fields indicator.
     /// Uses exact matching (no normalization) to match field names precisely.
-    fn build_predefined_filter(options: &Options) -> Arc<ExactIncludeExcludeKeyFilter> {
+    /// Fields referenced by the template, if given, are excluded as well because they are shown by its placeholders.
+    fn build_predefined_filter(options: &Options, template: Option<&Template>) -> Arc<ExactIncludeExcludeKeyFilter> {
         let mut filter = ExactIncludeExcludeKeyFilter::new(MatchOptions::<NoNormalizing>::default());
         for name in options.fields.settings.predefined.nested_field_names() {
             filter.entry(name).exclude();
         }
+        if let Some(template) = template {
+            for key in template.field_keys() {
+                filter.entry(key).exclude();
+            }
+        }
         Arc::new(filter)
     }
 }
@@ -1040,6 +2364,11 @@ pub trait SegmentProcess {
         limit: Option<usize>,
         observer: &mut O,
     );
+
+    /// Selects the input that the following segments belong to.
+    ///
+    /// Context entries are tracked separately for each input.
+    fn switch_input(&mut self, input: usize);
 }
 
 // ---
@@ -1050,7 +2379,41 @@ pub struct SegmentProcessorOptions {
     pub allow_unparsed_data: bool,
     pub delimiter: Delimiter,
     pub input_format: Option<InputFormat>,
+    pub status_levels: StatusLevels,
+    pub text_formats: Arc<[TextFormat]>,
+    pub wrapped: bool,
+    pub embedded_json: EmbeddedJsonExpansion,
     pub output_delimiter: String,
+    pub context: ContextMode,
+}
+
+impl SegmentProcessorOptions {
+    /// Returns a parser of raw records configured by the input options.
+    pub fn raw_parser(&self) -> RawRecordParser<'_> {
+        RawRecord::parser()
+            .allow_prefix(self.allow_prefix)
+            .format(self.input_format)
+            .status_levels(self.status_levels)
+            .text_formats(&self.text_formats)
+            .wrapped(self.wrapped)
+    }
+}
+
+/// Defines how entries rejected by the filter are used as context for matching entries.
+#[derive(Default, Clone, Debug)]
+pub enum ContextMode {
+    /// Rejected entries are dropped.
+    #[default]
+    Off,
+    /// Rejected entries surrounding matching entries are output as context,
+    /// non-adjacent groups of entries are delimited by the separator if it is specified.
+    Inline {
+        options: ContextOptions,
+        separator: Option<String>,
+    },
+    /// All rejected entries are output as context and reported to the observer,
+    /// so that context entries can be selected after reordering.
+    Deferred,
 }
 
 // ---
@@ -1058,9 +2421,14 @@ pub struct SegmentProcessorOptions {
 pub struct SegmentProcessor<'a, Formatter, Filter> {
     parser: &'a Parser,
     formatter: Formatter,
+    context_formatter: Option<Formatter>,
     filter: Filter,
     options: SegmentProcessorOptions,
     delim: <Delimiter as Delimit>::Searcher,
+    contexts: Vec<ContextWindow<Vec<u8>>>,
+    input: usize,
+    spare: Option<Vec<u8>>,
+    json_buf: Vec<u8>,
 }
 
 impl<'a, Formatter: RecordWithSourceFormatter, Filter: RecordFilter> SegmentProcessor<'a, Formatter, Filter> {
@@ -1070,9 +2438,22 @@ impl<'a, Formatter: RecordWithSourceFormatter, Filter: RecordFilter> SegmentProc
         Self {
             parser,
             formatter,
+            context_formatter: None,
             filter,
             options,
             delim,
+            contexts: Vec::new(),
+            input: 0,
+            spare: None,
+            json_buf: Vec::new(),
+        }
+    }
+
+    /// Sets the formatter for context entries, by default the main formatter is used.
+    pub fn with_context_formatter(self, formatter: Formatter) -> Self {
+        Self {
+            context_formatter: Some(formatter),
+            ..self
         }
     }
 
@@ -1080,6 +2461,41 @@ impl<'a, Formatter: RecordWithSourceFormatter, Filter: RecordFilter> SegmentProc
     fn show_unparsed(&self) -> bool {
         self.options.allow_unparsed_data
     }
+
+    #[inline(always)]
+    fn context_formatter(&self) -> &Formatter {
+        self.context_formatter.as_ref().unwrap_or(&self.formatter)
+    }
+
+    #[inline]
+    fn format_record(
+        &self,
+        formatter: &Formatter,
+        buf: &mut Vec<u8>,
+        prefix: &str,
+        source_prefix: &[u8],
+        record: RecordWithSource,
+    ) {
+        let begin = buf.len();
+        if source_prefix.is_empty() {
+            buf.extend(prefix.as_bytes());
+        } else {
+            let mut first = true;
+            for line in Newline.into_searcher().split(source_prefix) {
+                if !first {
+                    buf.extend(self.options.output_delimiter.as_bytes());
+                }
+                first = false;
+                buf.extend(prefix.as_bytes());
+                buf.extend(line);
+            }
+            if source_prefix.last().map(|&x| x == b' ') == Some(false) {
+                buf.push(b' ');
+            }
+        }
+        let prefix_range = begin..buf.len();
+        formatter.format_record(buf, prefix_range, record);
+    }
 }
 
 impl<'a, Formatter: RecordWithSourceFormatter, Filter: RecordFilter> SegmentProcess
@@ -1091,6 +2507,14 @@ impl<'a, Formatter: RecordWithSourceFormatter, Filter: RecordFilter> SegmentProc
     {
         let mut i = 0;
         let limit = limit.unwrap_or(usize::MAX);
+        let mut json_buf = std::mem::take(&mut self.json_buf);
+
+        if let ContextMode::Inline { options, .. } = &self.options.context {
+            if self.contexts.len() <= self.input {
+                self.contexts
+                    .resize_with(self.input + 1, || ContextWindow::new(*options));
+            }
+        }
 
         for chunk in self.delim.split(data) {
             if chunk.is_empty() {
@@ -1101,10 +2525,7 @@ impl<'a, Formatter: RecordWithSourceFormatter, Filter: RecordFilter> SegmentProc
                 continue;
             }
 
-            let mut stream = RawRecord::parser()
-                .allow_prefix(self.options.allow_prefix)
-                .format(self.options.input_format)
-                .parse(chunk);
+            let mut stream = self.options.raw_parser().parse(chunk);
             let mut parsed_some = false;
             let mut produced_some = false;
             let mut last_offset = 0;
@@ -1115,31 +2536,47 @@ impl<'a, Formatter: RecordWithSourceFormatter, Filter: RecordFilter> SegmentProc
                     buf.extend(self.options.output_delimiter.as_bytes());
                 }
                 parsed_some = true;
-                let record = self.parser.parse(&ar.record);
+                let record = self.options.embedded_json.expand(ar.record, &mut json_buf);
+                let record = self.parser.parse(&record);
+                let source = record.with_source(&chunk[ar.offsets]);
                 if record.matches(&self.filter) {
-                    let begin = buf.len();
-                    if ar.prefix.is_empty() {
-                        buf.extend(prefix.as_bytes());
-                    } else {
-                        let mut first = true;
-                        for line in Newline.into_searcher().split(ar.prefix) {
-                            if !first {
-                                buf.extend(self.options.output_delimiter.as_bytes());
-                            }
-                            first = false;
+                    if let ContextMode::Inline { separator, .. } = &self.options.context {
+                        let (separate, lines) = self.contexts[self.input].matched();
+                        if let Some(separator) = separator.as_ref().filter(|_| separate) {
                             buf.extend(prefix.as_bytes());
-                            buf.extend(line);
+                            buf.extend(separator.as_bytes());
+                            buf.extend(self.options.output_delimiter.as_bytes());
                         }
-                        if ar.prefix.last().map(|&x| x == b' ') == Some(false) {
-                            buf.push(b' ');
+                        for line in lines {
+                            buf.extend(line);
+                            buf.extend(self.options.output_delimiter.as_bytes());
                         }
                     }
-                    let prefix_range = begin..buf.len();
-                    self.formatter
-                        .format_record(buf, prefix_range, record.with_source(&chunk[ar.offsets]));
-                    let end = buf.len();
-                    observer.observe_record(&record, begin..end);
+                    let begin = buf.len();
+                    self.format_record(&self.formatter, buf, prefix, ar.prefix, source);
+                    observer.observe_record(&record, begin..buf.len());
                     produced_some = true;
+                } else {
+                    let placement = match &self.options.context {
+                        ContextMode::Off => None,
+                        ContextMode::Inline { .. } => self.contexts[self.input].rejected(),
+                        ContextMode::Deferred => Some(ContextPlacement::After),
+                    };
+                    match placement {
+                        Some(ContextPlacement::After) => {
+                            let begin = buf.len();
+                            self.format_record(self.context_formatter(), buf, prefix, ar.prefix, source);
+                            observer.observe_context_record(&record, begin..buf.len());
+                            produced_some = true;
+                        }
+                        Some(ContextPlacement::Before) => {
+                            let mut held = self.spare.take().unwrap_or_default();
+                            held.clear();
+                            self.format_record(self.context_formatter(), &mut held, prefix, ar.prefix, source);
+                            self.spare = self.contexts[self.input].hold(held);
+                        }
+                        None => {}
+                    }
                 }
                 if i >= limit {
                     break;
@@ -1162,6 +2599,13 @@ impl<'a, Formatter: RecordWithSourceFormatter, Filter: RecordFilter> SegmentProc
                 buf.extend(self.options.output_delimiter.as_bytes());
             }
         }
+
+        self.json_buf = json_buf;
+    }
+
+    #[inline]
+    fn switch_input(&mut self, input: usize) {
+        self.input = input;
     }
 }
 
@@ -1169,6 +2613,10 @@ impl<'a, Formatter: RecordWithSourceFormatter, Filter: RecordFilter> SegmentProc
 
 pub trait RecordObserver {
     fn observe_record<'a>(&mut self, record: &Record<'a>, location: Range<usize>);
+
+    /// Observes a record rejected by the filter but displayed as context.
+    #[inline]
+    fn observe_context_record<'a>(&mut self, _: &Record<'a>, _: Range<usize>) {}
 }
 
 // ---
@@ -1182,19 +2630,90 @@ impl RecordObserver for RecordIgnorer {
 
 // ---
 
-struct TimestampIndexBuilder {
-    result: TimestampIndex,
+/// Collects end offsets of matching records in the output buffer, so that the output can be cut after any of them.
+///
+/// Each end includes the context records following the matching record.
+#[derive(Default)]
+struct RecordEnds {
+    ends: Vec<usize>,
+    /// End of the context records preceding the first matching record, which follow a record of a previous segment.
+    leading_context: Option<usize>,
+}
+
+impl RecordObserver for RecordEnds {
+    #[inline]
+    fn observe_record<'a>(&mut self, _: &Record<'a>, location: Range<usize>) {
+        self.ends.push(location.end)
+    }
+
+    #[inline]
+    fn observe_context_record<'a>(&mut self, _: &Record<'a>, location: Range<usize>) {
+        match self.ends.last_mut() {
+            Some(end) => *end = location.end,
+            None => self.leading_context = Some(location.end),
+        }
+    }
+}
+
+// ---
+
+/// Collects sort keys, timestamps and locations of matching records in the output buffer.
+struct SortKeyCollector<'a> {
+    extractor: &'a FieldValueExtractor,
+    items: Vec<(Option<SortKey>, Option<Timestamp>, Range<usize>)>,
+}
+
+impl<'a> SortKeyCollector<'a> {
+    fn new(extractor: &'a FieldValueExtractor) -> Self {
+        Self {
+            extractor,
+            items: Vec::new(),
+        }
+    }
 }
 
-impl RecordObserver for TimestampIndexBuilder {
+impl RecordObserver for SortKeyCollector<'_> {
     #[inline]
     fn observe_record<'a>(&mut self, record: &Record<'a>, location: Range<usize>) {
+        let key = self.extractor.extract(record).map(SortKey::new);
+        let ts = record.ts.as_ref().and_then(|ts| ts.unix_utc()).map(|ts| ts.into());
+        self.items.push((key, ts, location));
+    }
+}
+
+// ---
+
+/// Collects timestamps and locations of records in the output buffer,
+/// and snapshots of the records that match the alert query.
+struct TimestampIndexBuilder<'q> {
+    alert: Option<&'q Query>,
+    result: TimestampIndex,
+}
+
+impl TimestampIndexBuilder<'_> {
+    #[inline]
+    fn push<'a>(&mut self, record: &Record<'a>, location: Range<usize>) {
         if let Some(ts) = record.ts.as_ref().and_then(|ts| ts.unix_utc()).map(|ts| ts.into()) {
             self.result.lines.push(TimestampIndexLine { location, ts });
         }
     }
 }
 
+impl RecordObserver for TimestampIndexBuilder<'_> {
+    #[inline]
+    fn observe_record<'a>(&mut self, record: &Record<'a>, location: Range<usize>) {
+        if self.alert.is_some_and(|query| query.apply(record)) {
+            self.result.matches.push(Match::new(record));
+        }
+        self.push(record, location)
+    }
+
+    #[inline]
+    fn observe_context_record<'a>(&mut self, record: &Record<'a>, location: Range<usize>) {
+        self.push(record, location)
+    }
+}
+
 // ---
 
 impl<T: FnMut(&Record, Range<usize>)> RecordObserver for T {
@@ -1209,6 +2728,7 @@ impl<T: FnMut(&Record, Range<usize>)> RecordObserver for T {
 struct TimestampIndex {
     block: usize,
     lines: Vec<TimestampIndexLine>,
+    matches: Vec<Match>,
 }
 
 impl TimestampIndex {
@@ -1216,6 +2736,7 @@ impl TimestampIndex {
         Self {
             block,
             lines: Vec::new(),
+            matches: Vec::new(),
         }
     }
 }
@@ -1230,17 +2751,268 @@ struct TimestampIndexLine {
 // ---
 
 struct OutputBlock {
-    ts_min: crate::index::Timestamp,
+    bound: crate::index::Timestamp, // ts_min of the block, or ts_max in reverse order
     buf: Arc<Vec<u8>>,
-    items: Vec<(Timestamp, Range<usize>)>,
+    items: Vec<(Timestamp, Range<usize>, bool)>, // (ts, location, is_context)
 }
 
 impl OutputBlock {
-    pub fn into_lines(self) -> impl Iterator<Item = (Timestamp, BlockEntry)> {
+    pub fn into_lines(self) -> impl Iterator<Item = (Timestamp, BlockEntry, bool)> {
         let buf = self.buf;
         self.items
             .into_iter()
-            .map(move |(ts, range)| (ts, BlockEntry::new(buf.clone(), range.clone())))
+            .map(move |(ts, range, is_context)| (ts, BlockEntry::new(buf.clone(), range.clone()), is_context))
+    }
+}
+
+// ---
+
+struct OutputBlockItems {
+    items: Vec<(Timestamp, Range<usize>, bool)>,
+}
+
+impl OutputBlockItems {
+    fn new(capacity: usize) -> Self {
+        Self {
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
+    fn push(&mut self, record: &Record, location: Range<usize>, is_context: bool) {
+        if let Some(ts) = &record.ts {
+            if let Some(unix_ts) = ts.unix_utc() {
+                self.items.push((unix_ts.into(), location, is_context));
+            } else {
+                log::warn!(
+                    "skipped a message because its timestamp could not be parsed: {:#?}",
+                    ts.raw()
+                )
+            }
+        }
+    }
+}
+
+impl RecordObserver for OutputBlockItems {
+    #[inline]
+    fn observe_record<'a>(&mut self, record: &Record<'a>, location: Range<usize>) {
+        self.push(record, location, false)
+    }
+
+    #[inline]
+    fn observe_context_record<'a>(&mut self, record: &Record<'a>, location: Range<usize>) {
+        self.push(record, location, true)
+    }
+}
+
+// ---
+
+/// Tracks entries rejected by the filter to display them as context around matching entries.
+struct ContextWindow<T> {
+    options: ContextOptions,
+    before: VecDeque<T>,
+    after: usize,
+    gap: bool,
+    started: bool,
+}
+
+impl<T> ContextWindow<T> {
+    fn new(options: ContextOptions) -> Self {
+        Self {
+            options,
+            before: VecDeque::with_capacity(options.before),
+            after: 0,
+            gap: false,
+            started: false,
+        }
+    }
+
+    /// Registers a matching entry.
+    ///
+    /// Returns whether a separator should precede the entry and the held entries to display before it.
+    fn matched(&mut self) -> (bool, vec_deque::Drain<'_, T>) {
+        let separate = self.gap && self.started;
+        self.gap = false;
+        self.started = true;
+        self.after = self.options.after;
+        (separate, self.before.drain(..))
+    }
+
+    /// Registers a rejected entry.
+    ///
+    /// Returns where the entry should be placed, or `None` if it should be dropped.
+    fn rejected(&mut self) -> Option<ContextPlacement> {
+        if self.after != 0 {
+            self.after -= 1;
+            Some(ContextPlacement::After)
+        } else if self.options.before != 0 {
+            Some(ContextPlacement::Before)
+        } else {
+            self.gap = true;
+            None
+        }
+    }
+
+    /// Returns whether more entries are expected to be displayed after the last matching entry.
+    fn pending(&self) -> bool {
+        self.after != 0
+    }
+
+    /// Holds an entry to be displayed before the next matching entry.
+    ///
+    /// Returns the evicted entry, if any.
+    fn hold(&mut self, item: T) -> Option<T> {
+        self.before.push_back(item);
+        if self.before.len() > self.options.before {
+            self.gap = true;
+            self.before.pop_front()
+        } else {
+            None
+        }
+    }
+}
+
+enum ContextPlacement {
+    Before,
+    After,
+}
+
+// ---
+
+/// Accepts entries matching the base filter and entries sharing a value of the key field with any of them.
+///
+/// If `limit` is specified, only that many values of the most recent matching entries are remembered.
+struct CorrelationFilter {
+    base: Query,
+    key: FieldValueExtractor,
+    values: RwLock<CorrelatedValues>,
+}
+
+impl CorrelationFilter {
+    fn new(base: Query, key: FieldValueExtractor, limit: Option<usize>) -> Self {
+        Self {
+            base,
+            key,
+            values: RwLock::new(CorrelatedValues::new(limit)),
+        }
+    }
+}
+
+impl RecordFilter for CorrelationFilter {
+    fn apply<'a>(&self, record: &Record<'a>) -> bool {
+        if self.base.apply(record) {
+            if let Some(value) = self.key.extract(record) {
+                self.values.write().unwrap().insert(value);
+            }
+            true
+        } else {
+            let values = self.values.read().unwrap();
+            !values.is_empty() && self.key.extract(record).is_some_and(|value| values.contains(&value))
+        }
+    }
+}
+
+/// Set of values that forgets the least recently inserted ones above the limit.
+struct CorrelatedValues {
+    // values along with the sequence number of their last insertion
+    values: HashMap<String, u64>,
+    // insertions in order, the ones with outdated sequence numbers are skipped
+    order: VecDeque<(u64, String)>,
+    seq: u64,
+    limit: Option<usize>,
+}
+
+impl CorrelatedValues {
+    fn new(limit: Option<usize>) -> Self {
+        Self {
+            values: HashMap::new(),
+            order: VecDeque::new(),
+            seq: 0,
+            limit,
+        }
+    }
+
+    fn insert(&mut self, value: String) {
+        let Some(limit) = self.limit else {
+            self.values.insert(value, 0);
+            return;
+        };
+
+        self.seq += 1;
+        if let Some(seq) = self.values.get_mut(&value) {
+            *seq = self.seq;
+        } else {
+            self.values.insert(value.clone(), self.seq);
+        }
+        self.order.push_back((self.seq, value));
+
+        while self.values.len() > limit {
+            let Some((seq, value)) = self.order.pop_front() else {
+                break;
+            };
+            if self.values.get(&value) == Some(&seq) {
+                self.values.remove(&value);
+            }
+        }
+        if self.order.len() > limit * 2 {
+            let values = &self.values;
+            self.order.retain(|(seq, value)| values.get(value) == Some(seq));
+        }
+    }
+
+    fn contains(&self, value: &str) -> bool {
+        self.values.contains_key(value)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+const MAX_CORRELATED_VALUES: usize = 64 * 1024;
+
+// ---
+
+/// Collects information about entries in modes showing an overview instead of the entries.
+trait Accumulator: Send {
+    fn add_valid(&mut self, record: &Record);
+    fn add_invalid(&mut self, n: u64);
+    fn merge(&mut self, other: Self);
+}
+
+struct SummaryAccumulator<'a> {
+    summary: Summary,
+    fields: &'a [FieldValueExtractor],
+}
+
+impl Accumulator for SummaryAccumulator<'_> {
+    #[inline]
+    fn add_valid(&mut self, record: &Record) {
+        self.summary.add_valid(record, self.fields);
+    }
+
+    #[inline]
+    fn add_invalid(&mut self, n: u64) {
+        self.summary.add_invalid(n);
+    }
+
+    #[inline]
+    fn merge(&mut self, other: Self) {
+        self.summary.merge(other.summary);
+    }
+}
+
+impl Accumulator for Histogram {
+    #[inline]
+    fn add_valid(&mut self, record: &Record) {
+        self.add(record);
+    }
+
+    #[inline]
+    fn add_invalid(&mut self, _: u64) {}
+
+    #[inline]
+    fn merge(&mut self, other: Self) {
+        Histogram::merge(self, other);
     }
 }
 
@@ -1290,9 +3062,11 @@ impl<T> StripedSender<T> {
 
 struct FollowBadges {
     si: SyncIndicator,
-    input: Option<Vec<String>>,
+    // updated when new files appear in the followed directories
+    input: RwLock<Option<Vec<String>>>,
 }
 
+#[derive(Default)]
 struct SyncIndicator {
     width: usize,
     synced: String,
@@ -1343,6 +3117,42 @@ where
     }
 }
 
+/// Returns true if the error concerns a single peer, so other peers can still be accepted.
+fn is_transient(err: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    matches!(
+        err.kind(),
+        ErrorKind::ConnectionAborted | ErrorKind::ConnectionReset | ErrorKind::Interrupted
+    )
+}
+
+/// Runs the function, and calls `interrupt` from another thread if the `stop` channel is closed before it returns,
+/// so that a blocking operation it waits for can be interrupted.
+fn interruptible<T, I, F>(stop: &Receiver<()>, interrupt: I, f: F) -> T
+where
+    I: FnOnce() + Send,
+    F: FnOnce() -> T,
+{
+    let (txd, rxd) = channel::bounded::<()>(0);
+    thread::scope(|scope| {
+        scope.spawn(|_| {
+            channel::select! {
+                recv(stop) -> _ => interrupt(),
+                recv(rxd) -> _ => {}
+            }
+        });
+        let result = f();
+        drop(txd);
+        result
+    })
+    .unwrap()
+}
+
+/// Returns true if the `stop` channel is closed.
+fn stopped(stop: &Receiver<()>) -> bool {
+    stop.try_recv().is_err_and(|e| e.is_disconnected())
+}
+
 #[allow(dead_code)]
 fn grapheme_slice_width(graphemes: &[impl AsRef<str>]) -> usize {
     graphemes.iter().map(|g| g.as_ref().width()).sum()
@@ -177,6 +177,206 @@ fn test_sort_with_blank_lines() {
     );
 }
 
+#[test]
+fn test_sort_reverse() {
+    let input = input(concat!(
+        r#"{"level":"debug","ts":"2024-01-25T19:10:20.435369+01:00","msg":"m2"}"#,
+        "\n",
+        r#"{"level":"debug","ts":"2024-01-25T19:09:16.860711+01:00","msg":"m1"}"#,
+        "\n",
+        r#"{"level":"debug","ts":"2024-01-25T19:11:00.000000+01:00","msg":"m3"}"#,
+        "\n",
+    ));
+
+    let mut output = Vec::new();
+    let app = App::new(options().with_sort(true).with_reverse(true));
+    app.run(vec![input], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:11:00.000 |DBG| m3\n",
+            "2024-01-25 18:10:20.435 |DBG| m2\n",
+            "2024-01-25 18:09:16.860 |DBG| m1\n",
+        ),
+    );
+}
+
+#[test]
+fn test_sort_reverse_multiple_blocks() {
+    let data = (0..20)
+        .map(|i| {
+            format!(
+                r#"{{"level":"info","ts":"2024-01-25T18:00:{:02}Z","msg":"m{}"}}"#,
+                (i * 7) % 20,
+                (i * 7) % 20
+            ) + "\n"
+        })
+        .collect::<String>();
+
+    let mut output = Vec::new();
+    let app = App::new(Options {
+        buffer_size: NonZeroUsize::new(128).unwrap(),
+        max_message_size: NonZeroUsize::new(128).unwrap(),
+        sort: true,
+        reverse: true,
+        ..options()
+    });
+    app.run(vec![input(data)], &mut output).unwrap();
+    let expected = (0..20)
+        .rev()
+        .map(|i| format!("2024-01-25 18:00:{:02}.000 |INF| m{}\n", i, i))
+        .collect::<String>();
+    assert_eq!(std::str::from_utf8(&output).unwrap(), expected);
+}
+
+#[test]
+fn test_max_count() {
+    let data = concat!(
+        "level=info time=2024-01-25T18:00:03Z msg=m3\n",
+        "level=info time=2024-01-25T18:00:01Z msg=m1\n",
+        "not parsed\n",
+        "level=info time=2024-01-25T18:00:02Z msg=m2\n",
+    );
+
+    let mut output = Vec::new();
+    let app = App::new(Options {
+        max_count: Some(2),
+        ..options()
+    });
+    app.run(vec![input(data)], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:03.000 |INF| m3\n",
+            "2024-01-25 18:00:01.000 |INF| m1\n",
+        ),
+    );
+
+    let mut output = Vec::new();
+    let app = App::new(Options {
+        max_count: Some(2),
+        ..options().with_sort(true)
+    });
+    app.run(vec![input(data)], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:01.000 |INF| m1\n",
+            "2024-01-25 18:00:02.000 |INF| m2\n",
+        ),
+    );
+
+    let mut output = Vec::new();
+    let app = App::new(Options {
+        max_count: Some(0),
+        ..options()
+    });
+    app.run(vec![input(data)], &mut output).unwrap();
+    assert_eq!(std::str::from_utf8(&output).unwrap(), "");
+}
+
+#[test]
+fn test_last() {
+    let data = concat!(
+        "level=info time=2024-01-25T18:00:03Z msg=m3\n",
+        "level=info time=2024-01-25T18:00:01Z msg=m1\n",
+        "level=info time=2024-01-25T18:00:04Z msg=m4\n",
+        "level=info time=2024-01-25T18:00:02Z msg=m2\n",
+    );
+
+    let mut output = Vec::new();
+    let app = App::new(Options {
+        last: Some(2),
+        ..options().with_sort(true)
+    });
+    app.run(vec![input(data)], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:03.000 |INF| m3\n",
+            "2024-01-25 18:00:04.000 |INF| m4\n",
+        ),
+    );
+
+    let mut output = Vec::new();
+    let app = App::new(Options {
+        last: Some(3),
+        ..options().with_sort(true).with_reverse(true)
+    });
+    app.run(vec![input(data)], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:03.000 |INF| m3\n",
+            "2024-01-25 18:00:02.000 |INF| m2\n",
+            "2024-01-25 18:00:01.000 |INF| m1\n",
+        ),
+    );
+}
+
+#[test]
+fn test_sort_by() {
+    let data = concat!(
+        "level=info time=2024-01-25T18:00:03Z msg=m3 duration=15\n",
+        "level=info time=2024-01-25T18:00:01Z msg=m1 duration=2.5\n",
+        "level=info time=2024-01-25T18:00:04Z msg=m4\n",
+        "level=info time=2024-01-25T18:00:02Z msg=m2 duration=15\n",
+        "level=info time=2024-01-25T18:00:05Z msg=m5 duration=100\n",
+    );
+
+    let mut output = Vec::new();
+    let app = App::new(options().with_sort_by("duration", false));
+    app.run(vec![input(data)], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:01.000 |INF| m1 duration=2.5\n",
+            "2024-01-25 18:00:02.000 |INF| m2 duration=15\n",
+            "2024-01-25 18:00:03.000 |INF| m3 duration=15\n",
+            "2024-01-25 18:00:05.000 |INF| m5 duration=100\n",
+            "2024-01-25 18:00:04.000 |INF| m4\n",
+        ),
+    );
+
+    let mut output = Vec::new();
+    let app = App::new(Options {
+        max_count: Some(3),
+        ..options().with_sort_by("duration", true)
+    });
+    app.run(vec![input(data)], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:05.000 |INF| m5 duration=100\n",
+            "2024-01-25 18:00:02.000 |INF| m2 duration=15\n",
+            "2024-01-25 18:00:03.000 |INF| m3 duration=15\n",
+        ),
+    );
+}
+
+#[test]
+fn test_sort_by_incomplete_segment() {
+    let input = input(concat!(
+        "level=debug time=2024-01-25T19:10:20.435369+01:00 msg=m1 a.b.c=10 a.b.d=20 a.c.b=11\n",
+        "level=debug time=2024-01-25T19:10:21.764733+01:00 msg=m2 x=2\n"
+    ));
+
+    let mut output = Vec::new();
+    let app = App::new(Options {
+        buffer_size: NonZeroUsize::new(32).unwrap(),
+        max_message_size: NonZeroUsize::new(64).unwrap(),
+        ..options().with_sort_by("x", false)
+    });
+    app.run(vec![input], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:10:21.764 |DBG| m2 x=2\n",
+            "level=debug time=2024-01-25T19:10:20.435369+01:00 msg=m1 a.b.c=10 a.b.d=20 a.c.b=11\n",
+        )
+    );
+}
+
 #[test]
 fn test_filter_with_blank_lines() {
     let input = input(concat!(
@@ -551,6 +751,10 @@ fn options() -> Options {
         max_message_size: NonZeroUsize::new(4096 * 1024).unwrap(),
         concurrency: 1,
         filter: Default::default(),
+        context: ContextOptions::default(),
+        correlate_by: None,
+        stats: None,
+        histogram: None,
         fields: FieldOptions::default(),
         formatting: Formatting {
             message: MessageFormatting {
@@ -561,11 +765,28 @@ fn options() -> Options {
         time_zone: Tz::IANA(UTC),
         hide_empty_fields: false,
         sort: false,
+        reverse: false,
+        max_count: None,
+        last: None,
+        sort_by: None,
         follow: false,
+        follow_dirs: Vec::new(),
+        listen: Vec::new(),
+        restart: false,
+        resume: None,
+        alert: None,
         sync_interval: Duration::from_secs(1),
         input_info: Default::default(),
         input_format: None,
+        status_levels: StatusLevels::default(),
+        text_formats: Arc::default(),
+        unwrap: None,
+        embedded_json: EmbeddedJsonExpansion::Disabled,
+        output_format: None,
+        template: None,
         dump_index: false,
+        indexed_fields: Vec::new(),
+        index_cache_limits: IndexCacheLimits::default(),
         app_dirs: None,
         tail: 0,
         delimiter: Delimiter::default(),
@@ -1393,8 +1614,9 @@ fn test_prepare_follow_badges() {
     assert!(result.si.failed.len() >= result.si.width);
     assert_eq!(result.si.placeholder.len(), result.si.width);
 
-    assert!(result.input.is_some());
-    let badges = result.input.unwrap();
+    let badges = result.input.into_inner().unwrap();
+    assert!(badges.is_some());
+    let badges = badges.unwrap();
     assert_eq!(badges.len(), 2);
 
     for badge in badges.iter() {
@@ -1424,8 +1646,9 @@ fn test_prepare_follow_badges_single_input() {
     assert!(result.si.failed.len() >= result.si.width);
     assert_eq!(result.si.placeholder.len(), result.si.width);
 
-    assert!(result.input.is_some());
-    let badges = result.input.unwrap();
+    let badges = result.input.into_inner().unwrap();
+    assert!(badges.is_some());
+    let badges = badges.unwrap();
     assert_eq!(badges.len(), 1);
     assert!(badges[0].starts_with(&result.si.placeholder));
 }
@@ -1451,8 +1674,9 @@ fn test_prepare_follow_badges_formatting() {
     let raw_badges = app.input_badges(inputs.iter()).unwrap();
     let result = app.prepare_follow_badges(inputs.iter());
 
-    assert!(result.input.is_some());
-    let formatted_badges = result.input.unwrap();
+    let formatted_badges = result.input.into_inner().unwrap();
+    assert!(formatted_badges.is_some());
+    let formatted_badges = formatted_badges.unwrap();
 
     for (i, formatted) in formatted_badges.iter().enumerate() {
         let raw = &raw_badges[i];
@@ -1480,7 +1704,7 @@ fn test_prepare_follow_badges_no_badges() {
     let result = app.prepare_follow_badges(inputs.iter());
 
     assert!(result.si.width > 0);
-    assert!(result.input.is_none());
+    assert!(result.input.into_inner().unwrap().is_none());
 }
 
 #[test]
@@ -1500,7 +1724,7 @@ fn test_process_segments() {
             failed: "!!".to_string(),
             placeholder: "  ".to_string(),
         },
-        input: Some(vec!["  [0] ".to_string(), "  [1] ".to_string()]),
+        input: RwLock::new(Some(vec!["  [0] ".to_string(), "  [1] ".to_string()])),
     };
 
     let (txi, rxi) = channel::bounded(10);
@@ -1510,7 +1734,7 @@ fn test_process_segments() {
     let segment_buf = SegmentBuf::from(log_data.as_bytes());
     let segment = Segment::Complete(segment_buf);
 
-    txi.send((0, 0, segment)).unwrap();
+    txi.send((0, 0, segment, true)).unwrap();
     drop(txi);
 
     app.process_segments(&parser, &bfo, &sfi, &badges, rxi, txo.clone());
@@ -1526,6 +1750,40 @@ fn test_process_segments() {
     }
 }
 
+#[test]
+fn test_process_segments_alert() {
+    let app = App::new(Options {
+        alert: Some(AlertOptions {
+            query: Query::parse("level=error").unwrap(),
+            command: None,
+            bell: false,
+            debounce: Duration::ZERO,
+            interval: Duration::ZERO,
+        }),
+        ..options()
+    });
+
+    let parser = app.parser();
+    let bfo = BufFactory::new(4096);
+    let sfi = SegmentBufFactory::new(4096);
+    let badges = test_badges(2);
+
+    let (txi, rxi) = channel::bounded(10);
+    let (txo, rxo) = channel::bounded(10);
+
+    // entries preloaded at start are not matched against the alert query, only the live ones are
+    for (j, live) in [false, true].into_iter().enumerate() {
+        let segment = Segment::Complete(SegmentBuf::from(b"level=error msg=failed\n"));
+        txi.send((0, j, segment, live)).unwrap();
+    }
+    drop(txi);
+
+    app.process_segments(&parser, &bfo, &sfi, &badges, rxi, txo);
+
+    let matches = rxo.iter().map(|(_, _, index)| index.matches.len()).collect_vec();
+    assert_eq!(matches, [0, 1]);
+}
+
 #[test]
 fn test_process_segments_incomplete() {
     let mut opts = options();
@@ -1543,7 +1801,7 @@ fn test_process_segments_incomplete() {
             failed: "!!".to_string(),
             placeholder: "  ".to_string(),
         },
-        input: Some(vec!["  [0] ".to_string()]),
+        input: RwLock::new(Some(vec!["  [0] ".to_string()])),
     };
 
     let (txi, rxi) = channel::bounded(10);
@@ -1551,13 +1809,19 @@ fn test_process_segments_incomplete() {
 
     let segment = Segment::Incomplete(SegmentBuf::from(b"incomplete"), PartialPlacement::First);
 
-    txi.send((0, 0, segment)).unwrap();
+    txi.send((0, 3, segment, true)).unwrap();
     drop(txi);
 
     app.process_segments(&parser, &bfo, &sfi, &badges, rxi, txo.clone());
     drop(txo);
 
-    assert!(rxo.try_recv().is_err(), "Incomplete segments should not produce output");
+    // incomplete segments produce no output but are still passed on to track the resume position
+    let (i, buf, index) = rxo.try_recv().unwrap();
+    assert_eq!(i, 0);
+    assert!(buf.is_empty());
+    assert_eq!(index.block, 3);
+    assert!(index.lines.is_empty());
+    assert!(rxo.try_recv().is_err());
 }
 
 #[test]
@@ -1577,7 +1841,7 @@ fn test_process_segments_receiver_dropped() {
             failed: "!!".to_string(),
             placeholder: "  ".to_string(),
         },
-        input: Some(vec!["  [0] ".to_string()]),
+        input: RwLock::new(Some(vec!["  [0] ".to_string()])),
     };
 
     let (txi, rxi) = channel::bounded(10);
@@ -1588,7 +1852,7 @@ fn test_process_segments_receiver_dropped() {
 
     drop(rxo);
 
-    txi.send((0, 0, segment)).unwrap();
+    txi.send((0, 0, segment, true)).unwrap();
     drop(txi);
 
     app.process_segments(&parser, &bfo, &sfi, &badges, rxi, txo);
@@ -2069,7 +2333,7 @@ fn test_badges(width: usize) -> FollowBadges {
             failed: "F".repeat(width),
             placeholder: " ".repeat(width),
         },
-        input: None,
+        input: RwLock::new(None),
     }
 }
 
@@ -2091,12 +2355,14 @@ fn test_merge_segments_single_line() {
             location: 0..buf.len(),
             ts: ts(100, 0),
         }],
+        matches: Vec::new(),
     };
     txo.send((0, buf, index)).unwrap();
     drop(txo);
 
     let mut output = Vec::new();
-    app.merge_segments(&badges, rxo, &mut output, 1).unwrap();
+    app.merge_segments(&badges, rxo, channel::never(), &mut output, 1, None)
+        .unwrap();
 
     let result = String::from_utf8(output).unwrap();
     assert_eq!(result, "SShello world\n");
@@ -2116,6 +2382,7 @@ fn test_merge_segments_ordered_timestamps() {
             location: 0..buf1.len(),
             ts: ts(100, 0),
         }],
+        matches: Vec::new(),
     };
 
     let buf2 = b"  line-two".to_vec();
@@ -2125,6 +2392,7 @@ fn test_merge_segments_ordered_timestamps() {
             location: 0..buf2.len(),
             ts: ts(200, 0),
         }],
+        matches: Vec::new(),
     };
 
     txo.send((0, buf1, index1)).unwrap();
@@ -2132,7 +2400,8 @@ fn test_merge_segments_ordered_timestamps() {
     drop(txo);
 
     let mut output = Vec::new();
-    app.merge_segments(&badges, rxo, &mut output, 1).unwrap();
+    app.merge_segments(&badges, rxo, channel::never(), &mut output, 1, None)
+        .unwrap();
 
     let result = String::from_utf8(output).unwrap();
     assert_eq!(result, "SSline-one\nSSline-two\n");
@@ -2152,6 +2421,7 @@ fn test_merge_segments_interleaved_sources() {
             location: 0..buf0.len(),
             ts: ts(200, 0),
         }],
+        matches: Vec::new(),
     };
 
     let buf1 = b"  from-source-1".to_vec();
@@ -2161,6 +2431,7 @@ fn test_merge_segments_interleaved_sources() {
             location: 0..buf1.len(),
             ts: ts(100, 0),
         }],
+        matches: Vec::new(),
     };
 
     txo.send((0, buf0, index0)).unwrap();
@@ -2168,7 +2439,8 @@ fn test_merge_segments_interleaved_sources() {
     drop(txo);
 
     let mut output = Vec::new();
-    app.merge_segments(&badges, rxo, &mut output, 1).unwrap();
+    app.merge_segments(&badges, rxo, channel::never(), &mut output, 1, None)
+        .unwrap();
 
     let result = String::from_utf8(output).unwrap();
     // Source 1 (ts=100) should come before Source 0 (ts=200)
@@ -2192,6 +2464,7 @@ fn test_merge_segments_out_of_order_shows_failed() {
             location: 0..buf1.len(),
             ts: ts(200, 0),
         }],
+        matches: Vec::new(),
     };
     txo.send((0, buf1, index1)).unwrap();
 
@@ -2203,12 +2476,14 @@ fn test_merge_segments_out_of_order_shows_failed() {
             location: 0..buf2.len(),
             ts: ts(100, 0),
         }],
+        matches: Vec::new(),
     };
     txo.send((0, buf2, index2)).unwrap();
     drop(txo);
 
     let mut output = Vec::new();
-    app.merge_segments(&badges, rxo, &mut output, 1).unwrap();
+    app.merge_segments(&badges, rxo, channel::never(), &mut output, 1, None)
+        .unwrap();
 
     let result = String::from_utf8(output).unwrap();
     // ts=200 flushed first (synced), then ts=100 arrives and gets flushed (failed because 200 > 100)
@@ -2223,7 +2498,8 @@ fn test_merge_segments_empty_channel() {
     drop(txo);
 
     let mut output = Vec::new();
-    app.merge_segments(&badges, rxo, &mut output, 1).unwrap();
+    app.merge_segments(&badges, rxo, channel::never(), &mut output, 1, None)
+        .unwrap();
 
     assert!(output.is_empty());
 }
@@ -2248,12 +2524,14 @@ fn test_merge_segments_multiple_lines_per_segment() {
                 ts: ts(100, 0),
             },
         ],
+        matches: Vec::new(),
     };
     txo.send((0, buf, index)).unwrap();
     drop(txo);
 
     let mut output = Vec::new();
-    app.merge_segments(&badges, rxo, &mut output, 1).unwrap();
+    app.merge_segments(&badges, rxo, channel::never(), &mut output, 1, None)
+        .unwrap();
 
     let result = String::from_utf8(output).unwrap();
     // BTreeMap sorts by timestamp, so ts=100 (line-B) comes first
@@ -2277,12 +2555,14 @@ fn test_merge_segments_gap_with_timestamp() {
             location: 9..17, // "  line-A"
             ts: ts(100, 0),
         }],
+        matches: Vec::new(),
     };
     txo.send((0, buf, index)).unwrap();
     drop(txo);
 
     let mut output = Vec::new();
-    app.merge_segments(&badges, rxo, &mut output, 1).unwrap();
+    app.merge_segments(&badges, rxo, channel::never(), &mut output, 1, None)
+        .unwrap();
 
     let result = String::from_utf8(output).unwrap();
     // Gap "gap-text" gets ts=100 from the next indexed line, inserted into window.
@@ -2305,12 +2585,14 @@ fn test_merge_segments_gap_without_timestamp_direct_output() {
     let index = TimestampIndex {
         block: 0,
         lines: vec![],
+        matches: Vec::new(),
     };
     txo.send((0, buf, index)).unwrap();
     drop(txo);
 
     let mut output = Vec::new();
-    app.merge_segments(&badges, rxo, &mut output, 1).unwrap();
+    app.merge_segments(&badges, rxo, channel::never(), &mut output, 1, None)
+        .unwrap();
 
     let result = String::from_utf8(output).unwrap();
     // No timestamp available → gap written directly (raw, no sync indicator stripping)
@@ -2334,12 +2616,14 @@ fn test_merge_segments_gap_trimming_no_trailing_delimiter() {
             location: 14..22, // "  line-A"
             ts: ts(100, 0),
         }],
+        matches: Vec::new(),
     };
     txo.send((0, buf, index)).unwrap();
     drop(txo);
 
     let mut output = Vec::new();
-    app.merge_segments(&badges, rxo, &mut output, 1).unwrap();
+    app.merge_segments(&badges, rxo, channel::never(), &mut output, 1, None)
+        .unwrap();
 
     let result = String::from_utf8(output).unwrap();
     // Gap "  gap-no-delim" doesn't end with \n, so trimmed=14=end.
@@ -2370,12 +2654,14 @@ fn test_merge_segments_empty_gap_skipped() {
                 ts: ts(200, 0),
             },
         ],
+        matches: Vec::new(),
     };
     txo.send((0, buf, index)).unwrap();
     drop(txo);
 
     let mut output = Vec::new();
-    app.merge_segments(&badges, rxo, &mut output, 1).unwrap();
+    app.merge_segments(&badges, rxo, channel::never(), &mut output, 1, None)
+        .unwrap();
 
     let result = String::from_utf8(output).unwrap();
     // Gap between lines: start = 0+8+1 = 9, end = 9 → start >= end, skipped
@@ -2398,6 +2684,7 @@ fn test_merge_segments_trailing_gap_with_source_history() {
             location: 0..8,
             ts: ts(100, 0),
         }],
+        matches: Vec::new(),
     };
 
     // Second segment from same source has an indexed line + trailing gap
@@ -2409,6 +2696,7 @@ fn test_merge_segments_trailing_gap_with_source_history() {
             location: 0..8, // "  line-B"
             ts: ts(200, 0),
         }],
+        matches: Vec::new(),
     };
 
     txo.send((0, buf1, index1)).unwrap();
@@ -2416,7 +2704,8 @@ fn test_merge_segments_trailing_gap_with_source_history() {
     drop(txo);
 
     let mut output = Vec::new();
-    app.merge_segments(&badges, rxo, &mut output, 1).unwrap();
+    app.merge_segments(&badges, rxo, channel::never(), &mut output, 1, None)
+        .unwrap();
 
     let result = String::from_utf8(output).unwrap();
     // line-A (ts=100) flushed first, then segment 2 arrives.
@@ -2425,3 +2714,690 @@ fn test_merge_segments_trailing_gap_with_source_history() {
     // So: trailing-gap(ts=100) sorts before line-B(ts=200).
     assert_eq!(result, "SSline-A\nSStrailing-gap\nSSline-B\n");
 }
+
+fn context_input() -> InputHolder {
+    input(concat!(
+        r#"{"level":"info","ts":"2024-01-25T18:00:01Z","msg":"m1"}"#,
+        "\n",
+        r#"{"level":"error","ts":"2024-01-25T18:00:02Z","msg":"m2"}"#,
+        "\n",
+        r#"{"level":"info","ts":"2024-01-25T18:00:03Z","msg":"m3"}"#,
+        "\n",
+        r#"{"level":"info","ts":"2024-01-25T18:00:04Z","msg":"m4"}"#,
+        "\n",
+        r#"{"level":"info","ts":"2024-01-25T18:00:05Z","msg":"m5"}"#,
+        "\n",
+        r#"{"level":"error","ts":"2024-01-25T18:00:06Z","msg":"m6"}"#,
+        "\n",
+        r#"{"level":"info","ts":"2024-01-25T18:00:07Z","msg":"m7"}"#,
+        "\n",
+    ))
+}
+
+fn error_filter() -> Arc<AdvancedFilter> {
+    Filter {
+        level: Some(Level::Error),
+        ..Default::default()
+    }
+    .into()
+}
+
+#[test]
+fn test_context_cat() {
+    let mut output = Vec::new();
+    let app = App::new(
+        options()
+            .with_filter(error_filter())
+            .with_context(ContextOptions { before: 1, after: 1 }),
+    );
+    app.run(vec![context_input()], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:01.000 |INF| m1\n",
+            "2024-01-25 18:00:02.000 |ERR| m2\n",
+            "2024-01-25 18:00:03.000 |INF| m3\n",
+            "--\n",
+            "2024-01-25 18:00:05.000 |INF| m5\n",
+            "2024-01-25 18:00:06.000 |ERR| m6\n",
+            "2024-01-25 18:00:07.000 |INF| m7\n",
+        ),
+    );
+}
+
+#[test]
+fn test_context_max_count() {
+    for buffer_size in [64, 4096] {
+        let mut output = Vec::new();
+        let app = App::new(Options {
+            max_count: Some(1),
+            buffer_size: NonZeroUsize::new(buffer_size).unwrap(),
+            ..options()
+                .with_filter(error_filter())
+                .with_context(ContextOptions { before: 1, after: 2 })
+        });
+        app.run(vec![context_input()], &mut output).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&output).unwrap(),
+            concat!(
+                "2024-01-25 18:00:01.000 |INF| m1\n",
+                "2024-01-25 18:00:02.000 |ERR| m2\n",
+                "2024-01-25 18:00:03.000 |INF| m3\n",
+                "2024-01-25 18:00:04.000 |INF| m4\n",
+            ),
+            "buffer size {buffer_size}",
+        );
+    }
+}
+
+#[test]
+fn test_context_cat_adjacent_groups() {
+    let mut output = Vec::new();
+    let app = App::new(
+        options()
+            .with_filter(error_filter())
+            .with_context(ContextOptions { before: 2, after: 1 }),
+    );
+    app.run(vec![context_input()], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:01.000 |INF| m1\n",
+            "2024-01-25 18:00:02.000 |ERR| m2\n",
+            "2024-01-25 18:00:03.000 |INF| m3\n",
+            "2024-01-25 18:00:04.000 |INF| m4\n",
+            "2024-01-25 18:00:05.000 |INF| m5\n",
+            "2024-01-25 18:00:06.000 |ERR| m6\n",
+            "2024-01-25 18:00:07.000 |INF| m7\n",
+        ),
+    );
+}
+
+#[test]
+fn test_context_cat_after_only() {
+    let mut output = Vec::new();
+    let app = App::new(
+        options()
+            .with_filter(error_filter())
+            .with_context(ContextOptions { before: 0, after: 1 }),
+    );
+    app.run(vec![context_input()], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:02.000 |ERR| m2\n",
+            "2024-01-25 18:00:03.000 |INF| m3\n",
+            "--\n",
+            "2024-01-25 18:00:06.000 |ERR| m6\n",
+            "2024-01-25 18:00:07.000 |INF| m7\n",
+        ),
+    );
+}
+
+#[test]
+fn test_context_sort() {
+    let input = input(concat!(
+        r#"{"level":"info","ts":"2024-01-25T18:00:05Z","msg":"m5"}"#,
+        "\n",
+        r#"{"level":"error","ts":"2024-01-25T18:00:02Z","msg":"m2"}"#,
+        "\n",
+        r#"{"level":"info","ts":"2024-01-25T18:00:01Z","msg":"m1"}"#,
+        "\n",
+        r#"{"level":"info","ts":"2024-01-25T18:00:04Z","msg":"m4"}"#,
+        "\n",
+        r#"{"level":"info","ts":"2024-01-25T18:00:03Z","msg":"m3"}"#,
+        "\n",
+    ));
+
+    let mut output = Vec::new();
+    let app = App::new(
+        options()
+            .with_sort(true)
+            .with_filter(error_filter())
+            .with_context(ContextOptions { before: 1, after: 1 }),
+    );
+    app.run(vec![input], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:01.000 |INF| m1\n",
+            "2024-01-25 18:00:02.000 |ERR| m2\n",
+            "2024-01-25 18:00:03.000 |INF| m3\n",
+        ),
+    );
+}
+
+#[test]
+fn test_context_sort_max_count() {
+    let mut output = Vec::new();
+    let app = App::new(Options {
+        max_count: Some(1),
+        ..options()
+            .with_sort(true)
+            .with_filter(error_filter())
+            .with_context(ContextOptions { before: 1, after: 2 })
+    });
+    app.run(vec![context_input()], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:01.000 |INF| m1\n",
+            "2024-01-25 18:00:02.000 |ERR| m2\n",
+            "2024-01-25 18:00:03.000 |INF| m3\n",
+            "2024-01-25 18:00:04.000 |INF| m4\n",
+        ),
+    );
+
+    // after-context ends at the next matching entry
+    let mut output = Vec::new();
+    let app = App::new(Options {
+        max_count: Some(1),
+        ..options()
+            .with_sort(true)
+            .with_filter(error_filter())
+            .with_context(ContextOptions { before: 0, after: 5 })
+    });
+    app.run(vec![context_input()], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:02.000 |ERR| m2\n",
+            "2024-01-25 18:00:03.000 |INF| m3\n",
+            "2024-01-25 18:00:04.000 |INF| m4\n",
+            "2024-01-25 18:00:05.000 |INF| m5\n",
+        ),
+    );
+}
+
+#[test]
+fn test_context_sort_last() {
+    let mut output = Vec::new();
+    let app = App::new(Options {
+        last: Some(1),
+        ..options()
+            .with_sort(true)
+            .with_filter(error_filter())
+            .with_context(ContextOptions { before: 2, after: 1 })
+    });
+    app.run(vec![context_input()], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:04.000 |INF| m4\n",
+            "2024-01-25 18:00:05.000 |INF| m5\n",
+            "2024-01-25 18:00:06.000 |ERR| m6\n",
+            "2024-01-25 18:00:07.000 |INF| m7\n",
+        ),
+    );
+}
+
+#[test]
+fn test_context_styled() {
+    let mut output = Vec::new();
+    let app = App::new(
+        options()
+            .with_theme(theme())
+            .with_filter(error_filter())
+            .with_context(ContextOptions { before: 1, after: 0 }),
+    );
+    app.run(vec![context_input()], &mut output).unwrap();
+    let output = std::str::from_utf8(&output).unwrap();
+    let lines = output.lines().collect_vec();
+    assert_eq!(lines.len(), 5);
+    assert!(!lines[0].contains("\u{1b}[0;36m|INF|"));
+    assert!(lines[0].contains("\u{1b}[0;2;36m|INF|"));
+    assert_eq!(lines[2], "--");
+}
+
+#[test]
+fn test_context_without_filter() {
+    let mut output = Vec::new();
+    let app = App::new(options().with_context(ContextOptions { before: 1, after: 1 }));
+    app.run(vec![context_input()], &mut output).unwrap();
+    assert_eq!(std::str::from_utf8(&output).unwrap().lines().count(), 7);
+}
+
+fn correlation_input() -> InputHolder {
+    input(concat!(
+        r#"{"level":"info","ts":"2024-01-25T18:00:01Z","msg":"m1","trace":"a"}"#,
+        "\n",
+        r#"{"level":"info","ts":"2024-01-25T18:00:02Z","msg":"m2","trace":"b"}"#,
+        "\n",
+        r#"{"level":"error","ts":"2024-01-25T18:00:03Z","msg":"m3","trace":"a"}"#,
+        "\n",
+        r#"{"level":"info","ts":"2024-01-25T18:00:04Z","msg":"m4","trace":"b"}"#,
+        "\n",
+        r#"{"level":"info","ts":"2024-01-25T18:00:05Z","msg":"m5","trace":"a"}"#,
+        "\n",
+        r#"{"level":"info","ts":"2024-01-25T18:00:06Z","msg":"m6"}"#,
+        "\n",
+    ))
+}
+
+#[test]
+fn test_correlate_cat() {
+    let mut output = Vec::new();
+    let app = App::new(options().with_filter(error_filter()).with_correlate_by("trace"));
+    app.run(vec![correlation_input()], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:03.000 |ERR| m3 trace=a\n",
+            "2024-01-25 18:00:05.000 |INF| m5 trace=a\n",
+        ),
+    );
+}
+
+#[test]
+fn test_correlated_values_limit() {
+    let mut values = CorrelatedValues::new(Some(2));
+    values.insert("a".into());
+    values.insert("b".into());
+    values.insert("a".into());
+    values.insert("c".into());
+    assert!(values.contains("a"));
+    assert!(!values.contains("b"));
+    assert!(values.contains("c"));
+
+    for i in 0..10 {
+        values.insert(i.to_string());
+    }
+    assert!(values.contains("8") && values.contains("9"));
+    assert!(!values.contains("a"));
+    assert!(values.order.len() <= 4);
+
+    let mut values = CorrelatedValues::new(None);
+    for i in 0..10 {
+        values.insert(i.to_string());
+    }
+    assert!((0..10).all(|i| values.contains(&i.to_string())));
+}
+
+#[test]
+fn test_correlate_sort() {
+    let mut output = Vec::new();
+    let app = App::new(
+        options()
+            .with_sort(true)
+            .with_filter(error_filter())
+            .with_correlate_by("trace"),
+    );
+    app.run(vec![correlation_input()], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "2024-01-25 18:00:01.000 |INF| m1 trace=a\n",
+            "2024-01-25 18:00:03.000 |ERR| m3 trace=a\n",
+            "2024-01-25 18:00:05.000 |INF| m5 trace=a\n",
+        ),
+    );
+}
+
+#[test]
+fn test_output_format_json() {
+    let input = input(concat!(
+        r#"{"level":"info","ts":"2024-01-25T18:00:01Z","msg":"m1","a":{"b":1}}"#,
+        "\n",
+        r#"level=warn ts=2024-01-25T19:00:02+01:00 msg="m 2" a.b=2"#,
+        "\n",
+    ));
+
+    let mut output = Vec::new();
+    let app = App::new(options().with_output_format(OutputFormat::Json));
+    app.run(vec![input], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            r#"{"time":"2024-01-25T18:00:01Z","level":"info","message":"m1","a":{"b":1}}"#,
+            "\n",
+            r#"{"time":"2024-01-25T18:00:02Z","level":"warning","message":"m 2","a.b":2}"#,
+            "\n",
+        ),
+    );
+}
+
+#[test]
+fn test_output_format_json_inputs() {
+    let inputs = vec![
+        context_input(),
+        input(concat!(
+            r#"{"level":"error","ts":"2024-01-25T18:00:09Z","msg":"n1"}"#,
+            "\n",
+            "not a record\n",
+        )),
+    ];
+
+    let mut output = Vec::new();
+    let app = App::new(
+        options()
+            .with_output_format(OutputFormat::Json)
+            .with_filter(error_filter())
+            .with_context(ContextOptions { before: 0, after: 1 }),
+    );
+    app.run(inputs, &mut output).unwrap();
+
+    // neither input badges nor context separators break JSON lines
+    let output = std::str::from_utf8(&output).unwrap();
+    let messages = output
+        .lines()
+        .map(|line| {
+            json::from_str::<json::Value>(line).unwrap()["message"]
+                .as_str()
+                .unwrap()
+                .to_owned()
+        })
+        .collect_vec();
+    assert_eq!(messages, ["m2", "m3", "m6", "m7", "n1"], "{output}");
+}
+
+#[test]
+fn test_output_format_logfmt() {
+    let input = input(concat!(
+        r#"{"level":"error","ts":"2024-01-25T18:00:01Z","msg":"m1","a":{"b":"x y"},"c":[1,2]}"#,
+        "\n",
+    ));
+
+    let mut output = Vec::new();
+    let app = App::new(options().with_output_format(OutputFormat::Logfmt));
+    app.run(vec![input], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            r#"time=2024-01-25T18:00:01Z level=error message=m1 a.b="x y" c="[1,2]""#,
+            "\n",
+        ),
+    );
+}
+
+#[test]
+fn test_template() {
+    let input = input(concat!(
+        r#"{"level":"error","ts":"2024-01-25T18:00:01Z","msg":"m1","http":{"status":500,"method":"GET"},"x":1}"#,
+        "\n",
+    ));
+
+    let mut output = Vec::new();
+    let app = App::new(options().with_template("{time} {level} {http.status:>4} {message} {fields}"));
+    app.run(vec![input], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        "2024-01-25 18:00:01.000 ERR  500 m1 http={ method=GET } x=1\n",
+    );
+}
+
+#[test]
+fn test_template_with_output_format() {
+    let input = input(concat!(
+        r#"{"level":"error","ts":"2024-01-25T18:00:01Z","msg":"m1","http":{"status":500},"x":1}"#,
+        "\n",
+    ));
+
+    let mut output = Vec::new();
+    let app = App::new(
+        options()
+            .with_template("{time} {level} {http.status} {message}")
+            .with_output_format(OutputFormat::Json),
+    );
+    app.run(vec![input], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            r#"{"time":"2024-01-25T18:00:01Z","level":"error","message":"m1","http":{"status":500},"x":1}"#,
+            "\n",
+        ),
+    );
+}
+
+#[test]
+fn test_stats() {
+    let input = input(concat!(
+        r#"{"level":"info","ts":"2024-01-25T18:00:01Z","msg":"m1","logger":"a","service":"x"}"#,
+        "\n",
+        r#"{"level":"error","ts":"2024-01-25T18:00:03Z","msg":"m2","logger":"b","service":"y"}"#,
+        "\n",
+        "not a json\n",
+        r#"{"level":"info","ts":"2024-01-25T18:00:02Z","msg":"m3","logger":"a","service":"x"}"#,
+        "\n",
+    ));
+
+    let mut output = Vec::new();
+    let app = App::new(options().with_stats(StatsOptions {
+        fields: vec!["service".into()],
+        top: 1,
+    }));
+    app.run(vec![input], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "Entries\n",
+            "  total    4\n",
+            "  valid    3\n",
+            "  invalid  1\n",
+            "Time span\n",
+            "  first  2024-01-25 18:00:01.000\n",
+            "  last   2024-01-25 18:00:03.000\n",
+            "Levels\n",
+            "  error    1\n",
+            "  warning  0\n",
+            "  info     2\n",
+            "  debug    0\n",
+            "  trace    0\n",
+            "Top loggers\n",
+            "  a  2\n",
+            "Top values of \"service\"\n",
+            "  x  2\n",
+        ),
+    );
+}
+
+#[test]
+fn test_stats_correlated() {
+    let mut output = Vec::new();
+    let app = App::new(Options {
+        buffer_size: NonZeroUsize::new(128).unwrap(),
+        ..options()
+            .with_filter(error_filter())
+            .with_correlate_by("trace")
+            .with_stats(StatsOptions {
+                fields: Vec::new(),
+                top: 1,
+            })
+    });
+    app.run(vec![correlation_input()], &mut output).unwrap();
+    let output = std::str::from_utf8(&output).unwrap();
+    assert!(output.contains("  valid    3\n"), "{output}");
+    assert!(output.contains("  error    1\n"), "{output}");
+    assert!(output.contains("  info     2\n"), "{output}");
+}
+
+#[test]
+fn test_histogram() {
+    let input = input(concat!(
+        r#"{"level":"info","ts":"2024-01-25T18:00:01Z","msg":"m1"}"#,
+        "\n",
+        r#"{"level":"error","ts":"2024-01-25T18:00:01.500Z","msg":"m2"}"#,
+        "\n",
+        "not a json\n",
+        r#"{"level":"info","ts":"2024-01-25T18:00:03Z","msg":"m3"}"#,
+        "\n",
+    ));
+
+    let mut output = Vec::new();
+    let app = App::new(options().with_histogram(HistogramOptions {
+        bucket: Some(Duration::from_secs(1)),
+        width: 0,
+    }));
+    app.run(vec![input], &mut output).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&output).unwrap(),
+        concat!(
+            "                            █ error  █ info\n",
+            "2024-01-25 18:00:01.000  2  ██████████\n",
+            "2024-01-25 18:00:02.000  0\n",
+            "2024-01-25 18:00:03.000  1  █████\n",
+        ),
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_follow_commands() {
+    use crate::command::Command;
+
+    let command = |line: &str| InputHolder::new(InputReference::Process(Command::parse(line).unwrap()), None);
+
+    let app = App::new(Options {
+        follow: true,
+        input_info: InputInfo::Full.into(),
+        ..options()
+    });
+
+    let mut output = Vec::new();
+    let inputs = vec![
+        command("sh -c 'x=first; echo ts=2024-01-02T03:04:05Z msg=$x-output'"),
+        command("sh -c 'x=second; echo ts=2024-01-02T03:04:06Z msg=$x-output >&2'"),
+    ];
+    app.run(inputs, &mut output).unwrap();
+
+    let output = std::str::from_utf8(&output).unwrap();
+    let lines = output.lines().collect_vec();
+    assert_eq!(lines.len(), 2, "{output}");
+    assert!(
+        lines[0].contains("x=first;") && lines[0].contains("first-output"),
+        "{output}"
+    );
+    assert!(
+        lines[1].contains("x=second;") && lines[1].contains("second-output"),
+        "{output}"
+    );
+
+    let mut output = Vec::new();
+    let inputs = vec![command("sh -c 'echo msg=failing; exit 5'")];
+    let err = app.run(inputs, &mut output).unwrap_err();
+    assert!(matches!(&err, Error::CommandFailed { command, .. } if command == "sh -c 'echo msg=failing; exit 5'"));
+    assert_eq!(err.exit_code(), 5);
+    assert!(std::str::from_utf8(&output).unwrap().contains("failing"));
+
+    // the commands still running are stopped after the first failure
+    let mut output = Vec::new();
+    let inputs = vec![command("sleep 60"), command("sh -c 'exit 3'")];
+    let started = std::time::Instant::now();
+    let err = app.run(inputs, &mut output).unwrap_err();
+    assert_eq!(err.exit_code(), 3);
+    assert!(started.elapsed() < Duration::from_secs(30));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_follow_context() {
+    use crate::command::Command;
+
+    let command = |lines: &[&str]| {
+        let script = lines.iter().map(|line| format!("{line}\\n")).collect::<String>();
+        InputHolder::new(
+            InputReference::Process(Command::parse(&format!("printf '{script}'")).unwrap()),
+            None,
+        )
+    };
+
+    let app = App::new(Options {
+        follow: true,
+        ..options()
+            .with_filter(error_filter())
+            .with_context(ContextOptions { before: 1, after: 1 })
+    });
+
+    let mut output = Vec::new();
+    let inputs = vec![
+        command(&[
+            "ts=2024-01-25T18:00:01Z level=info msg=a1",
+            "ts=2024-01-25T18:00:02Z level=error msg=a2",
+            "ts=2024-01-25T18:00:03Z level=info msg=a3",
+            "ts=2024-01-25T18:00:04Z level=info msg=a4",
+            "ts=2024-01-25T18:00:05Z level=info msg=a5",
+            "ts=2024-01-25T18:00:08Z level=error msg=a8",
+        ]),
+        command(&[
+            "ts=2024-01-25T18:00:06Z level=error msg=b6",
+            "ts=2024-01-25T18:00:07Z level=info msg=b7",
+        ]),
+    ];
+    app.run(inputs, &mut output).unwrap();
+
+    // the separator and the before-context go right before the group of their input
+    let output = std::str::from_utf8(&output).unwrap();
+    let messages = output
+        .lines()
+        .map(|line| line.split_whitespace().last().unwrap_or_default())
+        .collect_vec();
+    assert_eq!(messages, ["a1", "a2", "a3", "b6", "b7", "--", "a5", "a8"], "{output}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_follow_output_format_json() {
+    use crate::command::Command;
+
+    let command = |line: &str| InputHolder::new(InputReference::Process(Command::parse(line).unwrap()), None);
+
+    let app = App::new(Options {
+        follow: true,
+        input_info: InputInfo::Full.into(),
+        ..options().with_output_format(OutputFormat::Json)
+    });
+
+    let mut output = Vec::new();
+    let inputs = vec![
+        command("echo ts=2024-01-02T03:04:05Z msg=first"),
+        command("echo ts=2024-01-02T03:04:06Z msg=second"),
+    ];
+    app.run(inputs, &mut output).unwrap();
+
+    // neither input badges nor sync indicators break JSON lines
+    let output = std::str::from_utf8(&output).unwrap();
+    let messages = output
+        .lines()
+        .map(|line| {
+            json::from_str::<json::Value>(line).unwrap()["message"]
+                .as_str()
+                .unwrap()
+                .to_owned()
+        })
+        .collect_vec();
+    assert_eq!(messages, ["first", "second"], "{output}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_follow_alert() {
+    use crate::{command::Command, testing::TempDir};
+
+    let dir = TempDir::new("alert");
+    let path = dir.path().join("alert.json");
+    let app = App::new(Options {
+        follow: true,
+        alert: Some(AlertOptions {
+            query: Query::parse("level=error").unwrap(),
+            command: Some(Command::new(vec!["sh".into(), "-c".into(), format!("cat > '{}'", path.display())]).unwrap()),
+            bell: false,
+            debounce: Duration::from_secs(3600),
+            interval: Duration::ZERO,
+        }),
+        ..options()
+    });
+
+    let mut output = Vec::new();
+    let inputs = vec![InputHolder::new(
+        InputReference::Process(
+            Command::parse("printf 'level=info msg=a\\nlevel=error msg=b code=1\\nlevel=error msg=c\\n'").unwrap(),
+        ),
+        None,
+    )];
+    app.run(inputs, &mut output).unwrap();
+
+    // the burst is not finished within the debounce period, so it is alerted when the input ends
+    let alert: json::Value = json::from_slice(&fs::read(&path).unwrap()).unwrap();
+    assert_eq!(alert["count"], 2);
+    assert_eq!(alert["message"], "b");
+    assert_eq!(alert["fields"]["code"], 1);
+}
@@ -0,0 +1,420 @@
+// std imports
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    fs::{File, Metadata},
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+// third-party imports
+use deko::bufread::AnyDecoder;
+use flate2::bufread::DeflateDecoder;
+use zip::{CompressionMethod, ZipArchive};
+
+// local imports
+use crate::{
+    input::{Meta, RandomAccessStream},
+    iox::ReadFill,
+};
+
+// ---
+
+/// Format of an archive containing log files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Tar archive, possibly compressed as a whole.
+    Tar,
+    /// Zip archive.
+    Zip,
+}
+
+impl Format {
+    /// Detects the format of the archive by its content, returns None if the file is not an archive.
+    pub fn detect(path: &Path) -> io::Result<Option<Self>> {
+        let mut file = File::open(path)?;
+        let mut magic = [0; 4];
+        if file.read_fill(&mut magic)? == magic.len() && (magic == *b"PK\x03\x04" || magic == *b"PK\x05\x06") {
+            return Ok(Some(Self::Zip));
+        }
+
+        // tar header has a magic string at offset 257, it is checked after decompression
+        file.rewind()?;
+        let mut header = [0; 512];
+        let n = AnyDecoder::new(BufReader::new(file))
+            .read_fill(&mut header)
+            .unwrap_or(0);
+        Ok((n == header.len() && header[257..262] == *b"ustar").then_some(Self::Tar))
+    }
+
+    /// Returns regular file members of the archive in order of their appearance.
+    pub fn members(self, path: &Path) -> io::Result<Vec<Member>> {
+        match self {
+            Self::Tar => tar_members(path),
+            Self::Zip => zip_members(path),
+        }
+    }
+}
+
+// ---
+
+/// Member of an archive, a regular file stored in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    data: Data,
+}
+
+impl Member {
+    /// Opens the member in the archive file.
+    ///
+    /// The returned stream supports seeking only if the member is stored in the archive file as is.
+    pub fn open(&self, archive: &Path) -> io::Result<RandomAccessStream> {
+        let mut file = File::open(archive)?;
+        Ok(match self.data {
+            Data::Stored { offset, size } => {
+                file.seek(SeekFrom::Start(offset))?;
+                Box::new(Slice {
+                    inner: file,
+                    offset,
+                    size,
+                    position: 0,
+                })
+            }
+            Data::Deflated { offset, size } => {
+                file.seek(SeekFrom::Start(offset))?;
+                Box::new(Sequential(DeflateDecoder::new(BufReader::new(file).take(size))))
+            }
+            Data::Decoded {
+                ref archive,
+                offset,
+                size,
+            } => Box::new(Sequential(archive.slice(offset, size))),
+            Data::Unsupported => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "member is encrypted or compressed using unsupported method",
+                ));
+            }
+        })
+    }
+}
+
+// ---
+
+/// Location of member data in the archive file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Data {
+    /// Data is stored as is at the offset in the archive file.
+    Stored { offset: u64, size: u64 },
+    /// Data is compressed using deflate at the offset in the archive file.
+    Deflated { offset: u64, size: u64 },
+    /// Data is stored as is at the offset in the decompressed archive file, so it can only be read sequentially.
+    Decoded {
+        archive: DecodedArchive,
+        offset: u64,
+        size: u64,
+    },
+    /// Data cannot be read.
+    Unsupported,
+}
+
+// ---
+
+fn tar_members(path: &Path) -> io::Result<Vec<Member>> {
+    let mut file = File::open(path)?;
+    let kind = AnyDecoder::new(BufReader::new(&mut file)).kind()?;
+    file.rewind()?;
+
+    if kind == deko::Format::Verbatim {
+        tar_entries(tar::Archive::new(file).entries_with_seek()?, |offset, size| {
+            Data::Stored { offset, size }
+        })
+    } else {
+        let archive = DecodedArchive::new(path);
+        let decoder = AnyDecoder::new(BufReader::new(file));
+        tar_entries(tar::Archive::new(decoder).entries()?, |offset, size| Data::Decoded {
+            archive: archive.clone(),
+            offset,
+            size,
+        })
+    }
+}
+
+fn tar_entries<R: Read>(entries: tar::Entries<R>, data: impl Fn(u64, u64) -> Data) -> io::Result<Vec<Member>> {
+    let mut members = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        members.push(Member {
+            name: entry.path()?.to_string_lossy().into_owned(),
+            data: data(entry.raw_file_position(), entry.size()),
+        });
+    }
+    Ok(members)
+}
+
+fn zip_members(path: &Path) -> io::Result<Vec<Member>> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+    let mut members = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        if !file.is_file() {
+            continue;
+        }
+        let (offset, size) = (file.data_start(), file.compressed_size());
+        let data = match file.compression() {
+            _ if file.encrypted() => Data::Unsupported,
+            CompressionMethod::Stored => Data::Stored { offset, size },
+            method if method == CompressionMethod::DEFLATE => Data::Deflated { offset, size },
+            _ => Data::Unsupported,
+        };
+        members.push(Member {
+            name: file.name().to_owned(),
+            data,
+        });
+    }
+    Ok(members)
+}
+
+// ---
+
+/// Slice is a part of the archive file containing member data.
+struct Slice {
+    inner: File,
+    offset: u64,
+    size: u64,
+    position: u64,
+}
+
+impl Read for Slice {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.size.saturating_sub(self.position) as usize);
+        let n = self.inner.read(&mut buf[..n])?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for Slice {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.size.checked_add_signed(offset),
+        };
+        let position = position.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "position out of range"))?;
+        self.inner.seek(SeekFrom::Start(self.offset + position))?;
+        self.position = position;
+        Ok(position)
+    }
+}
+
+impl Meta for Slice {
+    #[inline]
+    fn metadata(&self) -> io::Result<Option<Metadata>> {
+        Ok(None)
+    }
+}
+
+// ---
+
+/// DecodedArchive is a compressed archive file decoded once for all its members.
+///
+/// Members are expected to be read mostly in order of their appearance, so each of them is read directly from
+/// the shared decoder when it reaches the member. Data of other open members that the decoder passes by is kept
+/// in their buffers until they are read, so the archive is decoded in a single pass regardless of the reading order.
+/// A buffer keeps up to 16 MiB, the rest of the member data is decoded again from the start of the archive
+/// once the buffer is read.
+#[derive(Clone)]
+struct DecodedArchive(Arc<Mutex<DecodedArchiveState>>);
+
+impl DecodedArchive {
+    fn new(path: &Path) -> Self {
+        Self(Arc::new(Mutex::new(DecodedArchiveState {
+            path: path.to_owned(),
+            decoder: None,
+            position: 0,
+            slices: HashMap::new(),
+            next_id: 0,
+            spill_limit: DecodedArchiveState::SPILL_LIMIT,
+        })))
+    }
+
+    fn slice(&self, offset: u64, size: u64) -> DecodedSlice {
+        let mut state = self.0.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.slices.insert(
+            id,
+            SliceState {
+                position: offset,
+                end: offset + size,
+                buffer: VecDeque::new(),
+            },
+        );
+        DecodedSlice {
+            archive: self.clone(),
+            id,
+        }
+    }
+}
+
+impl PartialEq for DecodedArchive {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for DecodedArchive {}
+
+impl fmt::Debug for DecodedArchive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DecodedArchive").finish_non_exhaustive()
+    }
+}
+
+struct DecodedArchiveState {
+    path: PathBuf,
+    decoder: Option<AnyDecoder<BufReader<File>>>,
+    position: u64,
+    slices: HashMap<usize, SliceState>,
+    next_id: usize,
+    spill_limit: usize,
+}
+
+impl DecodedArchiveState {
+    const CHUNK_SIZE: usize = 64 * 1024;
+    const SPILL_LIMIT: usize = 16 * 1024 * 1024;
+
+    fn read(&mut self, id: usize, buf: &mut [u8]) -> io::Result<usize> {
+        let slice = self.slices.get_mut(&id).unwrap();
+        if !slice.buffer.is_empty() {
+            let n = slice.buffer.read(buf)?;
+            slice.position += n as u64;
+            return Ok(n);
+        }
+        if slice.position == slice.end || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let target = slice.position;
+        let limit = buf.len().min((slice.end - slice.position) as usize);
+        if target < self.position {
+            // the data has been passed by before the slice was opened, so decoding is started over
+            self.decoder = None;
+            self.position = 0;
+        }
+        if self.position < target {
+            let mut chunk = vec![0; Self::CHUNK_SIZE];
+            while self.position < target {
+                let n = Self::CHUNK_SIZE.min((target - self.position) as usize);
+                let n = self.decode(&mut chunk[..n])?;
+                self.spill(&chunk[..n]);
+            }
+        }
+
+        let n = self.decode(&mut buf[..limit])?;
+        self.slices.get_mut(&id).unwrap().position += n as u64;
+        self.spill(&buf[..n]);
+        Ok(n)
+    }
+
+    fn decode(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.decoder.is_none() {
+            self.decoder = Some(AnyDecoder::new(BufReader::new(File::open(&self.path)?)));
+        }
+        let result = match self.decoder.as_mut().unwrap().read(buf) {
+            Ok(0) => Err(io::ErrorKind::UnexpectedEof.into()),
+            result => result,
+        };
+        match result {
+            Ok(n) => {
+                self.position += n as u64;
+                Ok(n)
+            }
+            Err(err) => {
+                self.decoder = None;
+                self.position = 0;
+                Err(err)
+            }
+        }
+    }
+
+    // keeps the data just decoded for the slices that need it and have not read it yet,
+    // once a buffer is full, the following data is not kept, so it is decoded again when the slice reaches it
+    fn spill(&mut self, data: &[u8]) {
+        let end = self.position;
+        let start = end - data.len() as u64;
+        for slice in self.slices.values_mut() {
+            let filled = slice.position + slice.buffer.len() as u64;
+            if (start..end).contains(&filled) && filled < slice.end {
+                let from = (filled - start) as usize;
+                let to = (slice.end.min(end) - start) as usize;
+                let to = to.min(from + self.spill_limit.saturating_sub(slice.buffer.len()));
+                slice.buffer.extend(&data[from..to]);
+            }
+        }
+    }
+}
+
+struct SliceState {
+    position: u64,
+    end: u64,
+    buffer: VecDeque<u8>,
+}
+
+// ---
+
+/// DecodedSlice is a stream of member data in a DecodedArchive.
+struct DecodedSlice {
+    archive: DecodedArchive,
+    id: usize,
+}
+
+impl Read for DecodedSlice {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.archive.0.lock().unwrap().read(self.id, buf)
+    }
+}
+
+impl Drop for DecodedSlice {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.archive.0.lock() {
+            state.slices.remove(&self.id);
+        }
+    }
+}
+
+// ---
+
+/// Sequential is a stream of member data that does not support seeking.
+struct Sequential<R>(R);
+
+impl<R: Read> Read for Sequential<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<R> Seek for Sequential<R> {
+    #[inline]
+    fn seek(&mut self, _: SeekFrom) -> io::Result<u64> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+impl<R> Meta for Sequential<R> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Option<Metadata>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,141 @@
+use std::{
+    fs,
+    io::{Cursor, Write},
+    path::Path,
+};
+
+use super::*;
+use crate::testing::TempDir;
+
+const FILES: &[(&str, &str)] = &[
+    (
+        "app/service.log",
+        "ts=2024-01-02T03:04:05Z msg=a\nts=2024-01-02T03:04:06Z msg=b\n",
+    ),
+    ("app/worker.log", "ts=2024-01-02T03:04:07Z msg=c\n"),
+    ("system.log", ""),
+];
+
+fn tar() -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Directory);
+    header.set_size(0);
+    builder.append_data(&mut header, "app/", io::empty()).unwrap();
+    for (name, data) in FILES {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        builder.append_data(&mut header, name, data.as_bytes()).unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+fn zip() -> Vec<u8> {
+    use zip::write::SimpleFileOptions;
+
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    writer.add_directory("app/", SimpleFileOptions::default()).unwrap();
+    for (i, (name, data)) in FILES.iter().enumerate() {
+        let method = if i % 2 == 0 {
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::DEFLATE
+        };
+        writer
+            .start_file(*name, SimpleFileOptions::default().compression_method(method))
+            .unwrap();
+        writer.write_all(data.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn check(path: &Path, format: Format, seekable: &[bool]) {
+    assert_eq!(Format::detect(path).unwrap(), Some(format));
+
+    let members = format.members(path).unwrap();
+    let names = members.iter().map(|m| m.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, FILES.iter().map(|(name, _)| *name).collect::<Vec<_>>());
+
+    for ((member, (_, data)), &seekable) in members.iter().zip(FILES).zip(seekable) {
+        let mut stream = member.open(path).unwrap();
+        assert_eq!(stream.stream_position().is_ok(), seekable, "{}", member.name);
+        let mut buf = String::new();
+        stream.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, *data, "{}", member.name);
+    }
+}
+
+#[test]
+fn test_tar() {
+    let dir = TempDir::new("archive-tar");
+    let path = dir.path().join("bundle.tar");
+    fs::write(&path, tar()).unwrap();
+    check(&path, Format::Tar, &[true, true, true]);
+
+    let member = &Format::Tar.members(&path).unwrap()[0];
+    let mut stream = member.open(&path).unwrap();
+    assert_eq!(stream.seek(SeekFrom::End(-2)).unwrap(), FILES[0].1.len() as u64 - 2);
+    let mut buf = String::new();
+    stream.read_to_string(&mut buf).unwrap();
+    assert_eq!(buf, "b\n");
+}
+
+#[test]
+fn test_tar_gz() {
+    let dir = TempDir::new("archive-tar-gz");
+    let path = dir.path().join("bundle.tar.gz");
+    fs::write(&path, gzip(&tar())).unwrap();
+    check(&path, Format::Tar, &[false, false, false]);
+
+    // members opened at once are decoded in a single pass regardless of the order they are read in
+    let members = Format::Tar.members(&path).unwrap();
+    let mut streams = members.iter().map(|m| m.open(&path).unwrap()).collect::<Vec<_>>();
+    for (stream, (_, data)) in streams.iter_mut().zip(FILES).rev() {
+        let mut buf = String::new();
+        stream.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, *data);
+    }
+
+    // data beyond the limit of a buffer is decoded again
+    let members = Format::Tar.members(&path).unwrap();
+    let Data::Decoded { archive, .. } = &members[0].data else {
+        panic!("unexpected data {:?}", members[0].data);
+    };
+    archive.0.lock().unwrap().spill_limit = 8;
+    let mut streams = members.iter().map(|m| m.open(&path).unwrap()).collect::<Vec<_>>();
+    for (stream, (_, data)) in streams.iter_mut().zip(FILES).rev() {
+        let mut buf = String::new();
+        stream.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, *data);
+        let state = archive.0.lock().unwrap();
+        assert!(state.slices.values().all(|slice| slice.buffer.len() <= 8));
+    }
+}
+
+#[test]
+fn test_zip() {
+    let dir = TempDir::new("archive-zip");
+    let path = dir.path().join("logs.zip");
+    fs::write(&path, zip()).unwrap();
+    check(&path, Format::Zip, &[true, false, true]);
+}
+
+#[test]
+fn test_not_archive() {
+    let dir = TempDir::new("archive-none");
+    for (name, data) in [
+        ("test.log", FILES[0].1.as_bytes().to_vec()),
+        ("test.log.gz", gzip(FILES[0].1.as_bytes())),
+        ("empty.log", Vec::new()),
+    ] {
+        let path = dir.path().join(name);
+        fs::write(&path, data).unwrap();
+        assert_eq!(Format::detect(&path).unwrap(), None, "{name}");
+    }
+}
@@ -1,5 +1,5 @@
 // std imports
-use std::{num::NonZeroUsize, path::PathBuf};
+use std::{num::NonZeroUsize, path::PathBuf, time::Duration};
 
 // third-party imports
 use clap::{
@@ -114,6 +114,34 @@ pub struct Opt {
     #[arg(long, short = 's', overrides_with = "sort")]
     pub sort: bool,
 
+    /// Sort entries in reverse chronological order, newest first, implies <c>--sort</>
+    #[arg(long, overrides_with = "reverse")]
+    pub reverse: bool,
+
+    /// Stop after <c><<N>></> matching entries
+    #[arg(
+        long,
+        short = 'm',
+        overrides_with = "max_count",
+        conflicts_with = "follow",
+        value_name = "N"
+    )]
+    pub max_count: Option<usize>,
+
+    /// Display only the last <c><<N>></> matching entries, implies <c>--sort</>
+    #[arg(long, overrides_with = "last", conflicts_with_all = ["follow", "max_count"], value_name = "N")]
+    pub last: Option<usize>,
+
+    /// Sort entries by value of the field, prefix the key with <c>-</> for descending order
+    #[arg(
+        long,
+        overrides_with = "sort_by",
+        allow_hyphen_values = true,
+        conflicts_with_all = ["follow", "reverse", "last", "after_context", "before_context", "context"],
+        value_name = "KEY"
+    )]
+    pub sort_by: Option<String>,
+
     /// Follow input streams and sort entries chronologically within time frame set by <c>--sync-interval-ms</> option
     #[arg(long, short = 'F', overrides_with = "follow")]
     pub follow: bool,
@@ -131,6 +159,59 @@ pub struct Opt {
     )]
     pub sync_interval_ms: u64,
 
+    /// Command to run in <c>--follow</> mode using its stdout and stderr as an input, can be repeated
+    #[arg(long = "command", value_name = "COMMAND", requires = "follow")]
+    pub commands: Vec<String>,
+
+    /// Socket to receive entries on in <c>--follow</> mode, <c>udp://HOST:PORT</>, <c>tcp://HOST:PORT</> or <c>unix://PATH</>, can be repeated
+    #[arg(long, value_name = "URL", requires = "follow")]
+    pub listen: Vec<String>,
+
+    /// Restart commands when they exit, otherwise a failed command makes hl exit with its exit code
+    #[arg(long, overrides_with = "restart", requires = "follow")]
+    pub restart: bool,
+
+    /// Continue from the positions in files where the previous run with the same <c><<NAME>></> stopped, instead of <c>--tail</>
+    #[arg(
+        long,
+        overrides_with = "resume",
+        conflicts_with_all = ["sort", "reverse", "last", "sort_by", "max_count", "stats", "histogram"],
+        value_name = "NAME"
+    )]
+    pub resume: Option<String>,
+
+    /// Alert when entries matching the query appear in <c>--follow</> mode, by ringing the bell unless <c>--exec</> is specified
+    #[arg(long, overrides_with = "on_match", requires = "follow", value_name = "QUERY")]
+    pub on_match: Option<String>,
+
+    /// Command to run on alerts, with the entry fields passed in <c>HL_*</> environment variables and as JSON on stdin
+    #[arg(long, overrides_with = "exec", requires = "on_match", value_name = "COMMAND")]
+    pub exec: Option<String>,
+
+    /// Ring the terminal bell on alerts along with running the <c>--exec</> command
+    #[arg(long, overrides_with = "bell", requires = "on_match")]
+    pub bell: bool,
+
+    /// Period without more matching entries after which an alert is issued for the burst of entries
+    #[arg(
+        long,
+        default_value = "1s",
+        overrides_with = "alert_debounce",
+        value_name = "DURATION",
+        value_parser = humantime::parse_duration
+    )]
+    pub alert_debounce: Duration,
+
+    /// Minimum interval between alerts
+    #[arg(
+        long,
+        default_value = "10s",
+        overrides_with = "alert_interval",
+        value_name = "DURATION",
+        value_parser = humantime::parse_duration
+    )]
+    pub alert_interval: Duration,
+
     /// Control pager usage (HL_PAGER or PAGER)
     #[arg(
         long,
@@ -217,6 +298,44 @@ pub struct Opt {
     #[arg(short, long, num_args = 1, help_heading = heading::FILTERING)]
     pub query: Vec<String>,
 
+    /// Display <c><<N>></> entries after each matching entry
+    #[arg(
+        short = 'A',
+        long,
+        overrides_with = "after_context",
+        value_name = "N",
+        help_heading = heading::FILTERING
+    )]
+    pub after_context: Option<usize>,
+
+    /// Display <c><<N>></> entries before each matching entry
+    #[arg(
+        short = 'B',
+        long,
+        overrides_with = "before_context",
+        value_name = "N",
+        help_heading = heading::FILTERING
+    )]
+    pub before_context: Option<usize>,
+
+    /// Display <c><<N>></> entries before and after each matching entry
+    ///
+    /// Context entries are displayed with faded styles.
+    /// Groups of entries that are not adjacent are separated by a line with <c>--</>.
+    /// Options <c>--after-context</> and <c>--before-context</> take precedence.
+    /// There is no short form, as <c>-C</> keeps its meaning of <c>--concurrency</>.
+    #[arg(long, overrides_with = "context", value_name = "N", help_heading = heading::FILTERING)]
+    pub context: Option<usize>,
+
+    /// Display entries sharing a value of <c><<KEY>></> field with any matching entry
+    ///
+    /// In <c>--sort</> mode, all such entries are displayed.
+    /// Otherwise, including <c>--follow</> mode, the input is read once, so only entries following
+    /// the first matching entry with the same value are displayed,
+    /// and only the values of the 65536 most recent matching entries are remembered.
+    #[arg(long, overrides_with = "correlate_by", value_name = "KEY", help_heading = heading::FILTERING)]
+    pub correlate_by: Option<String>,
+
     /// Whether to use ANSI colors and styles
     #[arg(
         long,
@@ -265,6 +384,62 @@ pub struct Opt {
     #[arg(long, overrides_with = "raw_fields", help_heading = heading::OUTPUT)]
     pub raw_fields: bool,
 
+    /// Output format
+    ///
+    /// Formats <c>json</> and <c>logfmt</> produce entries with normalized predefined fields
+    /// <c>time</>, <c>level</>, <c>message</>, <c>logger</> and <c>caller</>, honoring <c>--hide</> and <c>--flatten</> options.
+    /// Logfmt output is always flattened.
+    #[arg(
+        long,
+        env = "HL_OUTPUT_FORMAT",
+        default_value = "text",
+        overrides_with = "output_format",
+        value_name = "FORMAT",
+        help_heading = heading::OUTPUT
+    )]
+    pub output_format: OutputFormat,
+
+    /// Custom line template for text output, e.g. <c>'{time} {level} {logger}: {message} {fields}'</>
+    ///
+    /// Placeholders are <c>{time}</>, <c>{level}</>, <c>{logger}</>, <c>{message}</>, <c>{caller}</>,
+    /// <c>{fields}</> for the remaining fields, and any field key like <c>{http.status}</>.
+    /// A placeholder may have a specification <c>{key:[align][width][@style]}</>,
+    /// where align is one of <c>'<<'</>, <c>'>>'</> or <c>'^'</>, and style is a theme element name, e.g. <c>'{level:^5@message}'</>.
+    #[arg(
+        long,
+        env = "HL_FORMAT",
+        overrides_with = "format",
+        value_name = "TEMPLATE",
+        help_heading = heading::OUTPUT
+    )]
+    pub format: Option<String>,
+
+    /// Display a summary of matching entries instead of the entries
+    ///
+    /// The summary includes entry counts, time span, counts per level, top loggers and top values of <c>--stats-field</> fields.
+    #[arg(long, overrides_with = "stats", help_heading = heading::OUTPUT)]
+    pub stats: bool,
+
+    /// Display top values of the field with the specified key in the summary, implies <c>--stats</>
+    #[arg(long, num_args = 1, value_name = "KEY", help_heading = heading::OUTPUT)]
+    pub stats_field: Vec<String>,
+
+    /// Number of top loggers and top values of each field to display in the summary
+    #[arg(long, default_value_t = 10, overrides_with = "stats_top", value_name = "N", help_heading = heading::OUTPUT)]
+    pub stats_top: usize,
+
+    /// Display a histogram of matching entries per time bucket stacked by level instead of the entries
+    ///
+    /// Bucket size is a duration like <c>30s</>, <c>5m</> or <c>1h</>, it is chosen automatically if not specified.
+    #[arg(
+        long,
+        num_args = 0..=1,
+        value_name = "BUCKET",
+        value_parser = parse_non_zero_duration,
+        help_heading = heading::OUTPUT
+    )]
+    pub histogram: Option<Option<Duration>>,
+
     /// Hide or reveal fields with the specified keys, prefix with <c>!</> to reveal, provide <c>'!*'</> to reveal all
     #[arg(
         long,
@@ -413,6 +588,22 @@ pub struct Opt {
     )]
     pub input_format: InputFormat,
 
+    /// Unwrap lines written by a container runtime
+    ///
+    /// Possible values:
+    /// • <c>none</>: Do not unwrap lines (default)
+    /// • <c>cri</>: Kubernetes CRI format used by containerd and CRI-O
+    /// • <c>docker</>: Docker json-file logging driver format
+    #[arg(
+        long,
+        env = "HL_UNWRAP",
+        default_value = "none",
+        overrides_with = "unwrap",
+        value_name = "WRAPPER",
+        help_heading = heading::INPUT
+    )]
+    pub unwrap: Unwrap,
+
     /// Unix timestamp unit
     #[arg(
         long,
@@ -428,6 +619,20 @@ pub struct Opt {
     #[arg(long, env = "HL_ALLOW_PREFIX", overrides_with = "allow_prefix", help_heading = heading::INPUT)]
     pub allow_prefix: bool,
 
+    /// Expand JSON objects and arrays embedded in string values of fields with the specified keys
+    ///
+    /// Values of all fields are checked if no keys are specified, e.g. <c>--expand-embedded-json</> or <c>--expand-embedded-json=payload,body</>.
+    /// Expanded values are displayed as nested objects, and their sub-fields can be used in filters like <c>payload.user=alice</>.
+    #[arg(
+        long,
+        num_args = 0..=1,
+        value_name = "KEYS",
+        value_delimiter = ',',
+        require_equals = true,
+        help_heading = heading::INPUT
+    )]
+    pub expand_embedded_json: Option<Vec<String>>,
+
     /// Log entry delimiter
     ///
     /// By default, the delimiter is considered to be LF or CRLF followed by an empty line or a line that
@@ -518,6 +723,10 @@ pub struct Opt {
     #[arg(long, requires = "sort", help_heading = heading::ADVANCED)]
     pub dump_index: bool,
 
+    /// Manage cached indexes and exit, <c>build</> indexes the files and files in the directories
+    #[arg(long, value_name = "ACTION", help_heading = heading::ADVANCED)]
+    pub index_cache: Option<IndexCacheAction>,
+
     /// Print help
     #[arg(
         long,
@@ -528,9 +737,13 @@ pub struct Opt {
     )]
     pub help: Option<HelpVerbosity>,
 
-    /// Files to process
+    /// Files to process, use <ARCHIVE>:<PATTERN> to select files in a tar or zip archive
     #[arg(name = "FILE")]
     pub files: Vec<PathBuf>,
+
+    /// Command to run in <c>--follow</> mode, its stdout and stderr are used as an input
+    #[arg(name = "COMMAND", last = true, requires = "follow")]
+    pub command: Vec<String>,
 }
 
 pub type ColorOption = clap::ColorChoice;
@@ -547,6 +760,22 @@ pub enum InputFormat {
     Auto,
     Json,
     Logfmt,
+    Syslog,
+    AccessLog,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unwrap {
+    None,
+    Cri,
+    Docker,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Logfmt,
 }
 
 #[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
@@ -589,6 +818,18 @@ impl From<FlattenOption> for settings::FlattenOption {
     }
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexCacheAction {
+    /// List cached indexes with their sizes and paths of their source files
+    List,
+    /// Remove cached indexes of files that no longer exist
+    Prune,
+    /// Build indexes of the files
+    Build,
+    /// Remove all cached indexes
+    Clear,
+}
+
 #[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HelpVerbosity {
     Short,
@@ -702,5 +943,12 @@ fn parse_non_zero_size(s: &str) -> std::result::Result<NonZeroUsize, NonZeroSize
     }
 }
 
+fn parse_non_zero_duration(s: &str) -> std::result::Result<Duration, DurationParseError> {
+    match humantime::parse_duration(s)? {
+        Duration::ZERO => Err(DurationParseError::ZeroDuration),
+        value => Ok(value),
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,127 @@
+// std imports
+use std::{
+    borrow::Cow,
+    fs::Metadata,
+    io::{self, Read, Write},
+    process::{self, Child, ExitStatus, Stdio},
+};
+
+// third-party imports
+use os_pipe::PipeReader;
+
+// local imports
+use crate::{input::Meta, xerr::HighlightQuoted};
+
+// ---
+
+/// A command run to read its output as an input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    args: Vec<String>,
+}
+
+impl Command {
+    /// Returns a new Command running the program given by the first argument, or None if there are no arguments.
+    pub fn new(args: Vec<String>) -> Option<Self> {
+        (!args.is_empty()).then_some(Self { args })
+    }
+
+    /// Parses a command line, the arguments are split and unquoted the way a shell does it.
+    pub fn parse(line: &str) -> io::Result<Self> {
+        let args = shellwords::split(line).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid command {}: mismatched quotes", line.hlq()),
+            )
+        })?;
+        Self::new(args).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "command is empty"))
+    }
+
+    /// Returns the command line used to name the input.
+    ///
+    /// Only the arguments that would otherwise be split or unquoted by a shell are quoted to keep the name short.
+    pub fn name(&self) -> String {
+        self.args.iter().map(|arg| quote(arg)).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Starts the command with both its stdout and stderr redirected to the returned Output.
+    pub fn spawn(&self) -> io::Result<(Child, Output)> {
+        let failed =
+            |e: io::Error| io::Error::new(e.kind(), format!("failed to run command {}: {}", self.name().hlq(), e));
+
+        let (reader, writer) = os_pipe::pipe()?;
+        let mut command = process::Command::new(&self.args[0]);
+        command
+            .args(&self.args[1..])
+            .stdin(Stdio::null())
+            .stdout(writer.try_clone()?)
+            .stderr(writer);
+        let child = command.spawn().map_err(failed)?;
+
+        // write ends of the pipe held by the command must be closed, otherwise the output never ends
+        drop(command);
+
+        Ok((child, Output(reader)))
+    }
+
+    /// Runs the command with the variables added to its environment and the input written to its stdin,
+    /// and waits for it to exit.
+    ///
+    /// Output of the command is discarded except for its stderr, so it does not get mixed with the entries.
+    pub fn run(&self, vars: &[(String, String)], input: &[u8]) -> io::Result<ExitStatus> {
+        let failed =
+            |e: io::Error| io::Error::new(e.kind(), format!("failed to run command {}: {}", self.name().hlq(), e));
+
+        let mut child = process::Command::new(&self.args[0])
+            .args(&self.args[1..])
+            .envs(vars.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(failed)?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // the command is not obliged to read its input
+            match stdin.write_all(input) {
+                Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+                    child.wait().ok();
+                    return Err(failed(e));
+                }
+                _ => {}
+            }
+        }
+
+        child.wait().map_err(failed)
+    }
+}
+
+// ---
+
+/// Output of a running command, it ends when the command and all its child processes exit.
+pub struct Output(PipeReader);
+
+impl Read for Output {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Meta for Output {
+    #[inline]
+    fn metadata(&self) -> io::Result<Option<Metadata>> {
+        Ok(None)
+    }
+}
+
+// ---
+
+fn quote(arg: &str) -> Cow<'_, str> {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || matches!(c, '\'' | '"' | '\\')) {
+        return arg.into();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''")).into()
+}
+
+#[cfg(test)]
+mod tests;
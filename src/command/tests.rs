@@ -0,0 +1,65 @@
+use super::*;
+
+#[test]
+fn test_parse() {
+    let command = Command::parse(r#"kubectl logs -f -l 'app=my app' "--since=1h""#).unwrap();
+    assert_eq!(
+        command.args,
+        ["kubectl", "logs", "-f", "-l", "app=my app", "--since=1h"]
+    );
+    assert_eq!(command.name(), "kubectl logs -f -l 'app=my app' --since=1h");
+    assert_eq!(
+        Command::parse(r#"echo "it's" ''"#).unwrap().name(),
+        r#"echo 'it'\''s' ''"#
+    );
+
+    assert_eq!(Command::parse("").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(Command::parse("  ").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(
+        Command::parse("echo 'a").unwrap_err().kind(),
+        io::ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn test_new() {
+    assert_eq!(Command::new(Vec::new()), None);
+    assert_eq!(
+        Command::new(vec!["echo".into(), "a b".into()]).unwrap().name(),
+        "echo 'a b'"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_spawn() {
+    let command = Command::parse("sh -c 'echo out; echo err >&2; exit 3'").unwrap();
+    let (mut child, mut output) = command.spawn().unwrap();
+    assert!(output.metadata().unwrap().is_none());
+
+    let mut buf = String::new();
+    output.read_to_string(&mut buf).unwrap();
+    assert_eq!(buf, "out\nerr\n");
+    assert_eq!(child.wait().unwrap().code(), Some(3));
+}
+
+#[test]
+fn test_spawn_not_found() {
+    let command = Command::parse("hl-test-command-that-does-not-exist").unwrap();
+    let err = command.spawn().err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    assert!(err.to_string().contains("hl-test-command-that-does-not-exist"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run() {
+    let command = Command::parse(r#"sh -c 'read x; test "$x" = "$A"'"#).unwrap();
+    let vars = [("A".to_string(), "a b".to_string())];
+    assert!(command.run(&vars, b"a b\n").unwrap().success());
+    assert!(!command.run(&vars, b"c\n").unwrap().success());
+
+    // input that is not read does not make the command fail
+    let command = Command::parse("true").unwrap();
+    assert!(command.run(&[], &[b'x'; 1 << 20]).unwrap().success());
+}
@@ -0,0 +1,203 @@
+// std imports
+use std::{
+    borrow::Cow,
+    io::{self, BufRead, Read},
+};
+
+// third-party imports
+use serde::{Deserialize, Serialize};
+
+// ---
+
+/// Wrapper is a format of lines written by a container runtime around the lines written by a container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Wrapper {
+    /// `<time> <stream> <tag> <payload>` lines written by CRI runtimes like containerd and CRI-O.
+    Cri,
+    /// `{"log":"<payload>","stream":"<stream>","time":"<time>"}` lines written by Docker json-file logging driver.
+    Docker,
+}
+
+// ---
+
+/// Envelope is a line in CRI format split into parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Envelope<'a> {
+    pub time: &'a str,
+    pub stream: &'a str,
+    /// Partial lines are continued by the following lines of the same stream.
+    pub partial: bool,
+    pub payload: &'a [u8],
+    /// Offset of the payload in the line.
+    pub offset: usize,
+}
+
+impl<'a> Envelope<'a> {
+    /// Parses a line in CRI format, returning None if the line is not in this format.
+    pub fn parse(line: &'a [u8]) -> Option<Self> {
+        let mut parts = line.splitn(4, |&b| b == b' ');
+        let time = parts.next()?;
+        let stream = parts.next()?;
+        let tag = parts.next()?;
+        let payload = parts.next().unwrap_or_default();
+
+        if time.len() < 20 || time[4] != b'-' || time[10] != b'T' {
+            return None;
+        }
+        if stream != b"stdout" && stream != b"stderr" {
+            return None;
+        }
+        let partial = match tag.split(|&b| b == b':').next()? {
+            b"F" => false,
+            b"P" => true,
+            _ => return None,
+        };
+
+        Some(Self {
+            time: std::str::from_utf8(time).ok()?,
+            stream: std::str::from_utf8(stream).ok()?,
+            partial,
+            payload,
+            offset: line.len() - payload.len(),
+        })
+    }
+}
+
+// ---
+
+/// Unwrapper converts lines wrapped by a container runtime to lines in CRI format, joining partial lines.
+///
+/// Each joined line has the time of its first part. Lines not matching the wrapper format are passed as is.
+pub struct Unwrapper<R> {
+    inner: R,
+    wrapper: Wrapper,
+    follow: bool,
+    line: Vec<u8>,
+    pending: Vec<(String, Vec<u8>)>,
+    out: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: BufRead> Unwrapper<R> {
+    pub fn new(inner: R, wrapper: Wrapper) -> Self {
+        Self {
+            inner,
+            wrapper,
+            follow: false,
+            line: Vec::new(),
+            pending: Vec::new(),
+            out: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Makes the unwrapper keep partial and unterminated lines at the end of the stream
+    /// until the rest of them is appended, instead of flushing them as complete lines.
+    pub fn with_follow(self, follow: bool) -> Self {
+        Self { follow, ..self }
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        self.out.clear();
+        self.pos = 0;
+
+        while self.out.is_empty() {
+            self.inner.read_until(b'\n', &mut self.line)?;
+            let mut line = std::mem::take(&mut self.line);
+            match line.strip_suffix(b"\n") {
+                Some(data) => self.process(data),
+                None if self.follow => {
+                    self.line = line;
+                    return Ok(());
+                }
+                None => {
+                    if !line.is_empty() {
+                        self.process(&line);
+                    }
+                    for (_, mut data) in self.pending.drain(..) {
+                        data.push(b'\n');
+                        self.out.append(&mut data);
+                    }
+                    line.clear();
+                    self.line = line;
+                    return Ok(());
+                }
+            }
+            line.clear();
+            self.line = line;
+        }
+
+        Ok(())
+    }
+
+    fn process(&mut self, line: &[u8]) {
+        match self.wrapper {
+            Wrapper::Cri => match Envelope::parse(line) {
+                Some(envelope) => self.push(envelope.time, envelope.stream, envelope.partial, envelope.payload),
+                None => self.push_raw(line),
+            },
+            Wrapper::Docker => match json::from_slice::<DockerLine>(line) {
+                Ok(entry) => {
+                    let (payload, partial) = match entry.log.strip_suffix('\n') {
+                        Some(payload) => (payload, false),
+                        None => (&*entry.log, true),
+                    };
+                    self.push(&entry.time, &entry.stream, partial, payload.as_bytes());
+                }
+                Err(_) => self.push_raw(line),
+            },
+        }
+    }
+
+    fn push(&mut self, time: &str, stream: &str, partial: bool, payload: &[u8]) {
+        let i = match self.pending.iter().position(|(s, _)| s == stream) {
+            Some(i) => i,
+            None => {
+                let header = format!("{time} {stream} F ");
+                self.pending.push((stream.to_owned(), header.into_bytes()));
+                self.pending.len() - 1
+            }
+        };
+
+        self.pending[i].1.extend_from_slice(payload);
+        if !partial {
+            let (_, mut data) = self.pending.remove(i);
+            data.push(b'\n');
+            self.out.append(&mut data);
+        }
+    }
+
+    fn push_raw(&mut self, line: &[u8]) {
+        self.out.extend_from_slice(line);
+        self.out.push(b'\n');
+    }
+}
+
+impl<R: BufRead> Read for Unwrapper<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.out.len() {
+            self.fill()?;
+        }
+
+        let n = buf.len().min(self.out.len() - self.pos);
+        buf[..n].copy_from_slice(&self.out[self.pos..self.pos + n]);
+        self.pos += n;
+
+        Ok(n)
+    }
+}
+
+// ---
+
+#[derive(Deserialize)]
+struct DockerLine<'a> {
+    #[serde(borrow)]
+    log: Cow<'a, str>,
+    #[serde(borrow)]
+    stream: Cow<'a, str>,
+    #[serde(borrow)]
+    time: Cow<'a, str>,
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,101 @@
+use super::*;
+
+// std imports
+use std::{cell::RefCell, io::BufReader, rc::Rc};
+
+fn unwrap(wrapper: Wrapper, input: &str) -> String {
+    let mut output = String::new();
+    Unwrapper::new(input.as_bytes(), wrapper)
+        .read_to_string(&mut output)
+        .unwrap();
+    output
+}
+
+#[test]
+fn test_envelope() {
+    let line = b"2024-01-25T18:00:01.123456789Z stdout P {\"msg\":";
+    let envelope = Envelope::parse(line).unwrap();
+    assert_eq!(envelope.time, "2024-01-25T18:00:01.123456789Z");
+    assert_eq!(envelope.stream, "stdout");
+    assert!(envelope.partial);
+    assert_eq!(envelope.payload, b"{\"msg\":");
+    assert_eq!(&line[envelope.offset..], envelope.payload);
+
+    let envelope = Envelope::parse(b"2024-01-25T18:00:01Z stderr F").unwrap();
+    assert!(!envelope.partial);
+    assert_eq!(envelope.payload, b"");
+
+    assert_eq!(Envelope::parse(b"2024-01-25T18:00:01Z stdin F x"), None);
+    assert_eq!(Envelope::parse(b"2024-01-25T18:00:01Z stdout X x"), None);
+    assert_eq!(Envelope::parse(b"level=info msg=ok"), None);
+}
+
+#[test]
+fn test_unwrap_cri() {
+    let input = concat!(
+        "2024-01-25T18:00:01Z stdout P {\"msg\":\n",
+        "2024-01-25T18:00:02Z stderr F plain error\n",
+        "2024-01-25T18:00:03Z stdout F \"m1\"}\n",
+        "not wrapped\n",
+        "2024-01-25T18:00:04Z stdout P tail",
+    );
+    assert_eq!(
+        unwrap(Wrapper::Cri, input),
+        concat!(
+            "2024-01-25T18:00:02Z stderr F plain error\n",
+            "2024-01-25T18:00:01Z stdout F {\"msg\":\"m1\"}\n",
+            "not wrapped\n",
+            "2024-01-25T18:00:04Z stdout F tail\n",
+        )
+    );
+}
+
+#[test]
+fn test_unwrap_docker() {
+    let input = concat!(
+        r#"{"log":"{\"msg\":","stream":"stdout","time":"2024-01-25T18:00:01.1Z"}"#,
+        "\n",
+        r#"{"log":"\"m1\"}\n","stream":"stdout","time":"2024-01-25T18:00:01.2Z"}"#,
+        "\n",
+        r#"{"log":"level=warn msg=\"a\\tb\"\n","stream":"stderr","time":"2024-01-25T18:00:02Z"}"#,
+        "\n",
+    );
+    assert_eq!(
+        unwrap(Wrapper::Docker, input),
+        concat!(
+            "2024-01-25T18:00:01.1Z stdout F {\"msg\":\"m1\"}\n",
+            "2024-01-25T18:00:02Z stderr F level=warn msg=\"a\\tb\"\n",
+        )
+    );
+}
+
+#[test]
+fn test_unwrap_follow() {
+    struct Source(Rc<RefCell<Vec<u8>>>, usize);
+
+    impl Read for Source {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = (&self.0.borrow()[self.1..]).read(buf)?;
+            self.1 += n;
+            Ok(n)
+        }
+    }
+
+    let data = Rc::new(RefCell::new(
+        concat!(
+            "2024-01-25T18:00:01Z stdout P {\"msg\":\n",
+            "2024-01-25T18:00:02Z stdout F \"m1\"",
+        )
+        .as_bytes()
+        .to_vec(),
+    ));
+    let mut unwrapper = Unwrapper::new(BufReader::new(Source(data.clone(), 0)), Wrapper::Cri).with_follow(true);
+
+    let mut output = String::new();
+    unwrapper.read_to_string(&mut output).unwrap();
+    assert_eq!(output, "");
+
+    data.borrow_mut().extend_from_slice(b"}\n");
+    unwrapper.read_to_string(&mut output).unwrap();
+    assert_eq!(output, "2024-01-25T18:00:01Z stdout F {\"msg\":\"m1\"}\n");
+}
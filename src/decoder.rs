@@ -0,0 +1,586 @@
+// std imports
+use std::{
+    io::{self, BufRead, Read, Seek, SeekFrom},
+    mem,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+// third-party imports
+use flate2::Crc;
+use liblzma::bufread::XzDecoder;
+use miniz_oxide::inflate::{
+    TINFLStatus,
+    core::{
+        BlockBoundaryState, DecompressorOxide, TINFL_LZ_DICT_SIZE, decompress,
+        inflate_flags::{TINFL_FLAG_HAS_MORE_INPUT, TINFL_FLAG_STOP_ON_BLOCK_BOUNDARY},
+    },
+};
+
+// local imports
+use crate::index::Checkpoint;
+
+// ---
+
+/// Checkpoints recorded by a Decoder, shared with its owner.
+pub type Checkpoints = Arc<Mutex<Vec<Checkpoint>>>;
+
+// ---
+
+/// Compression format that allows decoding to be started at a Checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Xz,
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the compression of the detected format, or None if checkpoints are not supported for it.
+    pub fn from_format(format: deko::Format) -> Option<Self> {
+        match format {
+            deko::Format::Gzip => Some(Self::Gzip),
+            deko::Format::Xz => Some(Self::Xz),
+            deko::Format::Zstd => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+}
+
+// ---
+
+/// Decoder decodes compressed data member by member, recording a checkpoint at the start of each member.
+///
+/// Members are gzip members, zstd frames or xz streams concatenated in a single file.
+/// Inside gzip members, a checkpoint is also recorded at the first boundary between deflate blocks
+/// after each `span` bytes of decoded data, along with the window of decoded data preceding it,
+/// so that decoding can be resumed there without decoding the member from its start.
+pub struct Decoder<R: BufRead> {
+    compression: Compression,
+    state: State<R>,
+    decoded: u64,
+    checkpoints: Checkpoints,
+    span: u64,
+}
+
+impl<R: BufRead> Decoder<R> {
+    /// Returns a new Decoder for the reader positioned at the given checkpoint.
+    ///
+    /// The checkpoint must be at the start of a member.
+    pub fn new(compression: Compression, reader: R, start: Checkpoint) -> Self {
+        Self {
+            compression,
+            state: State::Between(Counter {
+                inner: reader,
+                position: start.compressed,
+            }),
+            decoded: start.decoded,
+            checkpoints: Checkpoints::default(),
+            span: DEFAULT_SPAN,
+        }
+    }
+
+    /// Sets the amount of decoded data between checkpoints inside gzip members.
+    pub fn with_span(self, span: u64) -> Self {
+        Self { span, ..self }
+    }
+
+    /// Returns the checkpoints recorded so far, more are added as the data is read.
+    pub fn checkpoints(&self) -> Checkpoints {
+        self.checkpoints.clone()
+    }
+
+    /// Returns the offset of the next byte to be read in the decoded data.
+    pub fn position(&self) -> u64 {
+        self.decoded
+    }
+
+    fn start_member(&mut self) -> io::Result<bool> {
+        let State::Between(reader) = &mut self.state else {
+            return Ok(true);
+        };
+
+        // some tools pad compressed files with zeros up to a block size, no member starts with zero
+        loop {
+            let buf = reader.fill_buf()?;
+            let n = buf.iter().take_while(|&&b| b == 0).count();
+            if n == 0 {
+                if buf.is_empty() {
+                    return Ok(false);
+                }
+                break;
+            }
+            reader.consume(n);
+        }
+
+        record(
+            &self.checkpoints,
+            Checkpoint {
+                compressed: reader.position,
+                decoded: self.decoded,
+                ..Default::default()
+            },
+        );
+
+        let State::Between(reader) = mem::replace(&mut self.state, State::Failed) else {
+            unreachable!()
+        };
+        let recorder = Recorder {
+            checkpoints: self.checkpoints.clone(),
+            span: self.span,
+        };
+        self.state = State::Member(Member::new(self.compression, reader, self.decoded, recorder)?);
+        Ok(true)
+    }
+}
+
+impl<R: BufRead + Seek> Decoder<R> {
+    /// Restarts decoding at the given checkpoint.
+    pub fn restart(&mut self, checkpoint: &Checkpoint) -> io::Result<()> {
+        let mut reader = match mem::replace(&mut self.state, State::Failed) {
+            State::Between(reader) => reader,
+            State::Member(member) => member.into_inner(),
+            State::Failed => return Err(failed()),
+        };
+
+        if checkpoint.window.is_empty() {
+            reader.inner.seek(SeekFrom::Start(checkpoint.compressed))?;
+            reader.position = checkpoint.compressed;
+            self.decoded = checkpoint.decoded;
+            self.state = State::Between(reader);
+            return Ok(());
+        }
+
+        if self.compression != Compression::Gzip {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "checkpoint inside a member is supported for gzip only",
+            ));
+        }
+
+        // the first bits of the next deflate block are the high bits of the preceding byte
+        let mut byte = [0];
+        if checkpoint.bits != 0 {
+            let offset = checkpoint.compressed.checked_sub(1).ok_or_else(invalid_checkpoint)?;
+            reader.inner.seek(SeekFrom::Start(offset))?;
+            reader.inner.read_exact(&mut byte)?;
+        } else {
+            reader.inner.seek(SeekFrom::Start(checkpoint.compressed))?;
+        }
+        reader.position = checkpoint.compressed;
+        self.decoded = checkpoint.decoded;
+
+        let recorder = Recorder {
+            checkpoints: self.checkpoints.clone(),
+            span: self.span,
+        };
+        self.state = State::Member(Member::Gzip(Inflater::resume(reader, checkpoint, byte[0], recorder)?));
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            if !self.start_member()? {
+                return Ok(0);
+            }
+            let State::Member(member) = &mut self.state else {
+                return Err(failed());
+            };
+            let n = member.read(buf)?;
+            if n != 0 {
+                self.decoded += n as u64;
+                return Ok(n);
+            }
+            let State::Member(member) = mem::replace(&mut self.state, State::Failed) else {
+                unreachable!()
+            };
+            self.state = State::Between(member.into_inner());
+        }
+    }
+}
+
+// ---
+
+/// SeekableDecoder provides random access to the decoded data using the recorded checkpoints.
+///
+/// Seeking to a position restarts decoding at the closest checkpoint before it unless the position is
+/// ahead of the current one and before the next checkpoint, so the cost of a seek is limited by the distance
+/// between checkpoints.
+pub struct SeekableDecoder<R: BufRead + Seek> {
+    decoder: Decoder<R>,
+    checkpoints: Vec<Checkpoint>,
+    size: u64,
+    position: u64,
+}
+
+impl<R: BufRead + Seek> SeekableDecoder<R> {
+    /// Returns a new SeekableDecoder for the decoded data of the given size.
+    ///
+    /// The checkpoints must be ordered by offset.
+    pub fn new(mut decoder: Decoder<R>, checkpoints: Vec<Checkpoint>, size: u64) -> io::Result<Self> {
+        let start = checkpoints.first().cloned().unwrap_or_default();
+        decoder.restart(&start)?;
+        Ok(Self {
+            decoder,
+            checkpoints,
+            size,
+            position: start.decoded,
+        })
+    }
+
+    fn checkpoint(&self, position: u64) -> Option<&Checkpoint> {
+        let n = self
+            .checkpoints
+            .partition_point(|checkpoint| checkpoint.decoded <= position);
+        n.checked_sub(1).map(|i| &self.checkpoints[i])
+    }
+}
+
+impl<R: BufRead + Seek> Read for SeekableDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let checkpoint = self.checkpoint(self.position).cloned().unwrap_or_default();
+        let current = self.decoder.position();
+        if current > self.position || current < checkpoint.decoded {
+            self.decoder.restart(&checkpoint)?;
+        }
+
+        let skip = self.position - self.decoder.position();
+        if skip != 0 {
+            let skipped = io::copy(&mut (&mut self.decoder).take(skip), &mut io::sink())?;
+            if skipped != skip {
+                return Ok(0);
+            }
+        }
+
+        let n = self.decoder.read(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: BufRead + Seek> Seek for SeekableDecoder<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.size.checked_add_signed(offset),
+        };
+        self.position = position.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "position out of range"))?;
+        Ok(self.position)
+    }
+}
+
+// ---
+
+enum State<R: BufRead> {
+    Between(Counter<R>),
+    Member(Member<R>),
+    Failed,
+}
+
+// ---
+
+enum Member<R: BufRead> {
+    Gzip(Inflater<R>),
+    Xz(XzDecoder<Counter<R>>),
+    Zstd(zstd::stream::read::Decoder<'static, Counter<R>>),
+}
+
+impl<R: BufRead> Member<R> {
+    fn new(compression: Compression, reader: Counter<R>, decoded: u64, recorder: Recorder) -> io::Result<Self> {
+        Ok(match compression {
+            Compression::Gzip => Self::Gzip(Inflater::new(reader, decoded, recorder)?),
+            Compression::Xz => Self::Xz(XzDecoder::new(reader)),
+            Compression::Zstd => Self::Zstd(zstd::stream::read::Decoder::with_buffer(reader)?.single_frame()),
+        })
+    }
+
+    fn into_inner(self) -> Counter<R> {
+        match self {
+            Self::Gzip(decoder) => decoder.reader,
+            Self::Xz(decoder) => decoder.into_inner(),
+            Self::Zstd(decoder) => decoder.finish(),
+        }
+    }
+}
+
+impl<R: BufRead> Read for Member<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Gzip(decoder) => decoder.read(buf),
+            Self::Xz(decoder) => decoder.read(buf),
+            Self::Zstd(decoder) => decoder.read(buf),
+        }
+    }
+}
+
+// ---
+
+/// Inflater decodes a gzip member and records checkpoints at boundaries between its deflate blocks.
+struct Inflater<R> {
+    reader: Counter<R>,
+    state: Box<DecompressorOxide>,
+    // decoded data in a ring buffer, which is also the window the following data may refer to
+    dict: Box<[u8]>,
+    // position in `dict` where the next decoded data is placed
+    pos: usize,
+    // decoded data in `dict` not yet read
+    pending: Range<usize>,
+    // amount of valid data in `dict`
+    filled: usize,
+    // offset in the decoded data of the end of the decoded data in `dict`
+    decoded: u64,
+    // checksum of the decoded data, unknown if decoding was resumed inside the member
+    crc: Option<Crc>,
+    done: bool,
+    recorder: Recorder,
+}
+
+impl<R: BufRead> Inflater<R> {
+    fn new(mut reader: Counter<R>, decoded: u64, recorder: Recorder) -> io::Result<Self> {
+        skip_gzip_header(&mut reader)?;
+        Ok(Self {
+            reader,
+            state: Box::default(),
+            dict: vec![0; TINFL_LZ_DICT_SIZE].into(),
+            pos: 0,
+            pending: 0..0,
+            filled: 0,
+            decoded,
+            crc: Some(Crc::new()),
+            done: false,
+            recorder,
+        })
+    }
+
+    fn resume(reader: Counter<R>, checkpoint: &Checkpoint, byte: u8, recorder: Recorder) -> io::Result<Self> {
+        let window = &checkpoint.window[..];
+        if window.len() > TINFL_LZ_DICT_SIZE || checkpoint.bits > 7 {
+            return Err(invalid_checkpoint());
+        }
+
+        let state = BlockBoundaryState {
+            num_bits: checkpoint.bits,
+            bit_buf: byte.checked_shr(u32::from(8 - checkpoint.bits)).unwrap_or(0),
+            ..Default::default()
+        };
+        let mut dict = vec![0; TINFL_LZ_DICT_SIZE];
+        dict[..window.len()].copy_from_slice(window);
+
+        Ok(Self {
+            reader,
+            state: Box::new(DecompressorOxide::from_block_boundary_state(&state)),
+            dict: dict.into(),
+            pos: window.len() % TINFL_LZ_DICT_SIZE,
+            pending: 0..0,
+            filled: window.len(),
+            decoded: checkpoint.decoded,
+            crc: None,
+            done: false,
+            recorder,
+        })
+    }
+
+    fn inflate(&mut self) -> io::Result<()> {
+        let input = self.reader.fill_buf()?;
+        let exhausted = input.is_empty();
+        let flags = TINFL_FLAG_HAS_MORE_INPUT | TINFL_FLAG_STOP_ON_BLOCK_BOUNDARY;
+        let (status, consumed, produced) = decompress(&mut self.state, input, &mut self.dict, self.pos, flags);
+        self.reader.consume(consumed);
+
+        let output = self.pos..self.pos + produced;
+        if let Some(crc) = &mut self.crc {
+            crc.update(&self.dict[output.clone()]);
+        }
+        self.pending = output;
+        self.pos = (self.pos + produced) % TINFL_LZ_DICT_SIZE;
+        self.filled = (self.filled + produced).min(TINFL_LZ_DICT_SIZE);
+        self.decoded += produced as u64;
+
+        match status {
+            TINFLStatus::Done => self.finish(),
+            TINFLStatus::BlockBoundary => {
+                self.checkpoint();
+                Ok(())
+            }
+            TINFLStatus::HasMoreOutput => Ok(()),
+            TINFLStatus::NeedsMoreInput if !exhausted => Ok(()),
+            TINFLStatus::NeedsMoreInput => Err(io::ErrorKind::UnexpectedEof.into()),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "corrupt deflate stream")),
+        }
+    }
+
+    fn checkpoint(&mut self) {
+        if !self.recorder.due(self.decoded) {
+            return;
+        }
+        let Some(state) = self.state.block_boundary_state() else {
+            return;
+        };
+
+        let start = (self.pos + TINFL_LZ_DICT_SIZE - self.filled) % TINFL_LZ_DICT_SIZE;
+        let mut window = Vec::with_capacity(self.filled);
+        if start < self.pos || self.filled == 0 {
+            window.extend_from_slice(&self.dict[start..self.pos]);
+        } else {
+            window.extend_from_slice(&self.dict[start..]);
+            window.extend_from_slice(&self.dict[..self.pos]);
+        }
+
+        record(
+            &self.recorder.checkpoints,
+            Checkpoint {
+                compressed: self.reader.position,
+                decoded: self.decoded,
+                bits: state.num_bits,
+                window: window.into(),
+            },
+        );
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let mut trailer = [0; 8];
+        self.reader.read_exact(&mut trailer)?;
+        if let Some(crc) = &self.crc {
+            let (sum, size) = trailer.split_at(4);
+            if sum != crc.sum().to_le_bytes() || size != crc.amount().to_le_bytes() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "corrupt gzip stream does not have a matching checksum",
+                ));
+            }
+        }
+        self.done = true;
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for Inflater<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if !self.pending.is_empty() {
+                let n = buf.len().min(self.pending.len());
+                let start = self.pending.start;
+                buf[..n].copy_from_slice(&self.dict[start..start + n]);
+                self.pending.start += n;
+                return Ok(n);
+            }
+            if self.done || buf.is_empty() {
+                return Ok(0);
+            }
+            self.inflate()?;
+        }
+    }
+}
+
+// ---
+
+/// Recorder decides when a checkpoint inside a member is due.
+struct Recorder {
+    checkpoints: Checkpoints,
+    span: u64,
+}
+
+impl Recorder {
+    fn due(&self, decoded: u64) -> bool {
+        let checkpoints = self.checkpoints.lock().unwrap();
+        checkpoints
+            .last()
+            .is_none_or(|last| decoded >= last.decoded.saturating_add(self.span))
+    }
+}
+
+/// Records the checkpoint unless it is not beyond the last one, which is the case when decoding is restarted.
+fn record(checkpoints: &Mutex<Vec<Checkpoint>>, checkpoint: Checkpoint) {
+    let mut checkpoints = checkpoints.lock().unwrap();
+    if checkpoints.last().is_none_or(|last| last.decoded < checkpoint.decoded) {
+        checkpoints.push(checkpoint);
+    }
+}
+
+// ---
+
+/// Counter keeps track of the position in the compressed data.
+struct Counter<R> {
+    inner: R,
+    position: u64,
+}
+
+impl<R: BufRead> Read for Counter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for Counter<R> {
+    #[inline]
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.position += amt as u64;
+    }
+}
+
+// ---
+
+fn skip_gzip_header<R: BufRead>(reader: &mut R) -> io::Result<()> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+
+    let mut header = [0; 10];
+    reader.read_exact(&mut header)?;
+    if header[..3] != [0x1f, 0x8b, 8] {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid gzip header"));
+    }
+    let flags = header[3];
+    if flags & FEXTRA != 0 {
+        let mut len = [0; 2];
+        reader.read_exact(&mut len)?;
+        let len = u64::from(u16::from_le_bytes(len));
+        if io::copy(&mut reader.take(len), &mut io::sink())? != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let mut buf = Vec::new();
+            reader.read_until(0, &mut buf)?;
+            if buf.last() != Some(&0) {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+        }
+    }
+    if flags & FHCRC != 0 {
+        reader.read_exact(&mut [0; 2])?;
+    }
+    Ok(())
+}
+
+fn failed() -> io::Error {
+    io::Error::other("decoder is in a failed state after a previous error")
+}
+
+fn invalid_checkpoint() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid decoder checkpoint")
+}
+
+/// Default amount of decoded data between checkpoints inside gzip members.
+const DEFAULT_SPAN: u64 = 1024 * 1024;
+
+#[cfg(test)]
+mod tests;
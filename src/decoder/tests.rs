@@ -0,0 +1,180 @@
+use super::*;
+
+use std::io::{Cursor, Write};
+
+fn compress(compression: Compression, data: &[u8]) -> Vec<u8> {
+    match compression {
+        Compression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(data).unwrap();
+            encoder.finish().unwrap()
+        }
+        Compression::Xz => {
+            let mut encoder = liblzma::write::XzEncoder::new(Vec::new(), 6);
+            encoder.write_all(data).unwrap();
+            encoder.finish().unwrap()
+        }
+        Compression::Zstd => zstd::encode_all(data, 0).unwrap(),
+    }
+}
+
+struct Sample {
+    compression: Compression,
+    data: Vec<u8>,
+    compressed: Vec<u8>,
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl Sample {
+    fn new(compression: Compression, members: &[&[u8]]) -> Self {
+        let mut sample = Self {
+            compression,
+            data: Vec::new(),
+            compressed: Vec::new(),
+            checkpoints: Vec::new(),
+        };
+        for member in members {
+            sample.checkpoints.push(Checkpoint {
+                compressed: sample.compressed.len() as u64,
+                decoded: sample.data.len() as u64,
+                ..Default::default()
+            });
+            sample.data.extend_from_slice(member);
+            sample.compressed.extend(compress(compression, member));
+        }
+        sample
+    }
+
+    fn decoder(&self) -> Decoder<Cursor<Vec<u8>>> {
+        Decoder::new(
+            self.compression,
+            Cursor::new(self.compressed.clone()),
+            Checkpoint::default(),
+        )
+    }
+}
+
+fn lines(prefix: &str, n: usize) -> Vec<u8> {
+    (0..n)
+        .map(|i| format!("{prefix} line {i}\n"))
+        .collect::<String>()
+        .into_bytes()
+}
+
+#[test]
+fn test_decoder() {
+    let (a, b, c) = (lines("a", 100), lines("b", 200), lines("c", 50));
+    for compression in [Compression::Gzip, Compression::Xz, Compression::Zstd] {
+        let sample = Sample::new(compression, &[&a, &b, &c]);
+        let mut decoder = sample.decoder();
+        let mut data = Vec::new();
+        decoder.read_to_end(&mut data).unwrap();
+        assert_eq!(data, sample.data, "{compression:?}");
+        assert_eq!(decoder.position(), sample.data.len() as u64);
+        assert_eq!(
+            *decoder.checkpoints().lock().unwrap(),
+            sample.checkpoints,
+            "{compression:?}"
+        );
+    }
+}
+
+#[test]
+fn test_decoder_padding() {
+    let mut sample = Sample::new(Compression::Gzip, &[b"a\n", b"b\n"]);
+    let end = sample.compressed.len();
+    sample.compressed.extend([0; 16]);
+    let mut decoder = sample.decoder();
+    let mut data = Vec::new();
+    decoder.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"a\nb\n");
+    assert_eq!(decoder.checkpoints().lock().unwrap().len(), 2);
+
+    sample.compressed.truncate(end - 1);
+    let mut data = Vec::new();
+    assert!(sample.decoder().read_to_end(&mut data).is_err());
+}
+
+#[test]
+fn test_seekable_decoder() {
+    let members = [lines("a", 100), lines("b", 200), lines("c", 50), lines("d", 10)];
+    let members = members.iter().map(|m| m.as_slice()).collect::<Vec<_>>();
+    for compression in [Compression::Gzip, Compression::Xz, Compression::Zstd] {
+        let sample = Sample::new(compression, &members);
+        let size = sample.data.len() as u64;
+        let mut decoder = SeekableDecoder::new(sample.decoder(), sample.checkpoints.clone(), size).unwrap();
+
+        for offset in [3000, 10, 2500, 2501, 0, 3600, size - 5, 1500] {
+            decoder.seek(SeekFrom::Start(offset)).unwrap();
+            let mut buf = [0; 32];
+            let n = decoder.read(&mut buf).unwrap();
+            let offset = offset as usize;
+            assert_ne!(n, 0, "{compression:?} at {offset}");
+            assert_eq!(
+                &buf[..n],
+                &sample.data[offset..offset + n],
+                "{compression:?} at {offset}"
+            );
+        }
+
+        assert_eq!(decoder.seek(SeekFrom::End(-3)).unwrap(), size - 3);
+        let mut tail = Vec::new();
+        decoder.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, &sample.data[size as usize - 3..]);
+
+        decoder.seek(SeekFrom::Start(size + 10)).unwrap();
+        assert_eq!(decoder.read(&mut [0; 8]).unwrap(), 0);
+        assert!(decoder.seek(SeekFrom::Current(-(size as i64) - 20)).is_err());
+    }
+}
+
+#[test]
+fn test_decoder_span() {
+    let data = (0..40000)
+        .map(|i| format!("line {i} value {}\n", i * 7919 % 10007))
+        .collect::<String>()
+        .into_bytes();
+    let sample = Sample::new(Compression::Gzip, &[&data]);
+    let span = 64 * 1024;
+    let mut decoder = sample.decoder().with_span(span);
+    let mut decoded = Vec::new();
+    decoder.read_to_end(&mut decoded).unwrap();
+    assert_eq!(decoded, data);
+
+    let checkpoints = decoder.checkpoints().lock().unwrap().clone();
+    assert!(checkpoints.len() > 4, "{}", checkpoints.len());
+    assert_eq!(checkpoints[0], sample.checkpoints[0]);
+    for pair in checkpoints.windows(2) {
+        assert!(pair[1].decoded >= pair[0].decoded + span);
+        assert_eq!(pair[1].window.len() as u64, pair[1].decoded.min(32 * 1024));
+    }
+
+    // decoding resumed at a checkpoint inside the member continues where it was recorded
+    for checkpoint in &checkpoints {
+        let mut decoder = sample.decoder();
+        decoder.restart(checkpoint).unwrap();
+        let mut tail = Vec::new();
+        decoder.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, &data[checkpoint.decoded as usize..]);
+    }
+
+    let size = data.len() as u64;
+    let mut decoder = SeekableDecoder::new(sample.decoder(), checkpoints, size).unwrap();
+    for offset in [size - 100, 10, size / 2, size / 3, 0, size / 2 + 1] {
+        decoder.seek(SeekFrom::Start(offset)).unwrap();
+        let mut buf = [0; 64];
+        decoder.read_exact(&mut buf).unwrap();
+        let offset = offset as usize;
+        assert_eq!(&buf[..], &data[offset..offset + 64], "at {offset}");
+    }
+}
+
+#[test]
+fn test_decoder_corrupt() {
+    let data = lines("a", 100);
+    let mut sample = Sample::new(Compression::Gzip, &[&data]);
+    let n = sample.compressed.len();
+    sample.compressed[n - 8] ^= 1;
+    let mut decoded = Vec::new();
+    assert!(sample.decoder().read_to_end(&mut decoded).is_err());
+}
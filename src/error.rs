@@ -5,6 +5,7 @@ use std::fmt;
 use std::io;
 use std::num::{ParseFloatError, ParseIntError, TryFromIntError};
 use std::path::PathBuf;
+use std::process::ExitStatus;
 use std::sync::mpsc;
 
 // third-party imports
@@ -16,8 +17,11 @@ use thiserror::Error;
 use serde_logfmt::logfmt;
 
 // local imports
+use crate::accesslog;
+use crate::formatting;
 use crate::level;
 use crate::pager;
+use crate::syslog;
 use crate::themecfg;
 use crate::xerr::{Highlight, HighlightQuoted, Suggestions};
 
@@ -64,6 +68,12 @@ pub enum Error {
     WrongFieldFilter(String),
     #[error("wrong regular expression: {0}")]
     WrongRegularExpression(#[from] regex::Error),
+    #[error("wrong regular expression in input format {}: {source}", .name.hlq())]
+    WrongInputFormatRegularExpression {
+        name: String,
+        #[source]
+        source: regex::Error,
+    },
     #[error("inconsistent index: {details}")]
     InconsistentIndex { details: String },
     #[error("failed to open file {} for reading: {source}", .path.hlq())]
@@ -108,6 +118,10 @@ pub enum Error {
     JsonParseError(#[from] json::Error),
     #[error("failed to parse logfmt: {0}")]
     LogfmtParseError(#[from] logfmt::error::Error),
+    #[error("failed to parse syslog: {0}")]
+    SyslogParseError(#[from] syslog::Error),
+    #[error("failed to parse access log entry: {0}")]
+    AccessLogParseError(#[from] accesslog::Error),
     #[error(transparent)]
     TryFromIntError(#[from] TryFromIntError),
     #[error(transparent)]
@@ -127,6 +141,18 @@ pub enum Error {
     ParseIntError(#[from] ParseIntError),
     #[error("failed to detect application directories")]
     AppDirs,
+    #[error("command {} failed: {status}", .command.hlq())]
+    CommandFailed { command: String, status: ExitStatus },
+    #[error("invalid resume name {}: only ASCII letters, digits, '-', '_' and '.' not at the start are allowed", .0.hlq())]
+    InvalidResumeName(String),
+    #[error("failed to parse resume state file {}: {source}", .path.hlq())]
+    FailedToParseResumeState {
+        path: PathBuf,
+        #[source]
+        source: json::Error,
+    },
+    #[error(transparent)]
+    Template(#[from] formatting::TemplateError),
 }
 
 impl Error {
@@ -158,6 +184,23 @@ impl Error {
         }
     }
 
+    /// Returns the exit code of the application terminated by the error.
+    ///
+    /// A failed command makes the application exit with the same code,
+    /// or with 128 plus the signal number if the command was terminated by a signal.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::CommandFailed { status, .. } => {
+                #[cfg(unix)]
+                if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(status) {
+                    return 128 + signal;
+                }
+                status.code().filter(|&code| code != 0).unwrap_or(1)
+            }
+            _ => 1,
+        }
+    }
+
     pub fn log<A>(&self, app: &A)
     where
         A: AppInfoProvider,
@@ -224,6 +267,15 @@ pub enum NonZeroSizeParseError {
     ZeroSize,
 }
 
+/// DurationParseError is an error which may occur when parsing non-zero duration.
+#[derive(Error, Debug)]
+pub enum DurationParseError {
+    #[error(transparent)]
+    InvalidDuration(#[from] humantime::DurationError),
+    #[error("zero duration")]
+    ZeroDuration,
+}
+
 /// NonZeroSizeParseError is an error which may occur when parsing non-zero size.
 #[derive(Error, Debug)]
 #[error("invalid level {value:?}, use any of {valid_values:?}")]
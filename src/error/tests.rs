@@ -111,3 +111,17 @@ fn test_from_pest_error() {
     let err = Error::from(pest_err);
     assert_matches!(err, Error::QueryParseError(_));
 }
+
+#[cfg(unix)]
+#[test]
+fn test_exit_code() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let failed = |raw| Error::CommandFailed {
+        command: "kubectl logs -f".into(),
+        status: ExitStatus::from_raw(raw),
+    };
+    assert_eq!(failed(3 << 8).exit_code(), 3);
+    assert_eq!(failed(9).exit_code(), 137);
+    assert_eq!(Error::AppDirs.exit_code(), 1);
+}
@@ -0,0 +1,336 @@
+// std imports
+use std::{
+    cmp::Ordering,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+    vec,
+};
+
+// local imports
+use crate::{index::Timestamp, model::looks_like_number};
+
+// ---
+
+/// SortKey is a value of the field the entries are sorted by.
+///
+/// Numbers are compared numerically and go before strings.
+#[derive(Debug, Clone)]
+pub enum SortKey {
+    Number(f64),
+    String(String),
+}
+
+impl SortKey {
+    pub fn new(value: String) -> Self {
+        match value.parse::<f64>() {
+            Ok(number) if looks_like_number(value.as_bytes()) => Self::Number(number),
+            _ => Self::String(value),
+        }
+    }
+}
+
+impl Ord for SortKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Number(a), Self::Number(b)) => a.total_cmp(b),
+            (Self::Number(_), Self::String(_)) => Ordering::Less,
+            (Self::String(_), Self::Number(_)) => Ordering::Greater,
+            (Self::String(a), Self::String(b)) => a.cmp(b),
+        }
+    }
+}
+
+impl PartialOrd for SortKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SortKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SortKey {}
+
+// ---
+
+/// Entry is a formatted record with its sort key and timestamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub key: Option<SortKey>,
+    pub ts: Option<Timestamp>,
+    pub data: Vec<u8>,
+}
+
+impl Entry {
+    /// Compares entries by key in the given direction, then by timestamp.
+    ///
+    /// Entries without a key go last in both directions.
+    fn compare(&self, other: &Self, descending: bool) -> Ordering {
+        let order = match (&self.key, &other.key) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) if descending => b.cmp(a),
+            (Some(a), Some(b)) => a.cmp(b),
+        };
+        order.then_with(|| self.ts.cmp(&other.ts))
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        let key = match &self.key {
+            Some(SortKey::String(s)) => s.len(),
+            _ => 0,
+        };
+        size_of::<Self>() + key + self.data.len()
+    }
+
+    fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        match &self.key {
+            None => w.write_all(&[0])?,
+            Some(SortKey::Number(n)) => {
+                w.write_all(&[1])?;
+                w.write_all(&n.to_le_bytes())?;
+            }
+            Some(SortKey::String(s)) => {
+                w.write_all(&[2])?;
+                write_bytes(w, s.as_bytes())?;
+            }
+        }
+        match &self.ts {
+            None => w.write_all(&[0])?,
+            Some(ts) => {
+                w.write_all(&[1])?;
+                w.write_all(&ts.sec.to_le_bytes())?;
+                w.write_all(&ts.nsec.to_le_bytes())?;
+            }
+        }
+        write_bytes(w, &self.data)
+    }
+
+    fn read<R: Read>(r: &mut R) -> io::Result<Option<Self>> {
+        let mut tag = [0];
+        if r.read(&mut tag)? == 0 {
+            return Ok(None);
+        }
+        let key = match tag[0] {
+            0 => None,
+            1 => Some(SortKey::Number(f64::from_le_bytes(read_array(r)?))),
+            _ => Some(SortKey::String(
+                String::from_utf8(read_bytes(r)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            )),
+        };
+        r.read_exact(&mut tag)?;
+        let ts = match tag[0] {
+            0 => None,
+            _ => Some(Timestamp {
+                sec: i64::from_le_bytes(read_array(r)?),
+                nsec: u32::from_le_bytes(read_array(r)?),
+            }),
+        };
+        let data = read_bytes(r)?;
+        Ok(Some(Self { key, ts, data }))
+    }
+}
+
+// ---
+
+/// Sorter sorts entries that may not fit in memory.
+///
+/// Entries are collected in runs of limited size, each run is sorted and spilled to a temporary file when full,
+/// and the runs are merged at the end. Entries with equal keys and timestamps keep their order.
+pub struct Sorter {
+    descending: bool,
+    run_size: usize,
+    dir: PathBuf,
+    entries: Vec<Entry>,
+    size: usize,
+    runs: Vec<Run>,
+}
+
+impl Sorter {
+    pub fn new(descending: bool, run_size: usize, dir: PathBuf) -> Self {
+        Self {
+            descending,
+            run_size,
+            dir,
+            entries: Vec::new(),
+            size: 0,
+            runs: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, entry: Entry) -> io::Result<()> {
+        self.size += entry.size();
+        self.entries.push(entry);
+        if self.size >= self.run_size {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the sorted entries.
+    pub fn finish(mut self) -> io::Result<Sorted> {
+        if self.runs.is_empty() {
+            self.sort();
+            return Ok(Sorted::Memory(self.entries.into_iter()));
+        }
+
+        if !self.entries.is_empty() {
+            self.spill()?;
+        }
+
+        let mut readers = Vec::with_capacity(self.runs.len());
+        let mut heads = Vec::with_capacity(self.runs.len());
+        for run in self.runs {
+            let mut reader = run.reader()?;
+            heads.push(Entry::read(&mut reader.file)?);
+            readers.push(reader);
+        }
+
+        Ok(Sorted::Merge(Merge {
+            descending: self.descending,
+            readers,
+            heads,
+        }))
+    }
+
+    fn sort(&mut self) {
+        let descending = self.descending;
+        self.entries.sort_by(|a, b| a.compare(b, descending));
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        self.sort();
+        let run = Run::create(&self.dir)?;
+        let mut writer = BufWriter::new(&run.file);
+        for entry in self.entries.drain(..) {
+            entry.write(&mut writer)?;
+        }
+        writer.flush()?;
+        drop(writer);
+        self.runs.push(run);
+        self.size = 0;
+        Ok(())
+    }
+}
+
+// ---
+
+/// Sorted is an iterator over the sorted entries, either kept in memory or merged from the spilled runs.
+pub enum Sorted {
+    Memory(vec::IntoIter<Entry>),
+    Merge(Merge),
+}
+
+impl Iterator for Sorted {
+    type Item = io::Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Memory(entries) => entries.next().map(Ok),
+            Self::Merge(merge) => merge.next(),
+        }
+    }
+}
+
+// ---
+
+pub struct Merge {
+    descending: bool,
+    readers: Vec<RunReader>,
+    heads: Vec<Option<Entry>>,
+}
+
+impl Iterator for Merge {
+    type Item = io::Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // runs are few, so a linear search is good enough, earlier runs win ties to keep the order stable
+        let mut best: Option<usize> = None;
+        for (i, head) in self.heads.iter().enumerate() {
+            let Some(head) = head else {
+                continue;
+            };
+            match best {
+                Some(b) if head.compare(self.heads[b].as_ref().unwrap(), self.descending) != Ordering::Less => {}
+                _ => best = Some(i),
+            }
+        }
+
+        let i = best?;
+        let next = match Entry::read(&mut self.readers[i].file) {
+            Ok(next) => next,
+            Err(e) => return Some(Err(e)),
+        };
+        std::mem::replace(&mut self.heads[i], next).map(Ok)
+    }
+}
+
+// ---
+
+/// Run is a temporary file with sorted entries, the file is removed when the run is dropped.
+struct Run {
+    path: PathBuf,
+    file: File,
+}
+
+impl Run {
+    fn create(dir: &Path) -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        loop {
+            let n = COUNTER.fetch_add(1, Relaxed);
+            let path = dir.join(format!("hl-sort-{}-{}", std::process::id(), n));
+            match OpenOptions::new().read(true).write(true).create_new(true).open(&path) {
+                Ok(file) => return Ok(Self { path, file }),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn reader(self) -> io::Result<RunReader> {
+        let file = BufReader::new(File::open(&self.path)?);
+        Ok(RunReader { file, _run: self })
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).ok();
+    }
+}
+
+struct RunReader {
+    file: BufReader<File>,
+    _run: Run,
+}
+
+// ---
+
+fn write_bytes<W: Write>(w: &mut W, data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u64).to_le_bytes())?;
+    w.write_all(data)
+}
+
+fn read_bytes<R: Read>(r: &mut R) -> io::Result<Vec<u8>> {
+    let len = u64::from_le_bytes(read_array(r)?);
+    let mut data = vec![0; usize::try_from(len).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?];
+    r.read_exact(&mut data)?;
+    Ok(data)
+}
+
+fn read_array<R: Read, const N: usize>(r: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests;
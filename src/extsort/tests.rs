@@ -0,0 +1,70 @@
+use super::*;
+use crate::testing::TempDir;
+
+fn entry(key: Option<&str>, sec: i64, data: &str) -> Entry {
+    Entry {
+        key: key.map(|key| SortKey::new(key.to_owned())),
+        ts: Some(Timestamp { sec, nsec: 0 }),
+        data: data.as_bytes().to_vec(),
+    }
+}
+
+fn sort(descending: bool, run_size: usize, entries: Vec<Entry>) -> Vec<String> {
+    let mut sorter = Sorter::new(descending, run_size, std::env::temp_dir());
+    for entry in entries {
+        sorter.push(entry).unwrap();
+    }
+    sorter
+        .finish()
+        .unwrap()
+        .map(|entry| String::from_utf8(entry.unwrap().data).unwrap())
+        .collect()
+}
+
+fn entries() -> Vec<Entry> {
+    vec![
+        entry(Some("15"), 1, "a"),
+        entry(None, 2, "b"),
+        entry(Some("abc"), 3, "c"),
+        entry(Some("2.5"), 4, "d"),
+        entry(Some("15"), 0, "e"),
+        entry(Some("100"), 5, "f"),
+        entry(Some("15"), 0, "g"),
+    ]
+}
+
+#[test]
+fn test_sort_key() {
+    assert!(SortKey::new("2".into()) < SortKey::new("10".into()));
+    assert!(SortKey::new("-1e3".into()) < SortKey::new("0".into()));
+    assert!(SortKey::new("10".into()) < SortKey::new("2s".into()));
+    assert!(SortKey::new("a".into()) < SortKey::new("b".into()));
+    assert!(matches!(SortKey::new("inf".into()), SortKey::String(_)));
+    assert_eq!(SortKey::new("1.0".into()), SortKey::new("1".into()));
+}
+
+#[test]
+fn test_sort_in_memory() {
+    assert_eq!(sort(false, usize::MAX, entries()), ["d", "e", "g", "a", "f", "c", "b"]);
+    assert_eq!(sort(true, usize::MAX, entries()), ["c", "f", "e", "g", "a", "d", "b"]);
+}
+
+#[test]
+fn test_sort_with_runs() {
+    assert_eq!(sort(false, 1, entries()), ["d", "e", "g", "a", "f", "c", "b"]);
+    assert_eq!(sort(true, 150, entries()), ["c", "f", "e", "g", "a", "d", "b"]);
+}
+
+#[test]
+fn test_run_files_removed() {
+    let tmp = TempDir::new("extsort");
+    let dir = tmp.path();
+    let mut sorter = Sorter::new(false, 1, dir.to_owned());
+    for entry in entries() {
+        sorter.push(entry).unwrap();
+    }
+    assert_eq!(fs::read_dir(dir).unwrap().count(), 7);
+    let sorted = sorter.finish().unwrap().collect::<io::Result<Vec<_>>>().unwrap();
+    assert_eq!(sorted.len(), 7);
+    assert_eq!(fs::read_dir(dir).unwrap().count(), 0);
+}
@@ -35,6 +35,14 @@ use string::{DynMessageFormat, ExtendedSpaceAction, Format, ValueFormatAuto};
 
 // ---
 
+mod structured;
+mod template;
+
+pub use structured::StructuredRecordFormatter;
+pub use template::{Template, TemplateError};
+
+// ---
+
 type Buf = Vec<u8>;
 
 // ---
@@ -205,6 +213,7 @@ pub struct RecordFormatterBuilder {
     punctuation: Option<Arc<ResolvedPunctuation>>,
     message_format: Option<DynMessageFormat>,
     expansion: Option<Expansion>,
+    template: Option<Arc<Template>>,
 }
 
 impl RecordFormatterBuilder {
@@ -304,6 +313,13 @@ impl RecordFormatterBuilder {
         }
     }
 
+    pub fn with_template(self, value: Arc<Template>) -> Self {
+        Self {
+            template: Some(value),
+            ..self
+        }
+    }
+
     pub fn build(self) -> RecordFormatter {
         let cfg = self.cfg.unwrap_or_default();
         let punctuation = self
@@ -331,6 +347,7 @@ impl RecordFormatterBuilder {
                 .unwrap_or_else(|| DynMessageFormat::new(&cfg, self.ascii)),
             punctuation,
             expansion: self.expansion.unwrap_or_default(),
+            template: self.template,
         }
     }
 
@@ -395,10 +412,15 @@ pub struct RecordFormatter {
     message_format: DynMessageFormat,
     punctuation: Arc<ResolvedPunctuation>,
     expansion: Expansion,
+    template: Option<Arc<Template>>,
 }
 
 impl RecordFormatter {
     pub fn format_record(&self, buf: &mut Buf, prefix: Range<usize>, rec: &model::Record) {
+        if let Some(template) = &self.template {
+            return self.format_record_with_template(template, buf, prefix, rec);
+        }
+
         let mut fs = FormattingStateWithRec {
             rec,
             fs: FormattingState {
@@ -0,0 +1,446 @@
+// std imports
+use std::{borrow::Cow, ops::Range, sync::Arc};
+
+// third-party imports
+use chrono::SecondsFormat;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_logfmt::logfmt;
+
+// workspace imports
+use encstr::EncodedString;
+
+// local imports
+use crate::{
+    ExactIncludeExcludeKeyFilter, IncludeExcludeKeyFilter,
+    app::OutputFormat,
+    filtering::IncludeExcludeSetting,
+    model::{self, RawValue},
+    timezone::Tz,
+};
+
+// relative imports
+use super::{Buf, RecordWithSourceFormatter};
+
+// ---
+
+/// Formats records as JSON or logfmt lines with normalized predefined fields.
+///
+/// Predefined fields are emitted as `time` in RFC 3339 format, `level` as a level name,
+/// `message`, `logger` and `caller`, followed by the remaining fields.
+/// Logfmt output omits fields with keys that logfmt cannot represent.
+pub struct StructuredRecordFormatter {
+    format: OutputFormat,
+    tz: Tz,
+    flatten: bool,
+    hide_empty_fields: bool,
+    fields: Arc<IncludeExcludeKeyFilter>,
+    predefined_fields: Arc<ExactIncludeExcludeKeyFilter>,
+}
+
+impl StructuredRecordFormatter {
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            tz: Tz::IANA(chrono_tz::UTC),
+            flatten: false,
+            hide_empty_fields: false,
+            fields: Default::default(),
+            predefined_fields: Default::default(),
+        }
+    }
+
+    pub fn with_time_zone(self, tz: Tz) -> Self {
+        Self { tz, ..self }
+    }
+
+    /// Enables flattening of nested objects into fields with dot-separated keys.
+    ///
+    /// Logfmt output is always flattened because logfmt has no nested values.
+    pub fn with_flatten(self, flatten: bool) -> Self {
+        Self { flatten, ..self }
+    }
+
+    pub fn with_empty_fields_hiding(self, enabled: bool) -> Self {
+        Self {
+            hide_empty_fields: enabled,
+            ..self
+        }
+    }
+
+    pub fn with_field_filter(self, fields: Arc<IncludeExcludeKeyFilter>) -> Self {
+        Self { fields, ..self }
+    }
+
+    pub fn with_predefined_field_filter(self, predefined_fields: Arc<ExactIncludeExcludeKeyFilter>) -> Self {
+        Self {
+            predefined_fields,
+            ..self
+        }
+    }
+
+    pub fn format_record(&self, buf: &mut Buf, rec: &model::Record) {
+        let mut w = Writer::new(buf, self.format, self.flatten || self.format == OutputFormat::Logfmt);
+        w.begin();
+
+        if let Some(ts) = &rec.ts {
+            match ts.parse() {
+                Some(ts) => {
+                    let ts = ts.with_timezone(&self.tz).fixed_offset();
+                    w.string_field("time", &ts.to_rfc3339_opts(SecondsFormat::AutoSi, true));
+                }
+                None => w.string_field("time", ts.raw()),
+            }
+        }
+        if let Some(level) = &rec.level {
+            w.string_field("level", level.as_ref());
+        }
+        if let Some(message) = rec.message {
+            w.field("message", message);
+        }
+        if let Some(logger) = rec.logger {
+            w.string_field("logger", logger);
+        }
+        if !rec.caller.is_empty() {
+            let caller = &rec.caller;
+            let mut location = caller.file.to_owned();
+            if !caller.line.is_empty() {
+                location.push(':');
+                location.push_str(caller.line);
+            }
+            match (caller.name.is_empty(), location.is_empty()) {
+                (false, false) => w.string_field("caller", &format!("{} {}", caller.name, location)),
+                (false, true) => w.string_field("caller", caller.name),
+                _ => w.string_field("caller", &location),
+            }
+        }
+
+        for (k, v) in rec.fields() {
+            self.format_field(
+                &mut w,
+                k,
+                *v,
+                Some(&self.fields),
+                IncludeExcludeSetting::Unspecified,
+                Some(&self.predefined_fields),
+                IncludeExcludeSetting::Unspecified,
+            );
+        }
+
+        w.end();
+    }
+
+    /// Formats a field applying the field filters to it and its nested fields.
+    ///
+    /// Returns `true` if anything has been written.
+    #[allow(clippy::too_many_arguments)]
+    fn format_field(
+        &self,
+        w: &mut Writer,
+        key: &str,
+        value: RawValue,
+        filter: Option<&IncludeExcludeKeyFilter>,
+        setting: IncludeExcludeSetting,
+        predefined_filter: Option<&ExactIncludeExcludeKeyFilter>,
+        predefined_setting: IncludeExcludeSetting,
+    ) -> bool {
+        if self.hide_empty_fields && value.is_empty() {
+            return false;
+        }
+
+        let (predefined_filter, predefined_setting, predefined_leaf) = match predefined_filter {
+            Some(filter) => {
+                let setting = predefined_setting.apply(filter.setting());
+                match filter.get(key) {
+                    Some(filter) => (Some(filter), setting.apply(filter.setting()), filter.leaf()),
+                    None => (None, setting, true),
+                }
+            }
+            None => (None, predefined_setting, true),
+        };
+        if predefined_setting == IncludeExcludeSetting::Exclude && predefined_leaf {
+            return false;
+        }
+
+        let (filter, setting, leaf) = match filter {
+            Some(filter) => {
+                let setting = setting.apply(filter.setting());
+                match filter.get(key) {
+                    Some(filter) => (Some(filter), setting.apply(filter.setting()), filter.leaf()),
+                    None => (None, setting, true),
+                }
+            }
+            None => (None, setting, true),
+        };
+        if setting == IncludeExcludeSetting::Exclude && leaf {
+            return false;
+        }
+
+        let RawValue::Object(object) = value else {
+            w.field(key, value);
+            return true;
+        };
+
+        let Ok(item) = object.parse() else {
+            w.field(key, value);
+            return true;
+        };
+        if item.fields.is_empty() {
+            w.field(key, value);
+            return true;
+        }
+
+        let checkpoint = w.begin_object(key);
+        let mut any = false;
+        for (k, v) in item.fields.iter() {
+            any |= self.format_field(w, k, *v, filter, setting, predefined_filter, predefined_setting);
+        }
+        if any {
+            w.end_object(checkpoint);
+        } else {
+            w.rollback(checkpoint);
+        }
+        any
+    }
+}
+
+impl RecordWithSourceFormatter for StructuredRecordFormatter {
+    #[inline]
+    fn format_record(&self, buf: &mut Buf, _prefix: Range<usize>, rec: model::RecordWithSource) {
+        StructuredRecordFormatter::format_record(self, buf, rec.record)
+    }
+}
+
+// ---
+
+struct Writer<'b> {
+    buf: &'b mut Buf,
+    format: OutputFormat,
+    flatten: bool,
+    key: String,
+    first: bool,
+}
+
+struct Checkpoint {
+    buf: usize,
+    key: usize,
+    first: bool,
+}
+
+impl<'b> Writer<'b> {
+    fn new(buf: &'b mut Buf, format: OutputFormat, flatten: bool) -> Self {
+        Self {
+            buf,
+            format,
+            flatten,
+            key: String::new(),
+            first: true,
+        }
+    }
+
+    fn begin(&mut self) {
+        if self.format == OutputFormat::Json {
+            self.buf.push(b'{');
+        }
+    }
+
+    fn end(&mut self) {
+        if self.format == OutputFormat::Json {
+            self.buf.push(b'}');
+        }
+    }
+
+    fn string_field(&mut self, key: &str, value: &str) {
+        match self.format {
+            OutputFormat::Json => {
+                self.key(key);
+                json_string(self.buf, value)
+            }
+            OutputFormat::Logfmt => self.logfmt_field(key, LogfmtValue::String(value.into())),
+        }
+    }
+
+    fn field(&mut self, key: &str, value: RawValue) {
+        match self.format {
+            OutputFormat::Json => {
+                self.key(key);
+                json_value(self.buf, value)
+            }
+            OutputFormat::Logfmt => self.logfmt_field(key, logfmt_value(value)),
+        }
+    }
+
+    fn logfmt_field(&mut self, key: &str, value: LogfmtValue) {
+        let checkpoint = self.buf.len();
+        if !self.first {
+            self.buf.push(b' ');
+        }
+        let key = if self.key.is_empty() {
+            Cow::Borrowed(key)
+        } else {
+            Cow::Owned(format!("{}{}", self.key, key))
+        };
+        match logfmt::to_writer(&mut *self.buf, &LogfmtField { key: &key, value }) {
+            Ok(()) => self.first = false,
+            Err(_) => self.buf.truncate(checkpoint),
+        }
+    }
+
+    fn begin_object(&mut self, key: &str) -> Checkpoint {
+        let checkpoint = Checkpoint {
+            buf: self.buf.len(),
+            key: self.key.len(),
+            first: self.first,
+        };
+        if self.flatten {
+            self.key.push_str(key);
+            self.key.push('.');
+        } else {
+            self.key(key);
+            self.buf.push(b'{');
+            self.first = true;
+        }
+        checkpoint
+    }
+
+    fn end_object(&mut self, checkpoint: Checkpoint) {
+        if self.flatten {
+            self.key.truncate(checkpoint.key);
+        } else {
+            self.buf.push(b'}');
+            self.first = false;
+        }
+    }
+
+    fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.buf.truncate(checkpoint.buf);
+        self.key.truncate(checkpoint.key);
+        self.first = checkpoint.first;
+    }
+
+    /// Writes a key of a JSON field, logfmt fields are written by [`Self::logfmt_field`].
+    fn key(&mut self, key: &str) {
+        if !self.first {
+            self.buf.push(b',');
+        }
+        if self.key.is_empty() {
+            json_string(self.buf, key);
+        } else {
+            json_string(self.buf, &format!("{}{}", self.key, key));
+        }
+        self.buf.push(b':');
+        self.first = false;
+    }
+}
+
+// ---
+
+fn json_string(buf: &mut Buf, value: &str) {
+    json::to_writer(buf, value).unwrap();
+}
+
+fn json_value(buf: &mut Buf, value: RawValue) {
+    let value = match value {
+        RawValue::String(EncodedString::Raw(value)) => RawValue::auto(value.as_str()),
+        _ => value,
+    };
+
+    match value {
+        RawValue::String(EncodedString::Json(_)) => buf.extend(value.raw_str().as_bytes()),
+        RawValue::String(_) => {
+            let mut decoded = Vec::new();
+            value.format_readable(&mut decoded);
+            json_string(buf, &String::from_utf8_lossy(&decoded));
+        }
+        RawValue::Number(value) => {
+            if json::from_str::<json::Number>(value).is_ok() {
+                buf.extend(value.as_bytes());
+            } else {
+                json_string(buf, value);
+            }
+        }
+        RawValue::Boolean(_) | RawValue::Null => buf.extend(value.raw_str().as_bytes()),
+        RawValue::Object(object) => match object.parse() {
+            Ok(item) => {
+                buf.push(b'{');
+                for (i, (k, v)) in item.fields.iter().enumerate() {
+                    if i != 0 {
+                        buf.push(b',');
+                    }
+                    json_string(buf, k);
+                    buf.push(b':');
+                    json_value(buf, *v);
+                }
+                buf.push(b'}');
+            }
+            Err(_) => json_string(buf, object.get()),
+        },
+        RawValue::Array(array) => match array.parse::<32>() {
+            Ok(item) => {
+                buf.push(b'[');
+                for (i, v) in item.iter().enumerate() {
+                    if i != 0 {
+                        buf.push(b',');
+                    }
+                    json_value(buf, *v);
+                }
+                buf.push(b']');
+            }
+            Err(_) => json_string(buf, array.get()),
+        },
+    }
+}
+
+fn logfmt_value(value: RawValue) -> LogfmtValue {
+    let value = match value {
+        RawValue::String(EncodedString::Raw(value)) => RawValue::auto(value.as_str()),
+        _ => value,
+    };
+
+    match value {
+        RawValue::String(_) => {
+            let mut decoded = Vec::new();
+            value.format_readable(&mut decoded);
+            LogfmtValue::String(String::from_utf8_lossy(&decoded).into_owned().into())
+        }
+        RawValue::Number(_) | RawValue::Boolean(_) | RawValue::Null => LogfmtValue::Token(value.raw_str()),
+        RawValue::Object(_) | RawValue::Array(_) => {
+            let mut encoded = Vec::new();
+            json_value(&mut encoded, value);
+            LogfmtValue::String(String::from_utf8_lossy(&encoded).into_owned().into())
+        }
+    }
+}
+
+// ---
+
+/// Single logfmt field serialized with the logfmt serializer.
+struct LogfmtField<'a> {
+    key: &'a str,
+    value: LogfmtValue<'a>,
+}
+
+impl Serialize for LogfmtField<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(self.key, &self.value)?;
+        map.end()
+    }
+}
+
+/// Value of a logfmt field, strings are quoted as needed while tokens such as numbers are written as is.
+enum LogfmtValue<'a> {
+    String(Cow<'a, str>),
+    Token(&'a str),
+}
+
+impl Serialize for LogfmtValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::String(value) => serializer.serialize_str(value),
+            Self::Token(value) => match logfmt::raw::RawValue::from_string((*value).to_owned()) {
+                Ok(value) => value.serialize(serializer),
+                Err(_) => serializer.serialize_str(value),
+            },
+        }
+    }
+}
@@ -0,0 +1,418 @@
+// std imports
+use std::{mem::take, ops::Range, str};
+
+// third-party imports
+use serde::{
+    Deserialize,
+    de::{IntoDeserializer, value},
+};
+use thiserror::Error;
+
+// workspace imports
+use encstr::EncodedString;
+
+// local imports
+use crate::{
+    fmtx::Alignment,
+    model::{self, FieldValueExtractor, Level, RawValue},
+    syntax::*,
+    theme::{Element, StylingPush},
+};
+
+// relative imports
+use super::{
+    Buf, FieldFormatResult, FormattingState, FormattingStateWithRec, RecordFormatter,
+    string::{ExtendedSpaceAction, Format, MessageFormatAutoQuoted, ValueFormatAuto},
+};
+
+// ---
+
+/// Template defines a custom layout of a formatted entry.
+///
+/// A template is a text with placeholders enclosed in curly braces, like
+/// `{time} {level:<3} {logger}: {message} {fields}`.
+/// Use `{{` and `}}` to output literal curly braces.
+///
+/// Supported placeholders are `time`, `level`, `logger`, `message`, `caller`,
+/// `fields` for all fields not referenced by other placeholders,
+/// and any other field key, like `http.status`.
+///
+/// A placeholder may be followed by a specification after a colon, in the form `[align][width][@style]`,
+/// where `align` is one of `<`, `>` or `^` for left, right or center alignment,
+/// `width` is the minimum width in characters, and `style` is a theme element name, like `number`.
+pub struct Template {
+    items: Vec<Item>,
+}
+
+impl Template {
+    pub fn parse(text: &str) -> Result<Self, TemplateError> {
+        let mut items = Vec::new();
+        let mut literal = String::new();
+        let mut chars = text.char_indices().peekable();
+
+        while let Some((pos, c)) = chars.next() {
+            match c {
+                '{' if chars.next_if(|&(_, c)| c == '{').is_some() => literal.push('{'),
+                '}' if chars.next_if(|&(_, c)| c == '}').is_some() => literal.push('}'),
+                '{' => {
+                    let end = match text[pos..].find('}') {
+                        Some(n) => pos + n,
+                        None => return Err(TemplateError::UnclosedPlaceholder(pos)),
+                    };
+                    if !literal.is_empty() {
+                        items.push(Item::Text(take(&mut literal)));
+                    }
+                    items.push(Item::Placeholder(Placeholder::parse(&text[pos + 1..end])?));
+                    while chars.next_if(|&(i, _)| i <= end).is_some() {}
+                }
+                '}' => return Err(TemplateError::UnmatchedClosingBrace(pos)),
+                _ => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            items.push(Item::Text(literal));
+        }
+
+        Ok(Self { items })
+    }
+
+    /// Returns keys of the fields referenced by the template explicitly.
+    pub fn field_keys(&self) -> impl Iterator<Item = &str> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Placeholder(Placeholder {
+                value: Value::Field { key, .. },
+                ..
+            }) => Some(key.as_str()),
+            _ => None,
+        })
+    }
+}
+
+// ---
+
+/// TemplateError is an error which may occur when parsing a template.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("unclosed placeholder at position {0} in template")]
+    UnclosedPlaceholder(usize),
+    #[error("unmatched closing brace at position {0} in template, use '}}}}' to output a literal brace")]
+    UnmatchedClosingBrace(usize),
+    #[error("empty placeholder in template")]
+    EmptyPlaceholder,
+    #[error("invalid width {0:?} in template placeholder")]
+    InvalidWidth(String),
+    #[error("unknown style {0:?} in template placeholder")]
+    UnknownStyle(String),
+    #[error("placeholder {{fields}} does not support width or style")]
+    UnsupportedFieldsSpec,
+}
+
+// ---
+
+enum Item {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+// ---
+
+struct Placeholder {
+    value: Value,
+    alignment: Alignment,
+    width: usize,
+    style: Option<Element>,
+}
+
+impl Placeholder {
+    fn parse(text: &str) -> Result<Self, TemplateError> {
+        let (name, spec) = text.split_once(':').unwrap_or((text, ""));
+
+        let value = match name {
+            "" => return Err(TemplateError::EmptyPlaceholder),
+            "time" => Value::Time,
+            "level" => Value::Level,
+            "logger" => Value::Logger,
+            "message" => Value::Message,
+            "caller" => Value::Caller,
+            "fields" => Value::Fields,
+            key => Value::Field {
+                key: key.to_owned(),
+                extractor: FieldValueExtractor::new(key),
+            },
+        };
+
+        let (spec, style) = match spec.split_once('@') {
+            Some((spec, style)) => (spec, Some(parse_style(style)?)),
+            None => (spec, None),
+        };
+
+        let (alignment, width) = match spec.as_bytes().first() {
+            Some(b'<') => (Alignment::Left, &spec[1..]),
+            Some(b'>') => (Alignment::Right, &spec[1..]),
+            Some(b'^') => (Alignment::Center, &spec[1..]),
+            _ => (Alignment::Left, spec),
+        };
+        let width = match width {
+            "" => 0,
+            _ => width
+                .parse()
+                .map_err(|_| TemplateError::InvalidWidth(width.to_owned()))?,
+        };
+
+        if matches!(value, Value::Fields) && (width != 0 || style.is_some()) {
+            return Err(TemplateError::UnsupportedFieldsSpec);
+        }
+
+        Ok(Self {
+            value,
+            alignment,
+            width,
+            style,
+        })
+    }
+
+    /// Pads the output produced by `f` with spaces up to the placeholder width.
+    fn aligned<F: FnOnce(&mut Buf)>(&self, buf: &mut Buf, f: F) {
+        let begin = buf.len();
+        f(buf);
+
+        let n = match str::from_utf8(&buf[begin..]) {
+            Ok(s) => s.chars().count(),
+            Err(_) => buf.len() - begin,
+        };
+        if n >= self.width {
+            return;
+        }
+
+        let padding = self.width - n;
+        let left = match self.alignment {
+            Alignment::Left => 0,
+            Alignment::Right => padding,
+            Alignment::Center => padding / 2,
+        };
+        buf.resize(buf.len() + padding, b' ');
+        buf[begin..].rotate_right(left);
+    }
+}
+
+fn parse_style(name: &str) -> Result<Element, TemplateError> {
+    let deserializer: value::StrDeserializer<value::Error> = name.into_deserializer();
+    Element::deserialize(deserializer).map_err(|_| TemplateError::UnknownStyle(name.to_owned()))
+}
+
+// ---
+
+enum Value {
+    Time,
+    Level,
+    Logger,
+    Message,
+    Caller,
+    Fields,
+    Field {
+        key: String,
+        extractor: FieldValueExtractor,
+    },
+}
+
+impl Value {
+    /// Returns theme elements used by default for the placeholder, an optional outer one and an inner one.
+    fn elements(&self, value: Option<RawValue>) -> (Option<Element>, Element) {
+        match self {
+            Self::Time => (None, Element::Time),
+            Self::Level => (Some(Element::Level), Element::LevelInner),
+            Self::Logger => (Some(Element::Logger), Element::LoggerInner),
+            Self::Message | Self::Fields => (None, Element::Message),
+            Self::Caller => (Some(Element::Caller), Element::CallerInner),
+            Self::Field { .. } => match value {
+                Some(RawValue::Number(_)) => (None, Element::Number),
+                Some(RawValue::Boolean(true)) => (Some(Element::Boolean), Element::BooleanTrue),
+                Some(RawValue::Boolean(false)) => (Some(Element::Boolean), Element::BooleanFalse),
+                Some(RawValue::Null) => (None, Element::Null),
+                Some(RawValue::Object(_)) => (None, Element::Object),
+                Some(RawValue::Array(_)) => (None, Element::Array),
+                Some(RawValue::String(_)) | None => (None, Element::String),
+            },
+        }
+    }
+}
+
+// ---
+
+impl RecordFormatter {
+    pub(super) fn format_record_with_template(
+        &self,
+        template: &Template,
+        buf: &mut Buf,
+        prefix: Range<usize>,
+        rec: &model::Record,
+    ) {
+        let mut fs = FormattingStateWithRec {
+            rec,
+            fs: FormattingState {
+                flatten: self.flatten && self.unescape_fields,
+                prefix,
+                has_fields: true,
+                ..Default::default()
+            },
+        };
+
+        self.theme.apply(buf, &rec.level, |s| {
+            let mut styled = false;
+            for item in &template.items {
+                match item {
+                    Item::Text(text) => {
+                        if take(&mut styled) {
+                            s.reset();
+                        }
+                        s.batch(|buf| buf.extend(text.as_bytes()));
+                    }
+                    Item::Placeholder(Placeholder {
+                        value: Value::Fields, ..
+                    }) => {
+                        self.format_template_fields(s, rec, &mut fs);
+                        styled = true;
+                    }
+                    Item::Placeholder(placeholder) => {
+                        self.format_placeholder(s, rec, placeholder);
+                        styled = true;
+                    }
+                }
+            }
+        });
+    }
+
+    fn format_template_fields<S: StylingPush<Buf>>(
+        &self,
+        s: &mut S,
+        rec: &model::Record,
+        fs: &mut FormattingStateWithRec,
+    ) {
+        let mut some_fields_hidden = false;
+        fs.dirty = false;
+
+        for (k, v) in rec.fields() {
+            if self.hide_empty_fields && v.is_empty() {
+                continue;
+            }
+            if let FieldFormatResult::Hidden =
+                self.format_field(s, k, *v, fs, Some(&self.fields), Some(&self.predefined_fields))
+            {
+                some_fields_hidden = true;
+            }
+        }
+
+        if some_fields_hidden || (fs.some_nested_fields_hidden && fs.flatten) || fs.some_fields_hidden {
+            fs.add_element(|| s.space());
+            s.element(Element::Ellipsis, |s| {
+                s.batch(|buf| buf.extend(self.punctuation.hidden_fields_indicator.as_bytes()))
+            });
+        }
+    }
+
+    fn format_placeholder<S: StylingPush<Buf>>(&self, s: &mut S, rec: &model::Record, placeholder: &Placeholder) {
+        let value = match &placeholder.value {
+            Value::Field { extractor, .. } => extractor.extract_raw(rec).map(|value| match value {
+                RawValue::String(EncodedString::Raw(value)) => RawValue::auto(value.as_str()),
+                _ => value,
+            }),
+            _ => None,
+        };
+
+        let (outer, inner) = match placeholder.style {
+            Some(style) => (None, style),
+            None => placeholder.value.elements(value),
+        };
+
+        let format = |s: &mut S| {
+            s.batch(|buf| {
+                placeholder.aligned(buf, |buf| {
+                    self.format_placeholder_value(buf, rec, &placeholder.value, value)
+                })
+            })
+        };
+
+        match outer {
+            Some(outer) => s.element(outer, |s| s.element(inner, format)),
+            None => s.element(inner, format),
+        }
+    }
+
+    fn format_placeholder_value(&self, buf: &mut Buf, rec: &model::Record, kind: &Value, value: Option<RawValue>) {
+        match kind {
+            Value::Time => match &rec.ts {
+                Some(ts) => {
+                    let begin = buf.len();
+                    if ts
+                        .as_rfc3339()
+                        .and_then(|ts| self.ts_formatter.reformat_rfc3339(buf, ts))
+                        .is_none()
+                    {
+                        buf.truncate(begin);
+                        match ts.parse() {
+                            Some(ts) => self.ts_formatter.format(buf, ts),
+                            None => buf.extend(ts.raw().as_bytes()),
+                        }
+                    }
+                }
+                None if self.always_show_time => buf.extend(self.ts_stub.as_bytes()),
+                None => {}
+            },
+            Value::Level => {
+                let level = match rec.level {
+                    Some(Level::Error) => Some(LEVEL_ERROR),
+                    Some(Level::Warning) => Some(LEVEL_WARNING),
+                    Some(Level::Info) => Some(LEVEL_INFO),
+                    Some(Level::Debug) => Some(LEVEL_DEBUG),
+                    Some(Level::Trace) => Some(LEVEL_TRACE),
+                    None => self.always_show_level.then_some(LEVEL_UNKNOWN),
+                };
+                if let Some(level) = level {
+                    buf.extend(level.as_bytes());
+                }
+            }
+            Value::Logger => {
+                if let Some(logger) = rec.logger {
+                    buf.extend(logger.as_bytes());
+                }
+            }
+            Value::Message => match rec.message {
+                Some(RawValue::String(value)) => {
+                    let xsa = ExtendedSpaceAction::Escape.into();
+                    if self.message_format.delimited {
+                        MessageFormatAutoQuoted.format(value, buf, xsa).unwrap();
+                    } else {
+                        self.message_format.format(value, buf, xsa).unwrap();
+                    }
+                }
+                Some(value) => buf.extend(value.raw_str().as_bytes()),
+                None => {}
+            },
+            Value::Caller => {
+                let caller = &rec.caller;
+                buf.extend(caller.name.as_bytes());
+                if !caller.file.is_empty() || !caller.line.is_empty() {
+                    if !caller.name.is_empty() {
+                        buf.extend(self.punctuation.caller_name_file_separator.as_bytes());
+                    }
+                    buf.extend(caller.file.as_bytes());
+                    if !caller.line.is_empty() {
+                        buf.push(b':');
+                        buf.extend(caller.line.as_bytes());
+                    }
+                }
+            }
+            Value::Fields => {}
+            Value::Field { .. } => match value {
+                Some(RawValue::String(value)) => {
+                    ValueFormatAuto
+                        .format(value, buf, ExtendedSpaceAction::Escape.into())
+                        .unwrap();
+                }
+                Some(value) => buf.extend(value.raw_str().as_bytes()),
+                None => {}
+            },
+        }
+    }
+}
@@ -6,6 +6,7 @@ use super::{
     *,
 };
 use crate::{
+    app::OutputFormat,
     datefmt::LinuxDateFormat,
     model::{
         Caller, Level, Parser, ParserSettings, RawArray, RawObject, RawRecord, Record, RecordFields,
@@ -56,6 +57,7 @@ fn formatter() -> RecordFormatterBuilder {
             },
             punctuation: Sample::sample(),
             prettify_field_keys: None,
+            template: None,
         })
 }
 
@@ -2979,6 +2981,7 @@ fn test_prettify_field_keys_disabled() {
             },
             punctuation: Sample::sample(),
             prettify_field_keys: Some(false),
+            template: None,
         })
         .build();
 
@@ -3009,6 +3012,7 @@ fn test_prettify_field_keys_enabled_default() {
             },
             punctuation: Sample::sample(),
             prettify_field_keys: Some(true),
+            template: None,
         })
         .build();
 
@@ -3041,6 +3045,7 @@ fn test_prettify_field_keys_flattened() {
             },
             punctuation: Sample::sample(),
             prettify_field_keys: Some(false),
+            template: None,
         })
         .build();
 
@@ -3072,6 +3077,7 @@ fn test_prettify_field_keys_raw_fields_override() {
             },
             punctuation: Sample::sample(),
             prettify_field_keys: Some(true),
+            template: None,
         })
         .build();
 
@@ -3095,3 +3101,156 @@ fn test_nested_object_key_with_escaped_quotes() {
 
     format_no_color(&rec);
 }
+
+fn structured_record<'a>(fields: &'a json::value::RawValue) -> Record<'a> {
+    Record {
+        ts: Some(Timestamp::new("2000-01-02T03:04:05.123+01:00")),
+        message: Some(RawValue::String(EncodedString::json(r#""tm""#))),
+        level: Some(Level::Debug),
+        logger: Some("tl"),
+        caller: Caller::with_name("tc"),
+        fields: RecordFields::from_slice(&[
+            ("k_a", RawValue::from(RawObject::Json(fields))),
+            ("k-b", RawValue::String(EncodedString::raw("x y"))),
+            ("k-c", RawValue::Number("42")),
+        ]),
+        ..Default::default()
+    }
+}
+
+fn format_structured(formatter: &StructuredRecordFormatter, rec: &Record) -> String {
+    let mut buf = Vec::new();
+    formatter.format_record(&mut buf, rec);
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn test_structured_json() {
+    let ka = json_raw_value(r#"{"va":{"kb":42,"kc":"4\n3"}}"#);
+    let rec = structured_record(&ka);
+
+    assert_eq!(
+        format_structured(&StructuredRecordFormatter::new(OutputFormat::Json), &rec),
+        r#"{"time":"2000-01-02T02:04:05.123Z","level":"debug","message":"tm","logger":"tl","caller":"tc","k_a":{"va":{"kb":42,"kc":"4\n3"}},"k-b":"x y","k-c":42}"#,
+    );
+
+    assert_eq!(
+        format_structured(
+            &StructuredRecordFormatter::new(OutputFormat::Json).with_flatten(true),
+            &rec
+        ),
+        r#"{"time":"2000-01-02T02:04:05.123Z","level":"debug","message":"tm","logger":"tl","caller":"tc","k_a.va.kb":42,"k_a.va.kc":"4\n3","k-b":"x y","k-c":42}"#,
+    );
+}
+
+#[test]
+fn test_structured_logfmt() {
+    let ka = json_raw_value(r#"{"va":{"kb":42,"kc":"4\n3"},"vd":[1,"2"],"ve":"true"}"#);
+    let rec = structured_record(&ka);
+
+    assert_eq!(
+        format_structured(&StructuredRecordFormatter::new(OutputFormat::Logfmt), &rec),
+        r#"time=2000-01-02T02:04:05.123Z level=debug message=tm logger=tl caller=tc k_a.va.kb=42 k_a.va.kc="4\n3" k_a.vd="[1,\"2\"]" k_a.ve="true" k-b="x y" k-c=42"#,
+    );
+}
+
+#[test]
+fn test_structured_hidden_fields() {
+    let mut fields = IncludeExcludeKeyFilter::default();
+    fields.entry("k-b").exclude();
+    fields.entry("k_a").entry("va").exclude();
+    let formatter = StructuredRecordFormatter::new(OutputFormat::Json).with_field_filter(fields.into());
+
+    let ka = json_raw_value(r#"{"va":{"kb":42}}"#);
+    let rec = structured_record(&ka);
+
+    assert_eq!(
+        format_structured(&formatter, &rec),
+        r#"{"time":"2000-01-02T02:04:05.123Z","level":"debug","message":"tm","logger":"tl","caller":"tc","k-c":42}"#,
+    );
+}
+
+fn format_with_template(builder: RecordFormatterBuilder, template: &str, rec: &Record) -> String {
+    builder
+        .with_template(Template::parse(template).unwrap().into())
+        .build()
+        .format_to_string(rec)
+}
+
+#[test]
+fn test_template() {
+    let ka = json_raw_value(r#"{"va":{"kb":42,"kc":43}}"#);
+    let rec = Record {
+        ts: Some(Timestamp::new("2000-01-02T03:04:05.123Z")),
+        message: Some(RawValue::String(EncodedString::json(r#""tm""#))),
+        level: Some(Level::Debug),
+        logger: Some("tl"),
+        caller: Caller::with_name("tc"),
+        fields: RecordFields::from_slice(&[
+            ("k_a", RawValue::from(RawObject::Json(&ka))),
+            ("k_b", RawValue::String(EncodedString::raw("x y"))),
+        ]),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        format_with_template(
+            formatter().with_theme(Default::default()),
+            "{time} [{level:^5}] {logger}: {message} {{{k_a.va.kb:>4}}} {caller:>4}|{missing:3}| {fields}",
+            &rec,
+        ),
+        r#"00-01-02 03:04:05.123 [ DBG ] tl: tm {  42}   tc|   | k-a={ va={ kb=42 kc=43 } } k-b="x y""#,
+    );
+
+    assert_eq!(
+        format_with_template(formatter().with_theme(Default::default()), "{k_b}/{message:4}.", &rec),
+        r#""x y"/tm  ."#,
+    );
+}
+
+#[test]
+fn test_template_styles() {
+    let status = json_raw_value(r#"{"status":200}"#);
+    let rec = Record {
+        level: Some(Level::Debug),
+        fields: RecordFields::from_slice(&[("http", RawValue::from(RawObject::Json(&status)))]),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        format_with_template(formatter(), "{level}: {http.status:>4}", &rec),
+        "\u{1b}[0;95mDBG\u{1b}[0m: \u{1b}[0;94m 200\u{1b}[0m",
+    );
+    assert_eq!(
+        format_with_template(formatter(), "{level}: {http.status:@level-inner}", &rec),
+        "\u{1b}[0;95mDBG\u{1b}[0m: \u{1b}[0;95m200\u{1b}[0m",
+    );
+}
+
+#[test]
+fn test_template_parse() {
+    let template = Template::parse("{time} {a.b:>10@number} {{c}} {d:5}").unwrap();
+    assert_eq!(template.field_keys().collect::<Vec<_>>(), vec!["a.b", "d"]);
+
+    assert_eq!(
+        Template::parse("x {time").err(),
+        Some(TemplateError::UnclosedPlaceholder(2))
+    );
+    assert_eq!(
+        Template::parse("x}").err(),
+        Some(TemplateError::UnmatchedClosingBrace(1))
+    );
+    assert_eq!(Template::parse("{}").err(), Some(TemplateError::EmptyPlaceholder));
+    assert_eq!(
+        Template::parse("{level:x}").err(),
+        Some(TemplateError::InvalidWidth("x".into()))
+    );
+    assert_eq!(
+        Template::parse("{level:@nope}").err(),
+        Some(TemplateError::UnknownStyle("nope".into()))
+    );
+    assert_eq!(
+        Template::parse("{fields:10}").err(),
+        Some(TemplateError::UnsupportedFieldsSpec)
+    );
+}
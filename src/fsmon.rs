@@ -1,5 +1,8 @@
 // std imports
-use std::path::PathBuf;
+use std::{ops::ControlFlow, path::PathBuf};
+
+// third-party imports
+use crossbeam_channel::Receiver;
 
 // local imports
 use crate::error::Result;
@@ -8,12 +11,18 @@ use crate::error::Result;
 
 pub type Event = notify::Event;
 pub type EventKind = notify::EventKind;
+pub type ModifyKind = notify::event::ModifyKind;
+
+/// Result of handling an event, tells whether to continue watching.
+pub type Flow = ControlFlow<()>;
 
 // ---
 
-pub fn run<H>(mut paths: Vec<PathBuf>, mut handle: H) -> Result<()>
+/// Watches the files and calls the handler for each event related to them,
+/// until the handler breaks the flow or the `stop` channel is closed.
+pub fn run<H>(mut paths: Vec<PathBuf>, stop: &Receiver<()>, mut handle: H) -> Result<()>
 where
-    H: FnMut(Event) -> Result<()>,
+    H: FnMut(Event) -> Result<Flow>,
 {
     if paths.is_empty() {
         return Ok(());
@@ -45,15 +54,38 @@ where
     watch.sort_unstable();
     watch.dedup();
 
-    imp::run(watch, |event| {
+    imp::run(watch, stop, |event| {
         if event.paths.iter().any(|path| paths.binary_search(path).is_ok()) {
             handle(event)
         } else {
-            Ok(())
+            Ok(Flow::Continue(()))
         }
     })
 }
 
+/// Watches the directories and calls the handler for each event related to them or their entries,
+/// until the handler breaks the flow or the `stop` channel is closed.
+pub fn run_dirs<H>(mut dirs: Vec<PathBuf>, stop: &Receiver<()>, handle: H) -> Result<()>
+where
+    H: FnMut(Event) -> Result<Flow>,
+{
+    dirs.retain(|dir| dir.is_dir());
+    if dirs.is_empty() {
+        return Ok(());
+    }
+
+    for dir in dirs.iter_mut() {
+        if let Ok(canonical_dir) = dir.canonicalize() {
+            *dir = canonical_dir;
+        }
+    }
+
+    dirs.sort_unstable();
+    dirs.dedup();
+
+    imp::run(dirs, stop, handle)
+}
+
 #[cfg(windows)]
 mod windows;
 #[cfg(windows)]
@@ -64,6 +96,7 @@ mod imp {
     use std::sync::mpsc::{self};
     use std::time::Duration;
 
+    use crossbeam_channel as channel;
     use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 
     use super::*;
@@ -71,23 +104,39 @@ mod imp {
 
     const FALLBACK_POLLING_INTERVAL: Duration = Duration::from_secs(1);
 
-    pub fn run<H>(paths: Vec<PathBuf>, mut handle: H) -> Result<()>
+    pub fn run<H>(paths: Vec<PathBuf>, stop: &Receiver<()>, mut handle: H) -> Result<()>
     where
-        H: FnMut(Event) -> Result<()>,
+        H: FnMut(Event) -> Result<Flow>,
     {
-        let (tx, rx) = mpsc::channel();
-        let mut watcher = RecommendedWatcher::new(tx, Config::default().with_poll_interval(FALLBACK_POLLING_INTERVAL))?;
+        let (tx, rx) = channel::unbounded();
+        let mut watcher = RecommendedWatcher::new(
+            move |event| {
+                tx.send(event).ok();
+            },
+            Config::default().with_poll_interval(FALLBACK_POLLING_INTERVAL),
+        )?;
 
         for path in &paths {
             watcher.watch(path, RecursiveMode::NonRecursive)?;
         }
 
         loop {
-            match rx.recv() {
-                Ok(Ok(event)) => handle(event)?,
-                Ok(Err(err)) => return Err(err.into()),
-                Err(err) => return Err(Error::RecvTimeoutError { source: err.into() }),
-            };
+            channel::select! {
+                recv(rx) -> event => match event {
+                    Ok(Ok(event)) => {
+                        if handle(event)?.is_break() {
+                            return Ok(());
+                        }
+                    }
+                    Ok(Err(err)) => return Err(err.into()),
+                    Err(_) => {
+                        return Err(Error::RecvTimeoutError {
+                            source: mpsc::RecvTimeoutError::Disconnected,
+                        });
+                    }
+                },
+                recv(stop) -> _ => return Ok(()),
+            }
         }
     }
 }
@@ -96,14 +145,15 @@ mod imp {
 mod imp {
     use std::{collections::HashSet, time::Duration};
 
+    use crossbeam_channel::TryRecvError;
     use kqueue::{EventData, EventFilter, FilterFlag, Ident, Vnode, Watcher};
     use notify::event::{CreateKind, DataChange, MetadataKind, ModifyKind, RemoveKind, RenameMode};
 
     use super::*;
 
-    pub fn run<H>(paths: Vec<PathBuf>, mut handle: H) -> Result<()>
+    pub fn run<H>(paths: Vec<PathBuf>, stop: &Receiver<()>, mut handle: H) -> Result<()>
     where
-        H: FnMut(Event) -> Result<()>,
+        H: FnMut(Event) -> Result<Flow>,
     {
         let mut watcher = Watcher::new()?;
         let mut added = HashSet::<&PathBuf>::new();
@@ -124,8 +174,10 @@ mod imp {
             for path in &paths {
                 if watcher.add_filename(path, EventFilter::EVFILT_VNODE, flags).is_ok() {
                     added.insert(path);
-                    if !synced {
-                        handle(Event::new(EventKind::Create(CreateKind::Any)).add_path(path.clone()))?;
+                    if !synced
+                        && handle(Event::new(EventKind::Create(CreateKind::Any)).add_path(path.clone()))?.is_break()
+                    {
+                        return Ok(());
                     }
                 }
             }
@@ -134,6 +186,10 @@ mod imp {
             watcher.watch()?;
 
             while synced {
+                // polling times out regularly, so that the stop channel is checked even if nothing happens
+                if let Err(TryRecvError::Disconnected) = stop.try_recv() {
+                    return Ok(());
+                }
                 let event = if let Some(event) = watcher.poll(Some(Duration::from_secs(1))) {
                     event
                 } else {
@@ -180,7 +236,9 @@ mod imp {
                             #[allow(unreachable_patterns)]
                             _ => Event::new(EventKind::Other),
                         };
-                        handle(event)?;
+                        if handle(event)?.is_break() {
+                            return Ok(());
+                        }
                     }
                     _ => unreachable!(),
                 };
@@ -5,12 +5,13 @@ use std::{
     time::{Duration, Instant},
 };
 
+use crossbeam_channel::{self as channel, Receiver};
 use notify::{
     Config, RecommendedWatcher, RecursiveMode, Watcher,
     event::{CreateKind, DataChange, ModifyKind, RemoveKind},
 };
 
-use super::{Event, EventKind};
+use super::{Event, EventKind, Flow};
 use crate::{
     error::{Error, Result},
     win_file_id,
@@ -78,11 +79,11 @@ fn sleep_duration(avg_secs: impl Iterator<Item = f64>) -> Duration {
     Duration::from_secs_f64((min / 2.0).clamp(FLOOR_SECS, CEILING_SECS))
 }
 
-pub(super) fn run<H>(paths: Vec<PathBuf>, mut handle: H) -> Result<()>
+pub(super) fn run<H>(paths: Vec<PathBuf>, stop: &Receiver<()>, mut handle: H) -> Result<()>
 where
-    H: FnMut(Event) -> Result<()>,
+    H: FnMut(Event) -> Result<Flow>,
 {
-    let (tx, rx) = mpsc::channel::<Event>();
+    let (tx, rx) = channel::unbounded::<Event>();
 
     // Thread 1: ReadDirectoryChangesW via notify.
     // Catches file rotation (rename/create/delete) immediately; write events
@@ -204,9 +205,20 @@ where
     drop(tx);
 
     loop {
-        match rx.recv() {
-            Ok(event) => handle(event)?,
-            Err(err) => return Err(Error::RecvTimeoutError { source: err.into() }),
+        channel::select! {
+            recv(rx) -> event => match event {
+                Ok(event) => {
+                    if handle(event)?.is_break() {
+                        return Ok(());
+                    }
+                }
+                Err(_) => {
+                    return Err(Error::RecvTimeoutError {
+                        source: mpsc::RecvTimeoutError::Disconnected,
+                    });
+                }
+            },
+            recv(stop) -> _ => return Ok(()),
         }
     }
 }
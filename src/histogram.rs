@@ -0,0 +1,303 @@
+// std imports
+use std::{
+    collections::BTreeMap,
+    io::{Result, Write},
+    sync::Arc,
+    time::Duration,
+};
+
+// third-party imports
+use chrono::DateTime;
+use enum_map::EnumMap;
+
+// local imports
+use crate::{
+    datefmt::DateTimeFormatter,
+    model::{Level, Record},
+    settings::AsciiMode,
+    theme::{Element, StylingPush, Theme},
+};
+
+// ---
+
+const MS_PER_SEC: i64 = 1000;
+const MS_PER_DAY: i64 = 24 * 60 * 60 * MS_PER_SEC;
+
+/// Bucket sizes in milliseconds considered when the bucket size is chosen automatically.
+const AUTO_BUCKETS: &[i64] = &[
+    1,
+    2,
+    5,
+    10,
+    20,
+    50,
+    100,
+    200,
+    500,
+    MS_PER_SEC,
+    2 * MS_PER_SEC,
+    5 * MS_PER_SEC,
+    10 * MS_PER_SEC,
+    15 * MS_PER_SEC,
+    30 * MS_PER_SEC,
+    60 * MS_PER_SEC,
+    2 * 60 * MS_PER_SEC,
+    5 * 60 * MS_PER_SEC,
+    10 * 60 * MS_PER_SEC,
+    15 * 60 * MS_PER_SEC,
+    30 * 60 * MS_PER_SEC,
+    60 * 60 * MS_PER_SEC,
+    2 * 60 * 60 * MS_PER_SEC,
+    3 * 60 * 60 * MS_PER_SEC,
+    6 * 60 * 60 * MS_PER_SEC,
+    12 * 60 * 60 * MS_PER_SEC,
+    MS_PER_DAY,
+    2 * MS_PER_DAY,
+    7 * MS_PER_DAY,
+];
+
+/// Desired maximum number of buckets when the bucket size is chosen automatically.
+const AUTO_BUCKET_COUNT: u128 = 50;
+
+const MIN_BAR_WIDTH: usize = 10;
+
+// ---
+
+/// Counts contains the number of entries per level.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Counts {
+    pub levels: EnumMap<Level, u64>,
+    pub unknown: u64,
+}
+
+impl Counts {
+    #[inline]
+    pub fn total(&self) -> u64 {
+        self.levels.values().sum::<u64>() + self.unknown
+    }
+
+    #[inline]
+    fn add(&mut self, level: Option<Level>) {
+        match level {
+            Some(level) => self.levels[level] += 1,
+            None => self.unknown += 1,
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (level, n) in other.levels {
+            self.levels[level] += n;
+        }
+        self.unknown += other.unknown;
+    }
+
+    fn segments(&self) -> impl Iterator<Item = (Option<Level>, u64)> + '_ {
+        self.levels
+            .iter()
+            .map(|(level, n)| (Some(level), *n))
+            .chain(std::iter::once((None, self.unknown)))
+    }
+}
+
+// ---
+
+/// Histogram counts entries per level in time buckets of the same size.
+#[derive(Debug)]
+pub struct Histogram {
+    bucket: i64,
+    offset: i64,
+    buckets: BTreeMap<i64, Counts>,
+    untimed: u64,
+}
+
+impl Histogram {
+    /// Returns a new Histogram with buckets of the given size.
+    ///
+    /// Buckets are aligned to midnight in a time zone with the given offset from UTC in seconds.
+    pub fn new(bucket: Duration, offset: i32) -> Self {
+        Self {
+            bucket: bucket.as_millis().clamp(1, i64::MAX as u128) as i64,
+            offset: i64::from(offset) * MS_PER_SEC,
+            buckets: BTreeMap::new(),
+            untimed: 0,
+        }
+    }
+
+    /// Returns the smallest of the predefined bucket sizes that splits the given time span into a reasonable number of buckets.
+    pub fn auto_bucket(span: Duration) -> Duration {
+        let span = span.as_millis();
+        let bucket = AUTO_BUCKETS
+            .iter()
+            .copied()
+            .find(|&bucket| span / (bucket as u128) < AUTO_BUCKET_COUNT)
+            .unwrap_or_else(|| {
+                let weeks = span / AUTO_BUCKET_COUNT / (7 * MS_PER_DAY) as u128 + 1;
+                weeks.min(i64::MAX as u128 / (7 * MS_PER_DAY) as u128) as i64 * 7 * MS_PER_DAY
+            });
+        Duration::from_millis(bucket as u64)
+    }
+
+    /// Counts the entry in the bucket its timestamp belongs to.
+    pub fn add(&mut self, rec: &Record) {
+        match rec.ts.as_ref().and_then(|ts| ts.unix_utc()) {
+            Some((sec, nsec)) => {
+                let ms = sec
+                    .saturating_mul(MS_PER_SEC)
+                    .saturating_add(i64::from(nsec / 1_000_000));
+                let key = ms.saturating_add(self.offset).div_euclid(self.bucket);
+                self.buckets.entry(key).or_default().add(rec.level);
+            }
+            None => self.untimed += 1,
+        }
+    }
+
+    /// Merges with other Histogram having the same bucket size and offset.
+    pub fn merge(&mut self, other: Self) {
+        for (key, counts) in other.buckets {
+            self.buckets.entry(key).or_default().merge(&counts);
+        }
+        self.untimed += other.untimed;
+    }
+
+    /// Returns the number of entries without a timestamp.
+    pub fn untimed(&self) -> u64 {
+        self.untimed
+    }
+
+    /// Returns start time in milliseconds since the Unix epoch and counts of all buckets
+    /// from the first to the last non-empty one, including the empty buckets between them.
+    pub fn buckets(&self) -> impl Iterator<Item = (i64, Counts)> + '_ {
+        let keys = match (self.buckets.keys().next(), self.buckets.keys().next_back()) {
+            (Some(&first), Some(&last)) => first..=last,
+            _ => 1..=0,
+        };
+        keys.map(|key| {
+            (
+                key * self.bucket - self.offset,
+                self.buckets.get(&key).copied().unwrap_or_default(),
+            )
+        })
+    }
+
+    fn totals(&self) -> Counts {
+        let mut totals = Counts::default();
+        for counts in self.buckets.values() {
+            totals.merge(counts);
+        }
+        totals
+    }
+}
+
+// ---
+
+/// HistogramFormatter writes a Histogram as a bar chart with bars stacked by level.
+pub struct HistogramFormatter {
+    theme: Arc<Theme>,
+    ts_formatter: DateTimeFormatter,
+    width: usize,
+    ascii: AsciiMode,
+}
+
+impl HistogramFormatter {
+    /// Returns a new HistogramFormatter fitting the chart into the given number of columns when possible.
+    pub fn new(theme: Arc<Theme>, ts_formatter: DateTimeFormatter, width: usize, ascii: AsciiMode) -> Self {
+        Self {
+            theme,
+            ts_formatter,
+            width,
+            ascii,
+        }
+    }
+
+    pub fn format<W: Write>(&self, out: &mut W, histogram: &Histogram) -> Result<()> {
+        let max = histogram.buckets.values().map(|c| c.total()).max().unwrap_or(0);
+        let ts_width = self.ts_formatter.max_length();
+        let count_width = max.to_string().len();
+        let bar_width = self.width.saturating_sub(ts_width + count_width + 4).max(MIN_BAR_WIDTH);
+
+        let mut buf = Vec::new();
+
+        let totals = histogram.totals();
+        if totals.total() != 0 {
+            buf.extend_from_slice(" ".repeat(ts_width + count_width + 4).as_bytes());
+            for (i, (level, _)) in totals.segments().filter(|(_, n)| *n != 0).enumerate() {
+                if i != 0 {
+                    buf.extend_from_slice(b"  ");
+                }
+                self.bar(&mut buf, level, 1);
+                buf.push(b' ');
+                let name = match level {
+                    Some(level) => level.as_ref(),
+                    None => "(none)",
+                };
+                buf.extend_from_slice(name.as_bytes());
+            }
+            buf.push(b'\n');
+            out.write_all(&buf)?;
+        }
+
+        for (start, counts) in histogram.buckets() {
+            buf.clear();
+
+            if let Some(start) = DateTime::from_timestamp_millis(start) {
+                self.ts_formatter.format(&mut buf, start.fixed_offset());
+            }
+            let ts = String::from_utf8_lossy(&buf).chars().count();
+            buf.extend_from_slice(" ".repeat(ts_width.saturating_sub(ts) + 2).as_bytes());
+
+            let total = counts.total();
+            buf.extend_from_slice(format!("{total:>count_width$}  ").as_bytes());
+
+            let mut sum = 0;
+            let mut end = 0;
+            for (level, n) in counts.segments() {
+                sum += n;
+                let next = Self::scale(sum, max, bar_width);
+                self.bar(&mut buf, level, next - end);
+                end = next;
+            }
+            while buf.last() == Some(&b' ') {
+                buf.pop();
+            }
+            buf.push(b'\n');
+            out.write_all(&buf)?;
+        }
+
+        if histogram.untimed() != 0 {
+            writeln!(out, "{} entries without timestamp are not shown", histogram.untimed())?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn scale(n: u64, max: u64, width: usize) -> usize {
+        if max == 0 {
+            0
+        } else {
+            (u128::from(n) * width as u128).div_ceil(u128::from(max)) as usize
+        }
+    }
+
+    fn bar(&self, buf: &mut Vec<u8>, level: Option<Level>, width: usize) {
+        if width == 0 {
+            return;
+        }
+        let symbol = match self.ascii {
+            AsciiMode::On => "#",
+            AsciiMode::Off => "█",
+        };
+        self.theme.apply(buf, &level, |s| {
+            s.element(Element::LevelInner, |s| {
+                s.batch(|buf| {
+                    for _ in 0..width {
+                        buf.extend_from_slice(symbol.as_bytes());
+                    }
+                })
+            })
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,102 @@
+use super::*;
+
+use chrono::{Offset, Utc};
+
+use crate::{datefmt::LinuxDateFormat, timestamp::Timestamp, timezone::Tz};
+
+fn record(ts: Option<&str>, level: Option<Level>) -> Record<'_> {
+    Record {
+        ts: ts.map(Timestamp::new),
+        level,
+        ..Default::default()
+    }
+}
+
+fn formatter(ascii: AsciiMode) -> HistogramFormatter {
+    HistogramFormatter::new(
+        Arc::new(Theme::none()),
+        DateTimeFormatter::new(LinuxDateFormat::new("%T").compile(), Tz::FixedOffset(Utc.fix())),
+        23,
+        ascii,
+    )
+}
+
+#[test]
+fn test_auto_bucket() {
+    assert_eq!(Histogram::auto_bucket(Duration::ZERO), Duration::from_millis(1));
+    assert_eq!(
+        Histogram::auto_bucket(Duration::from_secs(10)),
+        Duration::from_millis(500)
+    );
+    assert_eq!(
+        Histogram::auto_bucket(Duration::from_secs(3600)),
+        Duration::from_secs(120)
+    );
+    assert_eq!(
+        Histogram::auto_bucket(Duration::from_secs(365 * 24 * 3600)),
+        Duration::from_secs(14 * 24 * 3600)
+    );
+}
+
+#[test]
+fn test_histogram() {
+    let mut h1 = Histogram::new(Duration::from_secs(2), 0);
+    h1.add(&record(Some("2024-01-25T18:00:01Z"), Some(Level::Info)));
+    h1.add(&record(Some("2024-01-25T18:00:02Z"), Some(Level::Error)));
+    h1.add(&record(None, Some(Level::Error)));
+
+    let mut h2 = Histogram::new(Duration::from_secs(2), 0);
+    h2.add(&record(Some("2024-01-25T18:00:03.500Z"), Some(Level::Info)));
+    h2.add(&record(Some("2024-01-25T18:00:07Z"), None));
+
+    h1.merge(h2);
+
+    assert_eq!(h1.untimed(), 1);
+    let totals = h1.buckets().map(|(_, counts)| counts.total()).collect::<Vec<_>>();
+    assert_eq!(totals, vec![1, 2, 0, 1]);
+
+    let mut buf = Vec::new();
+    formatter(AsciiMode::Off).format(&mut buf, &h1).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&buf).unwrap(),
+        concat!(
+            "             █ error  █ info  █ (none)\n",
+            "18:00:00  1  █████\n",
+            "18:00:02  2  ██████████\n",
+            "18:00:04  0\n",
+            "18:00:06  1  █████\n",
+            "1 entries without timestamp are not shown\n",
+        )
+    );
+
+    let mut buf = Vec::new();
+    formatter(AsciiMode::On).format(&mut buf, &h1).unwrap();
+    assert!(std::str::from_utf8(&buf).unwrap().contains("18:00:02  2  ##########\n"));
+}
+
+#[test]
+fn test_histogram_offset() {
+    let mut h = Histogram::new(Duration::from_secs(24 * 3600), 3 * 3600);
+    h.add(&record(Some("2024-01-25T20:00:00Z"), Some(Level::Info)));
+    h.add(&record(Some("2024-01-25T22:00:00Z"), Some(Level::Info)));
+
+    let buckets = h.buckets().collect::<Vec<_>>();
+    assert_eq!(buckets.len(), 2);
+    assert_eq!(
+        DateTime::from_timestamp_millis(buckets[0].0).unwrap().to_rfc3339(),
+        "2024-01-24T21:00:00+00:00"
+    );
+    assert_eq!(
+        DateTime::from_timestamp_millis(buckets[1].0).unwrap().to_rfc3339(),
+        "2024-01-25T21:00:00+00:00"
+    );
+}
+
+#[test]
+fn test_histogram_empty() {
+    let mut buf = Vec::new();
+    formatter(AsciiMode::Off)
+        .format(&mut buf, &Histogram::new(Duration::from_secs(1), 0))
+        .unwrap();
+    assert_eq!(std::str::from_utf8(&buf).unwrap(), "");
+}
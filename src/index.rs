@@ -17,11 +17,11 @@ use std::{
     convert::{Into, TryFrom, TryInto},
     fmt::{self, Display},
     fs::{self},
-    io::{self, Read, Write},
+    io::{self, Read, Seek, SeekFrom, Write},
     iter::empty,
     num::{NonZero, NonZeroU32},
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, Mutex},
     time::{SystemTime, UNIX_EPOCH},
 };
 
@@ -44,15 +44,20 @@ use sha2::{Digest, Sha256};
 // local imports
 use crate::{
     app::{InputFormat, UnixTimestampUnit},
+    container::Wrapper,
     error::{Error, Result},
     index_capnp as schema,
     level::Level,
-    model::{Parser, ParserSettings, RawRecord},
+    model::{FieldMembership, IndexedFields, Parser, ParserSettings, RawRecord, RawRecordParser},
     scanning::{Delimit, Delimiter, Scanner, SearchExt, Segment, SegmentBuf, SegmentBufFactory},
-    settings::PredefinedFields,
-    vfs::{FileRead, FileSystem, LocalFileSystem},
+    settings::{PredefinedFields, StatusLevels},
+    textformat::TextFormat,
+    vfs::{FileRead, FileReadWrite, FileSystem, LocalFileSystem},
 };
 
+// sub-modules
+pub mod cache;
+
 // types
 pub type Writer = dyn Write + Send + Sync;
 pub type Reader = dyn Read + Send + Sync;
@@ -168,6 +173,10 @@ pub struct IndexerSettings<'a, FS: FileSystem> {
     pub allow_prefix: bool,
     pub unix_ts_unit: Option<UnixTimestampUnit>,
     pub format: Option<InputFormat>,
+    pub status_levels: StatusLevels,
+    pub text_formats: Arc<[TextFormat]>,
+    pub unwrap: Option<Wrapper>,
+    pub indexed_fields: IndexedFields,
 }
 
 impl<'a, FS: FileSystem + Default> Default for IndexerSettings<'a, FS> {
@@ -187,6 +196,10 @@ impl<'a, FS: FileSystem> IndexerSettings<'a, FS> {
             allow_prefix: false,
             unix_ts_unit: None,
             format: None,
+            status_levels: StatusLevels::default(),
+            text_formats: Arc::default(),
+            unwrap: None,
+            indexed_fields: IndexedFields::default(),
         }
     }
 
@@ -201,6 +214,13 @@ impl<'a, FS: FileSystem> IndexerSettings<'a, FS> {
             &self.allow_prefix,
             &self.unix_ts_unit,
             &self.format,
+            &self.status_levels,
+            self.text_formats
+                .iter()
+                .map(|format| format.definition())
+                .collect::<Vec<_>>(),
+            &self.unwrap,
+            self.indexed_fields.keys(),
         );
         let mut hasher = IoWrapper(Sha256::new());
         ciborium::into_writer(&data, &mut hasher)?;
@@ -287,6 +307,10 @@ pub struct Indexer<FS = LocalFileSystem> {
     delimiter: Delimiter,
     allow_prefix: bool,
     format: Option<InputFormat>,
+    status_levels: StatusLevels,
+    text_formats: Arc<[TextFormat]>,
+    wrapped: bool,
+    indexed_fields: IndexedFields,
 }
 
 impl<FS: FileSystem + Sync> Indexer<FS>
@@ -305,12 +329,17 @@ where
             delimiter: settings.delimiter,
             allow_prefix: settings.allow_prefix,
             format: settings.format,
+            status_levels: settings.status_levels,
+            text_formats: settings.text_formats,
+            wrapped: settings.unwrap.is_some(),
+            indexed_fields: settings.indexed_fields,
         }
     }
 
     /// Builds index for the given file.
     ///
     /// Builds the index, saves it to disk and returns it.
+    /// If the file has only grown since it was indexed, only the appended data is indexed.
     pub fn index(&self, source_path: &Path) -> Result<Index> {
         let (source_path, mut stream) = self.open_source(source_path)?;
         let meta = Metadata::from(&stream.metadata()?)?;
@@ -319,7 +348,17 @@ where
             return Ok(index.unwrap());
         }
 
-        self.build_index_from_stream(&mut stream, &source_path, &meta, &index_path, index)
+        let head = match &index {
+            Some(index) => Self::unchanged_head(&mut stream, index, &meta)?,
+            None => Vec::new(),
+        };
+        if let Some(last) = head.last() {
+            log::debug!("indexing data appended after {} unchanged blocks", head.len());
+            stream.seek(SeekFrom::Start(last.offset + u64::from(last.size)))?;
+        }
+
+        let mut output = self.create_index_file(&index_path)?;
+        self.process_file_after(head, None, &source_path, &meta, &mut stream, &mut output, index)
     }
 
     /// Builds index for the given file represended by a stream.
@@ -333,7 +372,28 @@ where
             return Ok(index.unwrap());
         }
 
-        self.build_index_from_stream(stream, source_path, meta, &index_path, index)
+        self.build_index_from_stream(stream, None, source_path, meta, &index_path, index)
+    }
+
+    /// Builds index for the given compressed file represented by a stream of its decoded data.
+    ///
+    /// Decoder checkpoints recorded while the stream is being read are stored in the blocks of the index,
+    /// so that a block can be decoded later without decoding all the data preceding it.
+    /// The source_path parameter must be the canonical path of the file.
+    pub fn index_decoded_stream(
+        &self,
+        stream: &mut Reader,
+        checkpoints: &Mutex<Vec<Checkpoint>>,
+        source_path: &Path,
+        meta: &fs::Metadata,
+    ) -> Result<Index> {
+        let meta = &meta.try_into()?;
+        let (index_path, index, actual) = self.prepare(source_path, meta)?;
+        if actual {
+            return Ok(index.unwrap());
+        }
+
+        self.build_index_from_stream(stream, Some(checkpoints), source_path, meta, &index_path, index)
     }
 
     /// Builds an in-memory index for the given stream.
@@ -380,6 +440,8 @@ where
                 );
                 if meta.len == index.source().size && meta.modified == index.source().modified {
                     actual = true;
+                    // the index cache treats modification time of index files as their last use time
+                    self.fs.touch(&index_path).ok();
                 }
                 existing_index = Some(index);
             }
@@ -390,22 +452,65 @@ where
     fn build_index_from_stream(
         &self,
         stream: &mut Reader,
+        checkpoints: Option<&Mutex<Vec<Checkpoint>>>,
         source_path: &Path,
         meta: &Metadata,
         index_path: &Path,
         existing_index: Option<Index>,
     ) -> Result<Index> {
-        let mut output = match self.fs.create(index_path) {
-            Ok(output) => output,
-            Err(err) => {
-                return Err(Error::FailedToOpenFileForWriting {
-                    path: index_path.to_path_buf(),
-                    source: err,
-                });
+        let mut output = self.create_index_file(index_path)?;
+        self.process_file_after(
+            Vec::new(),
+            checkpoints,
+            source_path,
+            meta,
+            stream,
+            &mut output,
+            existing_index,
+        )
+    }
+
+    fn create_index_file(
+        &self,
+        index_path: &Path,
+    ) -> Result<Box<dyn FileReadWrite<Metadata = FS::Metadata> + Send + Sync>> {
+        self.fs
+            .create(index_path)
+            .map_err(|err| Error::FailedToOpenFileForWriting {
+                path: index_path.to_path_buf(),
+                source: err,
+            })
+    }
+
+    /// Returns the blocks of the existing index that are still valid if the source file has only grown since it was indexed.
+    ///
+    /// Each of the returned blocks is verified by its hash, which is much cheaper than parsing it again.
+    /// The last block of the existing index is never returned because it could have ended with an incomplete entry.
+    fn unchanged_head<S: Read + Seek + ?Sized>(
+        stream: &mut S,
+        index: &Index,
+        meta: &Metadata,
+    ) -> Result<Vec<SourceBlock>> {
+        let source = index.source();
+        if meta.len <= source.size || meta.modified < source.modified || source.blocks.len() < 2 {
+            return Ok(Vec::new());
+        }
+
+        let head = &source.blocks[..source.blocks.len() - 1];
+        let mut buf = Vec::new();
+        for block in head {
+            let Some(Hash::WyHash(expected)) = block.hash else {
+                return Ok(Vec::new());
+            };
+            buf.resize(usize::try_from(block.size)?, 0);
+            stream.seek(SeekFrom::Start(block.offset))?;
+            stream.read_exact(&mut buf)?;
+            if wyhash::wyhash(&buf, 0) != expected {
+                return Ok(Vec::new());
             }
-        };
+        }
 
-        self.process_file(source_path, meta, stream, &mut output, existing_index)
+        Ok(head.to_vec())
     }
 
     fn process_file(
@@ -416,6 +521,24 @@ where
         output: &mut Writer,
         existing_index: Option<Index>,
     ) -> Result<Index> {
+        self.process_file_after(Vec::new(), None, path, metadata, input, output, existing_index)
+    }
+
+    /// Indexes the input following the given head blocks, which are included in the index as is.
+    ///
+    /// If checkpoints are given, they are expected to be complete once the input is read to the end.
+    #[allow(clippy::too_many_arguments)]
+    fn process_file_after(
+        &self,
+        head: Vec<SourceBlock>,
+        checkpoints: Option<&Mutex<Vec<Checkpoint>>>,
+        path: &Path,
+        metadata: &Metadata,
+        input: &mut Reader,
+        output: &mut Writer,
+        existing_index: Option<Index>,
+    ) -> Result<Index> {
+        let skip = head.len();
         let n = self.concurrency;
         let sfi = Arc::new(SegmentBufFactory::new(self.buffer_size.try_into()?));
         thread::scope(|scope| -> Result<Index> {
@@ -423,7 +546,7 @@ where
             let (txi, rxi): (Vec<_>, Vec<_>) = (0..n).map(|_| channel::bounded(1)).unzip();
             // prepare receive/transmit channels for output data
             let (txo, rxo): (Vec<_>, Vec<_>) = (0..n)
-                .map(|_| channel::bounded::<(usize, Stat, Chronology, Option<Hash>)>(1))
+                .map(|_| channel::bounded::<(usize, Stat, Chronology, Option<Bloom>, Option<Hash>)>(1))
                 .unzip();
             // spawn reader thread
             let reader = scope.spawn(closure!(clone sfi, |_| -> Result<()> {
@@ -439,24 +562,24 @@ where
             for (rxi, txo) in izip!(rxi, txo) {
                 scope.spawn(closure!(ref sfi, ref existing_index, |_| {
                     for (sn, segment) in rxi.iter() {
-                        let (stat, chronology, segment, hash) = match segment {
+                        let (stat, chronology, bloom, segment, hash) = match segment {
                             Segment::Complete(segment) => {
                                 let hash = Hash::WyHash(wyhash::wyhash(segment.data(), 0));
-                                let (stat, chronology) = existing_index
+                                let (stat, chronology, bloom) = existing_index
                                     .as_ref()
-                                    .and_then(|index| Self::match_segment(index, sn, &hash))
+                                    .and_then(|index| Self::match_segment(index, skip + sn, &hash))
                                     .unwrap_or_else(|| self.process_segment(&segment));
-                                (stat, chronology, segment, Some(hash))
+                                (stat, chronology, bloom, segment, Some(hash))
                             }
                             Segment::Incomplete(segment, _) => {
                                 let mut stat = Stat::new();
                                 stat.add_invalid();
-                                (stat, Chronology::default(), segment, None)
+                                (stat, Chronology::default(), None, segment, None)
                             }
                         };
                         let size = segment.data().len();
                         sfi.recycle(segment);
-                        if txo.send((size, stat, chronology, hash)).is_err() {
+                        if txo.send((size, stat, chronology, bloom, hash)).is_err() {
                             break;
                         };
                     }
@@ -476,16 +599,17 @@ where
                 };
 
                 let mut offset: u64 = 0;
+                for block in head {
+                    index.source.stat.merge(&block.stat);
+                    offset = block.offset + u64::from(block.size);
+                    index.source.blocks.push(block);
+                }
                 let mut sn = 0;
-                while let Ok((size, stat, chronology, hash)) = rxo[sn % n].recv() {
+                while let Ok((size, stat, chronology, bloom, hash)) = rxo[sn % n].recv() {
                     index.source.stat.merge(&stat);
-                    index.source.blocks.push(SourceBlock::new(
-                        offset,
-                        size.try_into()?,
-                        stat,
-                        chronology,
-                        hash,
-                    ));
+                    index.source.blocks.push(
+                        SourceBlock::new(offset, size.try_into()?, stat, chronology, hash).with_bloom(bloom),
+                    );
                     offset += u64::try_from(size)?;
                     sn += 1;
                 }
@@ -493,15 +617,19 @@ where
             });
             // collect errors from reader and builder threads
             reader.join().unwrap()?;
-            let index = builder.join().unwrap()?;
+            let mut index = builder.join().unwrap()?;
+            if let Some(checkpoints) = checkpoints {
+                index.source.set_checkpoints(&checkpoints.lock().unwrap());
+            }
             index.save(output)?;
             Ok(index)
         })
         .unwrap()
     }
 
-    fn process_segment(&self, segment: &SegmentBuf) -> (Stat, Chronology) {
+    fn process_segment(&self, segment: &SegmentBuf) -> (Stat, Chronology, Option<Bloom>) {
         let mut stat = Stat::new();
+        let mut hashes = Vec::new();
         let mut sorted = true;
         let mut prev_ts = None;
         let mut entries = Vec::<(Option<Timestamp>, u32, u32)>::with_capacity(segment.data().len() / 512);
@@ -516,14 +644,13 @@ where
             let mut ts = None;
             let mut rel = 0;
             if !data.is_empty() {
-                let mut stream = RawRecord::parser()
-                    .allow_prefix(self.allow_prefix)
-                    .format(self.format)
-                    .parse(data);
+                let mut stream = self.raw_parser().parse(data);
                 while let Some(item) = stream.next() {
                     match item {
                         Ok(ar) => {
                             let rec = self.parser.parse(&ar.record);
+                            self.indexed_fields
+                                .for_each(&rec, |key, value| hashes.push(Bloom::hash(key, value)));
                             let mut flags = 0;
                             if let Some(level) = rec.level {
                                 flags |= level_to_flag(level);
@@ -584,14 +711,25 @@ where
             }
             Chronology { bitmap, offsets, jumps }
         };
-        (stat, chronology)
+        let bloom = (!self.indexed_fields.is_empty()).then(|| Bloom::new(hashes));
+        (stat, chronology, bloom)
     }
 
-    fn match_segment(index: &Index, sn: usize, hash: &Hash) -> Option<(Stat, Chronology)> {
+    /// Returns a parser of raw records configured by the indexer settings.
+    fn raw_parser(&self) -> RawRecordParser<'_> {
+        RawRecord::parser()
+            .allow_prefix(self.allow_prefix)
+            .format(self.format)
+            .status_levels(self.status_levels)
+            .text_formats(&self.text_formats)
+            .wrapped(self.wrapped)
+    }
+
+    fn match_segment(index: &Index, sn: usize, hash: &Hash) -> Option<(Stat, Chronology, Option<Bloom>)> {
         index.source().blocks.get(sn).and_then(|block| {
             block.hash.as_ref().and_then(|h| {
                 if h == hash {
-                    Some((block.stat.clone(), block.chronology.clone()))
+                    Some((block.stat.clone(), block.chronology.clone(), block.bloom.clone()))
                 } else {
                     None
                 }
@@ -737,14 +875,28 @@ impl Index {
 
     fn load_blocks(source: schema::source_file::Reader) -> Result<Vec<SourceBlock>> {
         let blocks = source.get_blocks()?;
-        let mut result = Vec::with_capacity(blocks.len().try_into()?);
+        let mut result: Vec<SourceBlock> = Vec::with_capacity(blocks.len().try_into()?);
         for block in blocks.iter() {
+            let mut checkpoint = Checkpoint {
+                compressed: block.get_checkpoint_compressed(),
+                decoded: block.get_checkpoint_decoded(),
+                bits: block.get_checkpoint_bits(),
+                window: block.get_checkpoint_window()?.into(),
+            };
+            // the window is stored only in the first of the blocks sharing the checkpoint
+            if let Some(prev) = result.last().map(|prev| &prev.checkpoint) {
+                if checkpoint.window.is_empty() && prev.same_position(&checkpoint) {
+                    checkpoint.window = prev.window.clone();
+                }
+            }
             result.push(SourceBlock {
                 offset: block.get_offset(),
                 size: block.get_size(),
                 stat: Self::load_stat(block.get_index()?),
                 chronology: Self::load_chronology(block.get_chronology()?)?,
                 hash: Self::load_hash(block.get_hash()?)?,
+                bloom: Self::load_bloom(block)?,
+                checkpoint,
             })
         }
         Ok(result)
@@ -756,8 +908,18 @@ impl Index {
             let mut block = blocks.reborrow().get(i.try_into()?);
             block.set_offset(source_block.offset);
             block.set_size(source_block.size);
+            block.set_checkpoint_compressed(source_block.checkpoint.compressed);
+            block.set_checkpoint_decoded(source_block.checkpoint.decoded);
+            block.set_checkpoint_bits(source_block.checkpoint.bits);
+            let shared = i
+                .checked_sub(1)
+                .is_some_and(|i| self.source.blocks[i].checkpoint.same_position(&source_block.checkpoint));
+            if !shared && !source_block.checkpoint.window.is_empty() {
+                block.set_checkpoint_window(&source_block.checkpoint.window);
+            }
             Self::save_stat(block.reborrow().init_index(), &source_block.stat);
             Self::save_chronology(block.reborrow().init_chronology(), &source_block.chronology)?;
+            Self::save_bloom(block.reborrow(), &source_block.bloom);
             Self::save_hash(block.init_hash(), &source_block.hash)?;
         }
         Ok(())
@@ -825,6 +987,34 @@ impl Index {
         Ok(())
     }
 
+    fn load_bloom(block: schema::source_block::Reader) -> Result<Option<Bloom>> {
+        if !block.has_bloom() {
+            return Ok(None);
+        }
+        let bytes = block.get_bloom()?;
+        if bytes.is_empty() || bytes.len() % 8 != 0 {
+            return Err(Error::InconsistentIndex {
+                details: "bloom filter size is not a positive multiple of 8".into(),
+            });
+        }
+        let bits = bytes
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        Ok(Some(Bloom { bits }))
+    }
+
+    fn save_bloom(mut to: schema::source_block::Builder, from: &Option<Bloom>) {
+        if let Some(bloom) = from {
+            let bytes = bloom
+                .bits
+                .iter()
+                .flat_map(|word| word.to_le_bytes())
+                .collect::<Vec<_>>();
+            to.set_bloom(&bytes);
+        }
+    }
+
     fn load_hash(hash: schema::hash::Reader) -> Result<Option<Hash>> {
         match hash.get_algorithm().ok() {
             Some(schema::HashAlgorithm::Sha256) => {
@@ -877,6 +1067,29 @@ pub struct SourceFile {
     pub blocks: Vec<SourceBlock>,
 }
 
+impl SourceFile {
+    /// Returns distinct checkpoints of the blocks, ordered by offset.
+    pub fn checkpoints(&self) -> Vec<Checkpoint> {
+        let mut result: Vec<Checkpoint> = Vec::new();
+        for block in &self.blocks {
+            if !result.last().is_some_and(|last| last.same_position(&block.checkpoint)) {
+                result.push(block.checkpoint.clone());
+            }
+        }
+        result
+    }
+
+    /// Assigns each block the last of the given checkpoints that is not beyond the block start.
+    ///
+    /// The checkpoints must be ordered by offset.
+    fn set_checkpoints(&mut self, checkpoints: &[Checkpoint]) {
+        for block in &mut self.blocks {
+            let n = checkpoints.partition_point(|checkpoint| checkpoint.decoded <= block.offset);
+            block.checkpoint = n.checked_sub(1).map(|i| checkpoints[i].clone()).unwrap_or_default();
+        }
+    }
+}
+
 // ---
 
 /// SourceBlock contains index data of a block in a scanned source log file.
@@ -887,6 +1100,8 @@ pub struct SourceBlock {
     pub stat: Stat,
     pub chronology: Chronology,
     pub hash: Option<Hash>,
+    pub bloom: Option<Bloom>,
+    pub checkpoint: Checkpoint,
 }
 
 impl SourceBlock {
@@ -899,6 +1114,23 @@ impl SourceBlock {
             stat,
             chronology,
             hash,
+            bloom: None,
+            checkpoint: Checkpoint::default(),
+        }
+    }
+
+    /// Returns the SourceBlock with the given bloom filter over values of indexed fields.
+    #[inline]
+    pub fn with_bloom(self, bloom: Option<Bloom>) -> Self {
+        Self { bloom, ..self }
+    }
+
+    /// Returns field membership of the SourceBlock, judging by its bloom filter built for the given indexed fields.
+    #[inline]
+    pub fn fields<'a>(&'a self, indexed: &'a IndexedFields) -> BlockFields<'a> {
+        BlockFields {
+            indexed,
+            bloom: self.bloom.as_ref(),
         }
     }
 
@@ -921,6 +1153,104 @@ impl SourceBlock {
 
 // ---
 
+/// Checkpoint is a position in a compressed source file where decoding can be started from.
+///
+/// It is either the start of a gzip member, a zstd frame or an xz stream, so no decoder state is needed to start there,
+/// or a boundary between deflate blocks inside a gzip member, where decoding is resumed with the bits of the block
+/// in the preceding byte and the window of decoded data the following blocks may refer to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Offset in the compressed data.
+    pub compressed: u64,
+    /// Offset in the decoded data.
+    pub decoded: u64,
+    /// Number of high bits of the byte preceding `compressed` that belong to the deflate block starting there.
+    pub bits: u8,
+    /// Decoded data preceding the checkpoint, up to 32 KiB, empty at the start of a member.
+    pub window: Arc<[u8]>,
+}
+
+impl Checkpoint {
+    /// Returns true if the other checkpoint is at the same position.
+    pub fn same_position(&self, other: &Checkpoint) -> bool {
+        self.compressed == other.compressed && self.decoded == other.decoded
+    }
+}
+
+// ---
+
+/// BlockFields tells which values of indexed fields a SourceBlock may have.
+pub struct BlockFields<'a> {
+    indexed: &'a IndexedFields,
+    bloom: Option<&'a Bloom>,
+}
+
+impl FieldMembership for BlockFields<'_> {
+    #[inline]
+    fn may_contain(&self, key: &str, value: &str) -> bool {
+        match self.bloom {
+            Some(bloom) if self.indexed.contains(key) => bloom.contains(Bloom::hash(key, value)),
+            _ => true,
+        }
+    }
+}
+
+// ---
+
+/// Bloom is a bloom filter over hashes of values of indexed fields in a SourceBlock.
+///
+/// It may report a value that is not present in the block, but never misses a value that is present.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Bloom {
+    bits: Vec<u64>,
+}
+
+impl Bloom {
+    const BITS_PER_VALUE: usize = 10;
+    const PROBES: u64 = 7;
+
+    /// Returns a new Bloom containing the given hashes, sized for about 1% of false positives.
+    pub fn new(mut hashes: Vec<u64>) -> Self {
+        hashes.sort_unstable();
+        hashes.dedup();
+        let mut bits = vec![0; (hashes.len() * Self::BITS_PER_VALUE).div_ceil(64).max(1)];
+        for hash in hashes {
+            for bit in Self::probes(bits.len(), hash) {
+                bits[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        Self { bits }
+    }
+
+    /// Returns a hash of the field value to be looked up in a Bloom.
+    #[inline]
+    pub fn hash(key: &str, value: &str) -> u64 {
+        wyhash::wyhash(value.as_bytes(), wyhash::wyhash(key.as_bytes(), 0))
+    }
+
+    /// Returns false if the hash is definitely not contained.
+    #[inline]
+    pub fn contains(&self, hash: u64) -> bool {
+        Self::probes(self.bits.len(), hash).all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Returns positions of the bits representing the hash in a bit set of the given number of words.
+    #[inline]
+    fn probes(words: usize, hash: u64) -> impl Iterator<Item = usize> {
+        let n = words as u64 * 64;
+        let (h1, h2) = (hash & 0xffff_ffff, hash >> 32);
+        (0..Self::PROBES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % n) as usize)
+    }
+}
+
+impl fmt::Debug for Bloom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bloom").field("bits", &(self.bits.len() * 64)).finish()
+    }
+}
+
+// ---
+
 /// Stat contains statistical information over a file or over a block.
 #[derive(Debug, Clone)]
 pub struct Stat {
@@ -1188,7 +1518,7 @@ fn level_mask_higher_or_eq(flag: u64) -> u64 {
 }
 
 const VALID_MAGIC: u64 = 0x484c2d494e444558;
-const CURRENT_VERSION: u64 = 4;
+const CURRENT_VERSION: u64 = 6;
 
 /*
 ---
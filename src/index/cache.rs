@@ -0,0 +1,183 @@
+// std imports
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+// local imports
+use super::Index;
+
+// ---
+
+/// Limits of the index cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum total size of index files, the least recently used ones are removed to fit it.
+    pub max_size: Option<u64>,
+    /// Maximum time since an index file was last used, older ones are removed.
+    pub max_age: Option<Duration>,
+}
+
+// ---
+
+/// Cache is a directory with index files grouped into sub-directories by hash of the indexer settings.
+///
+/// Index files and sub-directories are named by hashes, anything else in the directory is left intact.
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Returns all index files in the cache, the most recently used first.
+    pub fn entries(&self) -> io::Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+        for dir in hashed(&self.dir)? {
+            for path in hashed(&dir)? {
+                let meta = match fs::metadata(&path) {
+                    Ok(meta) => meta,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(e),
+                };
+                if !meta.is_file() {
+                    continue;
+                }
+                // index files are touched on each use as access time is not updated on some file systems
+                let used = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                entries.push(Entry {
+                    path,
+                    size: meta.len(),
+                    used,
+                });
+            }
+        }
+        entries.sort_by(|a, b| b.used.cmp(&a.used));
+        Ok(entries)
+    }
+
+    /// Removes the least recently used index files exceeding the limits.
+    pub fn shrink(&self, limits: &Limits, now: SystemTime) -> io::Result<Removed> {
+        let mut removed = Removed::default();
+        let mut total = 0;
+        for entry in self.entries()? {
+            let expired = limits
+                .max_age
+                .is_some_and(|age| now.duration_since(entry.used).is_ok_and(|d| d > age));
+            let exceeds = limits.max_size.is_some_and(|size| total + entry.size > size);
+            if expired || exceeds {
+                removed.add(entry.remove()?);
+            } else {
+                total += entry.size;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Removes index files which cannot be loaded or whose source files no longer exist.
+    pub fn prune(&self) -> io::Result<Removed> {
+        let mut removed = Removed::default();
+        for entry in self.entries()? {
+            if !entry.source().is_some_and(|source| source.exists()) {
+                removed.add(entry.remove()?);
+            }
+        }
+        self.remove_empty_dirs()?;
+        Ok(removed)
+    }
+
+    /// Removes all index files.
+    pub fn clear(&self) -> io::Result<Removed> {
+        let mut removed = Removed::default();
+        for entry in self.entries()? {
+            removed.add(entry.remove()?);
+        }
+        self.remove_empty_dirs()?;
+        Ok(removed)
+    }
+
+    fn remove_empty_dirs(&self) -> io::Result<()> {
+        for dir in hashed(&self.dir)? {
+            if fs::read_dir(&dir)?.next().is_none() {
+                fs::remove_dir(&dir).ok();
+            }
+        }
+        Ok(())
+    }
+}
+
+// ---
+
+/// Entry is an index file in the cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Time of the last use of the index file.
+    pub used: SystemTime,
+}
+
+impl Entry {
+    /// Returns path of the indexed source file, or None if the index file cannot be loaded.
+    pub fn source(&self) -> Option<PathBuf> {
+        let mut file = fs::File::open(&self.path).ok()?;
+        let index = Index::load(&mut file).ok()?;
+        Some(PathBuf::from(&index.source().path))
+    }
+
+    fn remove(self) -> io::Result<Removed> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(Removed {
+                count: 1,
+                size: self.size,
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Removed::default()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+// ---
+
+/// Removed is a summary of the removed index files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Removed {
+    pub count: usize,
+    pub size: u64,
+}
+
+impl Removed {
+    fn add(&mut self, other: Self) {
+        self.count += other.count;
+        self.size += other.size;
+    }
+}
+
+// ---
+
+/// Returns paths of the items in the directory named by hashes, or nothing if the directory does not exist.
+fn hashed(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let items = match fs::read_dir(dir) {
+        Ok(items) => items,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut result = Vec::new();
+    for item in items {
+        let item = item?;
+        let name = item.file_name();
+        let name = name.to_string_lossy();
+        if name.len() == 64 && name.bytes().all(|b| b.is_ascii_hexdigit()) {
+            result.push(item.path());
+        }
+    }
+    result.sort();
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests;
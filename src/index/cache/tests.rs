@@ -0,0 +1,77 @@
+use super::*;
+use crate::testing::TempDir;
+
+use std::fs::FileTimes;
+
+const DAY: Duration = Duration::from_secs(86400);
+
+fn add(dir: &TempDir, group: char, name: char, size: usize, used: SystemTime) -> PathBuf {
+    let dir = dir.path().join(group.to_string().repeat(64));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name.to_string().repeat(64));
+    fs::write(&path, vec![0; size]).unwrap();
+    let file = fs::File::options().write(true).open(&path).unwrap();
+    file.set_times(FileTimes::new().set_accessed(used).set_modified(used))
+        .unwrap();
+    path
+}
+
+#[test]
+fn test_shrink() {
+    let dir = TempDir::new("cache-shrink");
+    let now = SystemTime::now();
+    let a = add(&dir, 'a', '1', 100, now - DAY);
+    let b = add(&dir, 'a', '2', 100, now - 3 * DAY);
+    let c = add(&dir, 'b', '3', 100, now - 2 * DAY);
+    let d = add(&dir, 'b', '4', 100, now - 10 * DAY);
+    fs::write(dir.path().join("other"), "x").unwrap();
+
+    let cache = Cache::new(dir.path().to_owned());
+    assert_eq!(
+        cache.entries().unwrap().iter().map(|e| &e.path).collect::<Vec<_>>(),
+        vec![&a, &c, &b, &d]
+    );
+
+    let limits = Limits {
+        max_size: None,
+        max_age: Some(7 * DAY),
+    };
+    assert_eq!(cache.shrink(&limits, now).unwrap(), Removed { count: 1, size: 100 });
+    assert!(!d.exists());
+
+    let limits = Limits {
+        max_size: Some(250),
+        max_age: None,
+    };
+    assert_eq!(cache.shrink(&limits, now).unwrap(), Removed { count: 1, size: 100 });
+    assert!(a.exists() && c.exists() && !b.exists());
+
+    assert_eq!(cache.shrink(&Limits::default(), now).unwrap(), Removed::default());
+    assert!(dir.path().join("other").exists());
+}
+
+#[test]
+fn test_prune_and_clear() {
+    let dir = TempDir::new("cache-prune");
+    let now = SystemTime::now();
+    let a = add(&dir, 'a', '1', 10, now);
+    let b = add(&dir, 'b', '2', 20, now);
+    fs::write(dir.path().join("other"), "x").unwrap();
+
+    let cache = Cache::new(dir.path().to_owned());
+    assert_eq!(cache.entries().unwrap()[0].source(), None);
+
+    // files that are not valid indexes are pruned along with indexes of removed source files
+    assert_eq!(cache.prune().unwrap(), Removed { count: 2, size: 30 });
+    assert!(!a.exists() && !b.exists());
+    assert!(!a.parent().unwrap().exists());
+
+    let a = add(&dir, 'a', '1', 10, now);
+    assert_eq!(cache.clear().unwrap(), Removed { count: 1, size: 10 });
+    assert!(!a.exists());
+    assert!(dir.path().join("other").exists());
+    assert_eq!(cache.entries().unwrap(), Vec::new());
+
+    let missing = Cache::new(dir.path().join("missing"));
+    assert_eq!(missing.clear().unwrap(), Removed::default());
+}
@@ -1,6 +1,7 @@
 use schema::{FLAG_LEVEL_ERROR, FLAG_LEVEL_INFO, FLAG_LEVEL_TRACE};
 
 use super::*;
+use crate::testing::TempDir;
 
 use std::{path::Component, time::Duration};
 
@@ -675,3 +676,221 @@ fn test_indexer_delimiter_in_settings() {
     assert_eq!(settings_json.delimiter, Delimiter::Json);
     assert_eq!(settings_auto.delimiter, Delimiter::PrettyCompatible);
 }
+
+#[test]
+fn test_indexer_with_indexed_fields() {
+    let fs = vfs::mem::FileSystem::new();
+
+    let data = concat!(
+        "ts=2024-01-02T03:04:05Z request-id=r1 msg=m1\n",
+        "ts=2024-01-02T03:04:06Z request_id=\"r2\" msg=m2\n",
+        "ts=2024-01-02T03:04:07Z trace=t3 msg=m3\n",
+    );
+    let mut file = fs.create(&PathBuf::from("test.log")).unwrap();
+    file.write_all(data.as_bytes()).unwrap();
+
+    let fields = IndexedFields::new(["request-id"]);
+    let indexer = Indexer::new(
+        1,
+        PathBuf::from("/tmp/cache"),
+        IndexerSettings {
+            indexed_fields: fields.clone(),
+            ..IndexerSettings::with_fs(fs)
+        },
+    );
+
+    let index1 = indexer.index(&PathBuf::from("test.log")).unwrap();
+    let index2 = indexer.index(&PathBuf::from("test.log")).unwrap();
+
+    for index in [index1, index2] {
+        assert_eq!(index.source.blocks.len(), 1);
+        let block = &index.source.blocks[0];
+        assert!(block.bloom.is_some());
+        let fields = block.fields(&fields);
+        assert!(fields.may_contain("request-id", "r1"));
+        assert!(fields.may_contain("request-id", "r2"));
+        assert!(!fields.may_contain("request-id", "r3"));
+        assert!(fields.may_contain("trace", "t4"));
+    }
+}
+
+#[test]
+fn test_bloom() {
+    let bloom = Bloom::new((0..100).map(|i| Bloom::hash("key", &i.to_string())).collect());
+    assert_eq!(format!("{:?}", bloom), "Bloom { bits: 1024 }");
+    assert!((0..100).all(|i| bloom.contains(Bloom::hash("key", &i.to_string()))));
+
+    let false_positives = (100..1100)
+        .filter(|i| bloom.contains(Bloom::hash("key", &i.to_string())))
+        .count();
+    assert!(false_positives < 50, "false_positives={}", false_positives);
+
+    let empty = Bloom::new(Vec::new());
+    assert!(!empty.contains(Bloom::hash("key", "value")));
+}
+
+#[test]
+fn test_indexer_appended_data() {
+    let lines = |range: std::ops::Range<usize>| {
+        range
+            .map(|i| format!("ts=2024-01-02T03:04:{:02}Z msg=\"message {}\"\n", i % 60, i))
+            .collect::<String>()
+    };
+
+    let tmp = TempDir::new("index-append");
+    let dir = tmp.path();
+    fs::create_dir_all(dir.join("cache")).unwrap();
+    let path = dir.join("test.log");
+    fs::write(&path, lines(0..100)).unwrap();
+
+    let indexer = Indexer::new(
+        2,
+        dir.join("cache"),
+        IndexerSettings {
+            buffer_size: nonzero!(1024u32).into(),
+            max_message_size: nonzero!(1024u32).into(),
+            ..IndexerSettings::with_fs(LocalFileSystem)
+        },
+    );
+
+    let index1 = indexer.index(&path).unwrap();
+    assert_eq!(index1.source.stat.entries_valid, 100);
+    assert!(index1.source.blocks.len() > 2);
+
+    let data = lines(0..150);
+    fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(lines(100..150).as_bytes())
+        .unwrap();
+
+    let index2 = indexer.index(&path).unwrap();
+
+    assert_eq!(index2.source.size, data.len() as u64);
+    assert_eq!(index2.source.stat.entries_valid, 150);
+    assert_eq!(index2.source.stat.entries_invalid, 0);
+    let mut offset = 0;
+    for block in &index2.source.blocks {
+        assert_eq!(block.offset, offset);
+        offset += u64::from(block.size);
+    }
+    assert_eq!(offset, data.len() as u64);
+    let n = index1.source.blocks.len() - 1;
+    for (a, b) in index1.source.blocks[..n].iter().zip(&index2.source.blocks) {
+        assert_eq!((a.offset, a.size, a.hash), (b.offset, b.size, b.hash));
+    }
+}
+
+#[test]
+fn test_unchanged_head() {
+    use io::Cursor;
+
+    let data = (0..100)
+        .map(|i| format!("ts=2024-01-02T03:04:{:02}Z msg=m{}\n", i % 60, i))
+        .collect::<String>();
+    let indexer = Indexer::new(
+        1,
+        PathBuf::from("/tmp/cache"),
+        IndexerSettings {
+            buffer_size: nonzero!(1024u32).into(),
+            max_message_size: nonzero!(1024u32).into(),
+            ..IndexerSettings::with_fs(MockFileSystem::<MockSourceMetadata>::new())
+        },
+    );
+    let meta = |len: usize, modified: i64| Metadata {
+        len: len as u64,
+        modified: (modified, 0),
+    };
+    let index = indexer
+        .process_file(
+            &PathBuf::from("/tmp/test.log"),
+            &meta(data.len(), 1714739340),
+            &mut Cursor::new(data.clone()),
+            &mut Cursor::new(Vec::new()),
+            None,
+        )
+        .unwrap();
+    let blocks = index.source.blocks.len();
+    assert!(blocks > 2);
+
+    let grown = data.clone() + "ts=2024-01-02T03:05:00Z msg=appended\n";
+    let head = Indexer::<MockFileSystem<MockSourceMetadata>>::unchanged_head;
+
+    let result = head(&mut Cursor::new(&grown), &index, &meta(grown.len(), 1714739341)).unwrap();
+    assert_eq!(result.len(), blocks - 1);
+
+    let changed = grown.replacen("m0", "x0", 1);
+    let result = head(&mut Cursor::new(&changed), &index, &meta(changed.len(), 1714739341)).unwrap();
+    assert!(result.is_empty());
+
+    // a block in the middle of the head is verified as well
+    assert!(blocks > 3);
+    let changed = grown.replacen("msg=m40\n", "msg=x40\n", 1);
+    let result = head(&mut Cursor::new(&changed), &index, &meta(changed.len(), 1714739341)).unwrap();
+    assert!(result.is_empty());
+
+    let result = head(&mut Cursor::new(&data), &index, &meta(data.len(), 1714739341)).unwrap();
+    assert!(result.is_empty());
+
+    let result = head(&mut Cursor::new(&grown), &index, &meta(grown.len(), 1714739339)).unwrap();
+    assert!(result.is_empty());
+}
+
+#[test]
+fn test_checkpoints() {
+    use io::Cursor;
+
+    let data = (0..100)
+        .map(|i| format!("ts=2024-01-02T03:04:{:02}Z msg=m{}\n", i % 60, i))
+        .collect::<String>();
+    let indexer = Indexer::new(
+        1,
+        PathBuf::from("/tmp/cache"),
+        IndexerSettings {
+            buffer_size: nonzero!(1024u32).into(),
+            max_message_size: nonzero!(1024u32).into(),
+            ..IndexerSettings::with_fs(MockFileSystem::<MockSourceMetadata>::new())
+        },
+    );
+    let checkpoint = |compressed, decoded| Checkpoint {
+        compressed,
+        decoded,
+        ..Default::default()
+    };
+    // a checkpoint inside a gzip member, its window is stored once but restored for all blocks sharing it
+    let inner = Checkpoint {
+        bits: 3,
+        window: b"ts=2024"[..].into(),
+        ..checkpoint(600, 2)
+    };
+    let checkpoints = Mutex::new(vec![
+        checkpoint(0, 0),
+        checkpoint(500, 1),
+        inner.clone(),
+        checkpoint(700, 5000),
+    ]);
+    let mut output = Cursor::new(Vec::new());
+    indexer
+        .process_file_after(
+            Vec::new(),
+            Some(&checkpoints),
+            &PathBuf::from("/tmp/test.log.gz"),
+            &Metadata {
+                len: 800,
+                modified: (1714739340, 0),
+            },
+            &mut Cursor::new(data),
+            &mut output,
+            None,
+        )
+        .unwrap();
+
+    output.set_position(0);
+    let index = Index::load(&mut output).unwrap();
+    let blocks = &index.source().blocks;
+    assert!(blocks.len() > 2);
+    assert_eq!(blocks[0].checkpoint, checkpoint(0, 0));
+    assert!(blocks[1..].iter().all(|block| block.checkpoint == inner));
+    assert_eq!(index.source().checkpoints(), vec![checkpoint(0, 0), inner]);
+}
@@ -12,13 +12,18 @@ use std::{
 
 // third-party imports
 use deko::{Format, bufread::AnyDecoder};
+use wildcard::Pattern;
 
 // local imports
 use crate::{
-    Delimit,
+    Delimit, archive,
+    command::Command,
+    container::{Unwrapper, Wrapper},
+    decoder::{Compression, Decoder, SeekableDecoder},
     error::Result,
-    index::{Index, Indexer, SourceBlock, SourceMetadata},
+    index::{Checkpoint, Index, Indexer, SourceBlock, SourceMetadata},
     iox::ReadFill,
+    listen::Peer,
     replay::{ReplayBufCreator, ReplayBufReader, ReplaySeekReader},
     scanning::{Delimiter, Search},
     tee::TeeReader,
@@ -82,19 +87,45 @@ impl TryFrom<PathBuf> for InputPath {
 
 // ---
 
-/// A reference to an input file or stdin.
+/// A reference to an input file, a member of an archive file, stdin, output of a command or a peer sending entries to a socket.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum InputReference {
     Stdin,
     File(InputPath),
+    Member(InputPath, archive::Member),
+    Process(Command),
+    Socket(Peer),
 }
 
 impl InputReference {
+    /// Returns references to the inputs specified by the path.
+    ///
+    /// An archive file is expanded to all its members, and `archive:pattern`
+    /// selects the members with names matching the wildcard pattern.
+    pub fn expand(path: PathBuf) -> io::Result<Vec<Self>> {
+        if path.is_file() {
+            // files that cannot be read are reported later when they are opened
+            if let Some(format) = archive::Format::detect(&path).ok().flatten() {
+                return Self::members(path, format, None);
+            }
+        } else if let Some((archive, pattern)) = Self::split_member_pattern(&path) {
+            let format = archive::Format::detect(&archive)?.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is not a tar or zip archive", archive.hlq()),
+                )
+            })?;
+            return Self::members(archive, format, Some(Pattern::new(pattern)));
+        }
+
+        Ok(vec![Self::File(path.try_into()?)])
+    }
+
     /// Preliminarily opens the input file to ensure it exists and is readable
     /// and protect it from being suddenly deleted while we need it.
     pub fn hold(&self) -> io::Result<InputHolder> {
         let (reference, stream): (_, Option<Box<dyn ReadSeekMeta + Send + Sync>>) = match self {
-            Self::Stdin => (self.clone(), None),
+            Self::Stdin | Self::Process(_) | Self::Socket(_) => (self.clone(), None),
             Self::File(path) => {
                 let meta = fs::metadata(&path.canonical).map_err(|e| {
                     io::Error::new(
@@ -115,6 +146,13 @@ impl InputReference {
 
                 (InputReference::File(path.clone()), Some(stream))
             }
+            Self::Member(path, member) => {
+                let stream = member
+                    .open(&path.canonical)
+                    .map_err(|e| io::Error::new(e.kind(), format!("failed to open {}: {}", self.description(), e)))?;
+
+                (self.clone(), Some(stream))
+            }
         };
 
         Ok(InputHolder::new(reference, stream))
@@ -131,16 +169,111 @@ impl InputReference {
         match self {
             Self::Stdin => "<stdin>".into(),
             Self::File(path) => format!("file {}", path.original.hlq()),
+            Self::Member(path, member) => format!("{} in archive {}", member.name.hlq(), path.original.hlq()),
+            Self::Process(command) => format!("output of command {}", command.name().hlq()),
+            Self::Socket(peer) => format!("input from {}", peer.name().hlq()),
         }
     }
 
+    /// Returns a path of the indexed file, members of archives are indexed in memory only.
     #[inline]
     fn path(&self) -> Option<&PathBuf> {
         match self {
-            Self::Stdin => None,
+            Self::Stdin | Self::Member(..) | Self::Process(_) | Self::Socket(_) => None,
             Self::File(path) => Some(&path.canonical),
         }
     }
+
+    fn members(path: PathBuf, format: archive::Format, pattern: Option<Pattern>) -> io::Result<Vec<Self>> {
+        let members = format
+            .members(&path)
+            .map_err(|e| io::Error::new(e.kind(), format!("failed to read archive {}: {}", path.hlq(), e)))?;
+        let archive = InputPath::resolve(path)?;
+
+        let members = members
+            .into_iter()
+            .filter(|member| pattern.as_ref().is_none_or(|pattern| pattern.matches(&member.name)))
+            .map(|member| Self::Member(archive.clone(), member))
+            .collect::<Vec<_>>();
+
+        if members.is_empty() {
+            let reason = match pattern {
+                Some(pattern) => format!("has no files matching {}", pattern.hlq()),
+                None => "has no files".into(),
+            };
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("archive {} {}", archive.original.hlq(), reason),
+            ));
+        }
+
+        Ok(members)
+    }
+
+    /// Splits `archive:pattern` to the path of an existing archive file and the member pattern.
+    fn split_member_pattern(path: &Path) -> Option<(PathBuf, String)> {
+        let path = path.to_str()?;
+        path.match_indices(':')
+            .map(|(i, _)| (&path[..i], &path[i + 1..]))
+            .find(|(archive, _)| Path::new(archive).is_file())
+            .map(|(archive, pattern)| (archive.into(), pattern.into()))
+    }
+}
+
+// ---
+
+/// A directory followed for input files, optionally limited to the files with names matching a wildcard pattern.
+#[derive(Clone, Debug)]
+pub struct InputDir {
+    pub path: PathBuf,
+    pub pattern: Option<Pattern>,
+}
+
+impl InputDir {
+    /// Returns InputDir if the path is a directory or a wildcard pattern of file names in a directory.
+    pub fn parse(path: &Path) -> Option<Self> {
+        if path.is_dir() {
+            return Some(Self {
+                path: path.into(),
+                pattern: None,
+            });
+        }
+
+        let name = path.file_name()?.to_str()?;
+        if !name.contains(['*', '?']) || path.exists() {
+            return None;
+        }
+
+        let dir = match path.parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+        dir.is_dir().then(|| Self {
+            path: dir.into(),
+            pattern: Some(Pattern::new(name)),
+        })
+    }
+
+    /// Returns paths of the regular files in the directory matching the pattern, sorted by name.
+    pub fn files(&self) -> io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for item in fs::read_dir(&self.path)? {
+            let path = item?.path();
+            if path.is_file() && self.matches(&path) {
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        match (&self.pattern, path.file_name().and_then(|name| name.to_str())) {
+            (None, _) => true,
+            (Some(pattern), Some(name)) => pattern.matches(name),
+            (Some(_), None) => false,
+        }
+    }
 }
 
 // ---
@@ -231,10 +364,19 @@ impl InputHolder {
                 Some(stream) => Stream::Sequential(Stream::RandomAccess(stream).into_sequential()),
                 None => Stream::Sequential(Self::stdin(stream)),
             },
-            InputReference::File(_) => match stream {
+            InputReference::File(_) | InputReference::Member(..) => match stream {
                 Some(stream) => Stream::RandomAccess(stream),
                 None => Stream::RandomAccess(reference.hold()?.stream.unwrap()),
             },
+            // the exit status is not checked here, inputs that need it spawn the command on their own
+            InputReference::Process(command) => Stream::Sequential(Box::new(command.spawn()?.1)),
+            // connections are accepted by the listener and followed on their own
+            InputReference::Socket(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("{} cannot be opened again", reference.description()),
+                ));
+            }
         })
     }
 
@@ -279,10 +421,23 @@ impl Input {
         InputReference::Stdin.open()
     }
 
+    /// Converts lines wrapped by a container runtime if the wrapper is specified.
+    ///
+    /// If `follow` is set, incomplete lines at the end of the input are held until the rest of them is appended.
+    pub fn unwrapped(self, wrapper: Option<Wrapper>, follow: bool) -> Self {
+        match wrapper {
+            Some(wrapper) => Self {
+                reference: self.reference,
+                stream: self.stream.unwrapped(wrapper, follow),
+            },
+            None => self,
+        }
+    }
+
     /// Seeks to the last `entries` entries of the input.
     pub fn tail(mut self, entries: u64, delimiter: Delimiter) -> io::Result<Self> {
         match &mut self.stream {
-            Stream::Sequential(_) => (),
+            Stream::Sequential(_) | Stream::Compressed(_) => (),
             Stream::RandomAccess(stream) => Self::seek_tail(stream, entries, delimiter)?,
         }
         Ok(self)
@@ -334,9 +489,11 @@ impl Input {
 
 /// Stream of input data.
 /// It can be either sequential or supporing random access.
+/// Compressed files are sequential until they are indexed, see [`CompressedStream`].
 pub enum Stream {
     Sequential(SequentialStream),
     RandomAccess(RandomAccessStream),
+    Compressed(CompressedStream),
 }
 
 impl Stream {
@@ -345,6 +502,7 @@ impl Stream {
     pub fn verified(self) -> Self {
         match self {
             Self::Sequential(stream) => Self::Sequential(stream),
+            Self::Compressed(stream) => Self::Compressed(stream),
             Self::RandomAccess(stream) => {
                 let mut stream = stream;
                 if stream.stream_position().is_err() {
@@ -363,6 +521,7 @@ impl Stream {
                 let meta = stream.metadata().ok().flatten();
                 Self::Sequential(Box::new(AnyDecoder::new(BufReader::new(stream)).with_metadata(meta)))
             }
+            Self::Compressed(stream) => Self::Compressed(stream),
             Self::RandomAccess(mut stream) => {
                 if let Ok(size) = stream.stream_position() {
                     log::debug!("detecting format of random access stream");
@@ -374,7 +533,17 @@ impl Stream {
                         Some(Format::Verbatim) => {
                             return Self::RandomAccess(stream);
                         }
-                        Some(_) => {
+                        Some(format) => {
+                            // only files with metadata can be indexed and then accessed randomly using checkpoints
+                            let compression = Compression::from_format(format).filter(|_| meta.is_some());
+                            if let Some(compression) = compression {
+                                log::debug!("creating decoder with checkpoints");
+                                let start = Checkpoint {
+                                    compressed: size,
+                                    ..Default::default()
+                                };
+                                return Self::Compressed(CompressedStream::new(stream, compression, start, meta));
+                            }
                             log::debug!("creating decoder");
                             let dec = AnyDecoder::new(BufReader::new(stream));
                             log::debug!("decoder created");
@@ -388,11 +557,26 @@ impl Stream {
         }
     }
 
+    /// Converts lines wrapped by a container runtime.
+    /// The resulting stream is sequential because the positions in it do not match the positions in the source stream.
+    ///
+    /// If `follow` is set, incomplete lines at the end of the stream are held until the rest of them is appended.
+    pub fn unwrapped(self, wrapper: Wrapper, follow: bool) -> Self {
+        let meta = self.metadata().ok().flatten();
+        let stream = self.into_sequential();
+        Self::Sequential(Box::new(
+            Unwrapper::new(BufReader::new(stream), wrapper)
+                .with_follow(follow)
+                .with_metadata(meta),
+        ))
+    }
+
     /// Converts the stream to a sequential stream.
     pub fn as_sequential(&mut self) -> StreamOver<&mut (dyn ReadMeta + Send + Sync)> {
         match self {
             Self::Sequential(stream) => StreamOver(stream),
             Self::RandomAccess(stream) => StreamOver(stream),
+            Self::Compressed(stream) => StreamOver(stream),
         }
     }
 
@@ -401,6 +585,7 @@ impl Stream {
         match self {
             Self::Sequential(stream) => stream,
             Self::RandomAccess(stream) => Box::new(StreamOver(stream)),
+            Self::Compressed(stream) => Box::new(stream),
         }
     }
 
@@ -409,6 +594,7 @@ impl Stream {
         match self {
             Self::Sequential(stream) => Self::Sequential(Box::new(TaggedStream { reference, stream })),
             Self::RandomAccess(stream) => Self::RandomAccess(Box::new(TaggedStream { reference, stream })),
+            Self::Compressed(stream) => Self::Compressed(stream.tagged(reference)),
         }
     }
 }
@@ -419,6 +605,7 @@ impl Read for Stream {
         match self {
             Self::Sequential(stream) => stream.read(buf),
             Self::RandomAccess(stream) => stream.read(buf),
+            Self::Compressed(stream) => stream.read(buf),
         }
     }
 }
@@ -429,12 +616,69 @@ impl Meta for Stream {
         match self {
             Self::Sequential(stream) => stream.metadata(),
             Self::RandomAccess(stream) => stream.metadata(),
+            Self::Compressed(stream) => stream.metadata(),
         }
     }
 }
 
 // ---
 
+/// Compressed file decoded sequentially with recording of decoder checkpoints.
+///
+/// Once the file is indexed, the checkpoints stored in the index allow to decode any of its blocks
+/// starting from the closest checkpoint before it, see [`IndexedInput`].
+/// Checkpoints are recorded at the start of each member and, inside gzip members, about every megabyte
+/// of decoded data, so an xz or zstd file consisting of a single member is always decoded from its beginning.
+pub struct CompressedStream {
+    decoder: Decoder<BufReader<RandomAccessStream>>,
+    meta: Option<Metadata>,
+    reference: Option<InputReference>,
+}
+
+impl CompressedStream {
+    fn new(stream: RandomAccessStream, compression: Compression, start: Checkpoint, meta: Option<Metadata>) -> Self {
+        Self {
+            decoder: Decoder::new(compression, BufReader::new(stream), start),
+            meta,
+            reference: None,
+        }
+    }
+
+    fn tagged(self, reference: InputReference) -> Self {
+        Self {
+            reference: Some(reference),
+            ..self
+        }
+    }
+
+    /// Converts the stream to a random access stream using the given checkpoints.
+    fn into_seekable(self, checkpoints: Vec<Checkpoint>, size: u64) -> io::Result<RandomAccessStream> {
+        let stream = SeekableDecoder::new(self.decoder, checkpoints, size)?.with_metadata(self.meta);
+        Ok(match self.reference {
+            Some(reference) => Box::new(TaggedStream { reference, stream }),
+            None => Box::new(stream),
+        })
+    }
+}
+
+impl Read for CompressedStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.decoder.read(buf).map_err(|e| match &self.reference {
+            Some(reference) => io::Error::new(e.kind(), format!("failed to read {}: {}", reference.description(), e)),
+            None => e,
+        })
+    }
+}
+
+impl Meta for CompressedStream {
+    #[inline]
+    fn metadata(&self) -> io::Result<Option<Metadata>> {
+        Ok(self.meta.clone())
+    }
+}
+
+// ---
+
 /// A wrapper around a stream that adds context to the returned errors.
 pub struct TaggedStream<R> {
     reference: InputReference,
@@ -511,9 +755,14 @@ impl IndexedInput {
     }
 
     /// Converts the input to blocks.
-    pub fn into_blocks(self) -> Blocks<IndexedInput, impl Iterator<Item = usize>> {
+    pub fn into_blocks(self) -> Blocks<IndexedInput, Range<usize>> {
+        Arc::new(self).blocks()
+    }
+
+    /// Returns blocks of the shared input, so that they can be iterated more than once.
+    pub fn blocks(self: &Arc<Self>) -> Blocks<IndexedInput, Range<usize>> {
         let n = self.index.source().blocks.len();
-        Blocks::new(Arc::new(self), 0..n)
+        Blocks::new(self.clone(), 0..n)
     }
 
     fn from_stream<FS>(
@@ -545,6 +794,7 @@ impl IndexedInput {
             match stream {
                 Stream::Sequential(stream) => Self::index_sequential_stream(path, &meta, stream, indexer),
                 Stream::RandomAccess(stream) => Self::index_random_access_stream(path, &meta, stream, indexer),
+                Stream::Compressed(stream) => Self::index_compressed_stream(path, &meta, stream, indexer),
             }
         } else {
             let mut tee = TeeReader::new(stream, ReplayBufCreator::new());
@@ -597,6 +847,38 @@ impl IndexedInput {
 
         Ok((stream, index))
     }
+
+    fn index_compressed_stream<FS>(
+        path: &Path,
+        meta: &Metadata,
+        stream: CompressedStream,
+        indexer: &Indexer<FS>,
+    ) -> Result<(RandomAccessStream, Index)>
+    where
+        FS: FileSystem + Sync,
+        FS::Metadata: SourceMetadata,
+    {
+        let checkpoints = stream.decoder.checkpoints();
+        let mut tee = TeeReader::new(stream, ReplayBufCreator::new());
+        let index = indexer.index_decoded_stream(&mut tee, &checkpoints, path, meta)?;
+        let meta = meta.clone();
+
+        if tee.processed() != 0 {
+            let buf = tee.into_writer().result()?;
+            return Ok((Box::new(ReplayBufReader::new(buf).with_metadata(Some(meta))), index));
+        }
+
+        // with a single checkpoint, caching the decoded data is better than decoding it again for each seek back
+        let checkpoints = index.source().checkpoints();
+        let stream: RandomAccessStream = if checkpoints.len() > 1 {
+            let size = index.source().blocks.last().map_or(0, |b| b.offset + u64::from(b.size));
+            tee.into_reader().into_seekable(checkpoints, size)?
+        } else {
+            Box::new(ReplaySeekReader::new(tee.into_reader()).with_metadata(Some(meta)))
+        };
+
+        Ok((stream, index))
+    }
 }
 
 // ---
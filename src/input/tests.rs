@@ -1,4 +1,4 @@
-use std::io::{self, Cursor, Read};
+use std::io::{self, Cursor, Read, Write};
 
 use assert_matches::assert_matches;
 use itertools::Itertools;
@@ -7,6 +7,7 @@ use nonzero_ext::nonzero;
 use super::*;
 use crate::{
     index::IndexerSettings,
+    testing::TempDir,
     vfs::{self, LocalFileSystem},
 };
 
@@ -22,6 +23,17 @@ fn test_input_reference() {
     assert_eq!(reference.path(), Some(&PathBuf::from("test.log")));
 }
 
+#[test]
+fn test_input_reference_socket() {
+    let reference = InputReference::Socket(Peer::new("tcp://127.0.0.1:5514".into()));
+    assert_eq!(
+        reference.description(),
+        "input from \u{1b}[33m\"tcp://127.0.0.1:5514\"\u{1b}[0m"
+    );
+    assert_eq!(reference.path(), None);
+    assert_eq!(reference.open().err().unwrap().kind(), io::ErrorKind::Unsupported);
+}
+
 #[test]
 fn test_input_holder() {
     let reference = InputReference::File(InputPath::ephemeral(PathBuf::from("sample/test.log")));
@@ -245,6 +257,178 @@ fn test_indexed_input_sequential_access() {
     }
 }
 
+#[test]
+fn test_indexed_input_compressed() {
+    use flate2::{Compression, write::GzEncoder};
+
+    let tmp = TempDir::new("input-compressed");
+    let dir = tmp.path();
+    fs::create_dir_all(dir.join("cache")).unwrap();
+    let path = dir.join("test.log.gz");
+
+    let mut expected = Vec::new();
+    let mut data = Vec::new();
+    for member in 0..4 {
+        let text = (0..20)
+            .map(|i| format!("ts=2024-01-02T03:{:02}:{:02}Z msg=m{}\n", member, i, i))
+            .collect::<String>();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        data.extend(encoder.finish().unwrap());
+        expected.extend(text.lines().map(String::from));
+    }
+    fs::write(&path, data).unwrap();
+
+    // the second pass uses the saved index and seeks using the checkpoints
+    for _ in 0..2 {
+        let indexer = Indexer::new(
+            1,
+            dir.join("cache"),
+            IndexerSettings {
+                buffer_size: nonzero!(256u32).into(),
+                ..IndexerSettings::with_fs(LocalFileSystem)
+            },
+        );
+        let input = IndexedInput::open(&path, &indexer, Delimiter::Newline).unwrap();
+        assert_eq!(input.index.source().checkpoints().len(), 4);
+
+        // read blocks in reverse order to make decoding restart at the checkpoints
+        let mut lines = Vec::new();
+        for block in input.into_blocks().collect_vec().into_iter().rev() {
+            let entries = block.into_entries().unwrap().collect_vec();
+            for entry in entries.iter().rev() {
+                lines.push(String::from_utf8_lossy(entry.bytes()).trim_end().to_string());
+            }
+        }
+        lines.reverse();
+        assert_eq!(lines, expected);
+    }
+}
+
+#[test]
+fn test_indexed_input_compressed_single_member() {
+    use flate2::{Compression, write::GzEncoder};
+
+    let tmp = TempDir::new("input-compressed-single");
+    let dir = tmp.path();
+    fs::create_dir_all(dir.join("cache")).unwrap();
+    let path = dir.join("test.log.gz");
+
+    let text = (0..60000)
+        .map(|i| {
+            format!(
+                "ts=2024-01-02T03:{:02}:{:02}Z msg=m{} n={}\n",
+                i / 60 % 60,
+                i % 60,
+                i,
+                i * 7919 % 10007
+            )
+        })
+        .collect::<String>();
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(text.as_bytes()).unwrap();
+    fs::write(&path, encoder.finish().unwrap()).unwrap();
+    let expected = text.lines().map(String::from).collect_vec();
+
+    // the second pass uses the saved index and resumes decoding inside the member
+    for _ in 0..2 {
+        let indexer = Indexer::new(
+            1,
+            dir.join("cache"),
+            IndexerSettings {
+                buffer_size: nonzero!(65536u32).into(),
+                ..IndexerSettings::with_fs(LocalFileSystem)
+            },
+        );
+        let input = IndexedInput::open(&path, &indexer, Delimiter::Newline).unwrap();
+        let checkpoints = input.index.source().checkpoints();
+        assert!(checkpoints.len() > 1);
+        assert!(checkpoints[1..].iter().all(|checkpoint| !checkpoint.window.is_empty()));
+
+        let mut lines = Vec::new();
+        for block in input.into_blocks().collect_vec().into_iter().rev() {
+            let entries = block.into_entries().unwrap().collect_vec();
+            for entry in entries.iter().rev() {
+                lines.push(String::from_utf8_lossy(entry.bytes()).trim_end().to_string());
+            }
+        }
+        lines.reverse();
+        assert_eq!(lines, expected);
+    }
+}
+
+#[test]
+fn test_input_reference_expand_archive() {
+    let tmp = TempDir::new("input-archive");
+    let dir = tmp.path();
+    let path = dir.join("bundle.tar");
+
+    let mut builder = tar::Builder::new(Vec::new());
+    for (name, data) in [("app/a.log", "msg=a\n"), ("app/b.log", "msg=b\n"), ("c.log", "msg=c\n")] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        builder.append_data(&mut header, name, data.as_bytes()).unwrap();
+    }
+    fs::write(&path, builder.into_inner().unwrap()).unwrap();
+
+    let read = |reference: &InputReference| {
+        let mut buf = String::new();
+        reference.open().unwrap().stream.read_to_string(&mut buf).unwrap();
+        buf
+    };
+
+    let references = InputReference::expand(path.clone()).unwrap();
+    assert_eq!(references.len(), 3);
+    assert_eq!(
+        references.iter().map(read).collect_vec(),
+        ["msg=a\n", "msg=b\n", "msg=c\n"]
+    );
+    assert_eq!(references[0].path(), None);
+    assert!(references[0].description().contains("app/a.log"));
+
+    let references = InputReference::expand(format!("{}:app/*", path.display()).into()).unwrap();
+    assert_eq!(references.iter().map(read).collect_vec(), ["msg=a\n", "msg=b\n"]);
+
+    let err = InputReference::expand(format!("{}:*.txt", path.display()).into()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    let log = dir.join("test.log");
+    fs::write(&log, "msg=d\n").unwrap();
+    assert_matches!(
+        &InputReference::expand(log.clone()).unwrap()[..],
+        [InputReference::File(_)]
+    );
+    let err = InputReference::expand(format!("{}:*", log.display()).into()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_input_dir() {
+    let tmp = TempDir::new("input-dir");
+    let dir = tmp.path();
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    for name in ["b.log", "a.log", "c.txt"] {
+        fs::write(dir.join(name), "msg=test\n").unwrap();
+    }
+
+    let all = InputDir::parse(dir).unwrap();
+    assert!(all.pattern.is_none());
+    assert_eq!(
+        all.files().unwrap(),
+        [dir.join("a.log"), dir.join("b.log"), dir.join("c.txt")]
+    );
+
+    let logs = InputDir::parse(&dir.join("*.log")).unwrap();
+    assert_eq!(logs.path, dir);
+    assert_eq!(logs.files().unwrap(), [dir.join("a.log"), dir.join("b.log")]);
+
+    fs::write(dir.join("d.log"), "msg=test\n").unwrap();
+    assert_eq!(logs.files().unwrap().len(), 3);
+
+    assert!(InputDir::parse(&dir.join("a.log")).is_none());
+    assert!(InputDir::parse(&dir.join("missing").join("*.log")).is_none());
+}
+
 // ---
 
 struct FailingReader;
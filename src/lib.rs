@@ -1,9 +1,12 @@
 // public modules
 pub mod app;
 pub mod appdirs;
+pub mod archive;
 pub mod cli;
+pub mod command;
 pub mod condition;
 pub mod config;
+pub mod container;
 pub mod datefmt;
 pub mod error;
 pub mod fmtx;
@@ -14,11 +17,13 @@ pub mod index_capnp;
 pub mod input;
 pub mod iox;
 pub mod level;
+pub mod listen;
 pub mod output;
 pub mod pager;
 pub mod query;
 pub mod settings;
 pub mod syntax;
+pub mod textformat;
 pub mod theme;
 pub mod themecfg;
 pub mod timeparse;
@@ -27,15 +32,23 @@ pub mod timezone;
 pub mod types;
 
 // private modules
+mod accesslog;
+mod alert;
 mod console;
+mod decoder;
 mod eseq;
+mod extsort;
 mod filtering;
 mod fsmon;
+mod histogram;
 mod model;
 mod number;
 mod replay;
+mod resume;
 mod scanning;
 mod serdex;
+mod stats;
+mod syslog;
 mod tee;
 mod vfs;
 #[cfg(windows)]
@@ -56,7 +69,9 @@ pub use app::{App, FieldOptions, Options, SegmentProcessor};
 pub use datefmt::{DateTimeFormatter, LinuxDateFormat};
 pub use filtering::DefaultNormalizing;
 pub use formatting::RecordFormatter;
-pub use model::{FieldFilterSet, Filter, Level, Parser, ParserSettings, RecordFilter};
+pub use model::{
+    EmbeddedJsonExpansion, FieldFilterSet, FieldMembership, Filter, Level, Parser, ParserSettings, RecordFilter,
+};
 pub use query::Query;
 pub use scanning::{Delimit, Delimiter, SearchExt};
 pub use settings::Settings;
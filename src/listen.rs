@@ -0,0 +1,461 @@
+// std imports
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::Metadata,
+    io::{self, Read},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream, UdpSocket},
+    time::{Duration, Instant},
+};
+
+// unix-only std imports
+#[cfg(unix)]
+use std::{
+    fs,
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+};
+
+// third-party imports
+use crossbeam_channel::{self as channel, Receiver, Sender, TrySendError};
+
+// local imports
+use crate::{input::Meta, xerr::HighlightQuoted};
+
+// ---
+
+/// Address of a socket to listen on for the entries sent by other processes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Udp(String),
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl Endpoint {
+    /// Parses an address given as `udp://HOST:PORT`, `tcp://HOST:PORT` or `unix://PATH`.
+    pub fn parse(url: &str) -> io::Result<Self> {
+        let invalid = |reason: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid address {} to listen on: {}", url.hlq(), reason),
+            )
+        };
+        let (scheme, address) = url
+            .split_once("://")
+            .ok_or_else(|| invalid("expected udp://HOST:PORT, tcp://HOST:PORT or unix://PATH"))?;
+        match scheme {
+            "udp" | "tcp" => {
+                if address
+                    .rsplit_once(':')
+                    .is_none_or(|(host, port)| host.is_empty() || port.is_empty())
+                {
+                    return Err(invalid("expected HOST:PORT"));
+                }
+                Ok(match scheme {
+                    "udp" => Self::Udp(address.into()),
+                    _ => Self::Tcp(address.into()),
+                })
+            }
+            #[cfg(unix)]
+            "unix" if !address.is_empty() => Ok(Self::Unix(address.into())),
+            #[cfg(unix)]
+            "unix" => Err(invalid("expected a path")),
+            #[cfg(not(unix))]
+            "unix" => Err(invalid("unix sockets are not supported on this platform")),
+            _ => Err(invalid(&format!("unsupported protocol {}", scheme.hlq()))),
+        }
+    }
+
+    /// Binds the socket and returns the Listener accepting peers on it.
+    pub fn bind(&self) -> io::Result<Listener> {
+        let failed = |e: io::Error| io::Error::new(e.kind(), format!("failed to listen on {}: {}", self.hlq(), e));
+
+        let socket = match self {
+            Self::Udp(address) => {
+                let socket = UdpSocket::bind(address.as_str()).map_err(failed)?;
+                // receiving times out to expire the idle peers even if nothing else is received
+                socket.set_read_timeout(Some(UDP_PEER_IDLE_TIMEOUT)).map_err(failed)?;
+                Socket::Udp {
+                    socket,
+                    peers: HashMap::new(),
+                    buf: vec![0; MAX_DATAGRAM_SIZE],
+                    idle_timeout: UDP_PEER_IDLE_TIMEOUT,
+                    expired: Instant::now(),
+                    queue_size: DATAGRAM_QUEUE_SIZE,
+                }
+            }
+            Self::Tcp(address) => Socket::Tcp(TcpListener::bind(address.as_str()).map_err(failed)?),
+            #[cfg(unix)]
+            Self::Unix(path) => Socket::Unix {
+                listener: Self::bind_unix(path).map_err(failed)?,
+                connections: 0,
+            },
+        };
+
+        Ok(Listener {
+            endpoint: self.clone(),
+            socket,
+        })
+    }
+
+    /// Binds the unix socket, a socket file left by a process that is not running anymore is replaced.
+    #[cfg(unix)]
+    fn bind_unix(path: &Path) -> io::Result<UnixListener> {
+        match UnixListener::bind(path) {
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse && UnixStream::connect(path).is_err() => {
+                fs::remove_file(path)?;
+                UnixListener::bind(path)
+            }
+            result => result,
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Udp(address) => write!(f, "udp://{}", address),
+            Self::Tcp(address) => write!(f, "tcp://{}", address),
+            #[cfg(unix)]
+            Self::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+// ---
+
+/// Listener accepts peers sending entries to a socket.
+pub struct Listener {
+    endpoint: Endpoint,
+    socket: Socket,
+}
+
+impl Listener {
+    /// Returns the address the socket is bound to, with the actual port if port 0 was requested.
+    pub fn address(&self) -> String {
+        let local = |address: io::Result<SocketAddr>| address.map(|a| a.to_string());
+        let address = match &self.socket {
+            Socket::Udp { socket, .. } => local(socket.local_addr()),
+            Socket::Tcp(listener) => local(listener.local_addr()),
+            #[cfg(unix)]
+            Socket::Unix { .. } => return self.endpoint.to_string(),
+        };
+        match (&self.endpoint, address) {
+            (Endpoint::Udp(_), Ok(address)) => format!("udp://{}", address),
+            (Endpoint::Tcp(_), Ok(address)) => format!("tcp://{}", address),
+            _ => self.endpoint.to_string(),
+        }
+    }
+
+    /// Returns a Waker making a pending `accept` return from another thread.
+    pub fn waker(&self) -> io::Result<Waker> {
+        // a socket bound to an unspecified address is reached through the loopback interface
+        let reachable = |mut address: SocketAddr| {
+            if address.ip().is_unspecified() {
+                address.set_ip(match address.ip() {
+                    IpAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+                    IpAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+                });
+            }
+            address
+        };
+        Ok(match &self.socket {
+            Socket::Udp { socket, .. } => Waker::Udp(reachable(socket.local_addr()?)),
+            Socket::Tcp(listener) => Waker::Tcp(reachable(listener.local_addr()?)),
+            #[cfg(unix)]
+            Socket::Unix { .. } => match &self.endpoint {
+                Endpoint::Unix(path) => Waker::Unix(path.clone()),
+                _ => unreachable!(),
+            },
+        })
+    }
+
+    /// Waits for a new peer and returns its Connection.
+    ///
+    /// Stream sockets make a new peer of each accepted connection.
+    /// Datagram sockets make a new peer of each new source address, and the datagrams of known peers
+    /// are passed to their connections while waiting, so this has to be called continuously.
+    /// Peers sending nothing for a while are forgotten, so that their connections end,
+    /// and they become new peers again if they send more.
+    pub fn accept(&mut self) -> io::Result<Connection> {
+        match &mut self.socket {
+            Socket::Udp {
+                socket,
+                peers,
+                buf,
+                idle_timeout,
+                expired,
+                queue_size,
+            } => loop {
+                let received = socket.recv_from(buf);
+                let now = Instant::now();
+                if now.duration_since(*expired) >= *idle_timeout {
+                    peers.retain(|_, peer| now.duration_since(peer.seen) < *idle_timeout);
+                    *expired = now;
+                }
+                let (n, address) = match received {
+                    Ok(received) => received,
+                    Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => continue,
+                    Err(e) => return Err(e),
+                };
+                let mut datagram = buf[..n].to_vec();
+                if let Some(peer) = peers.get_mut(&address) {
+                    match peer.tx.try_send(datagram) {
+                        Ok(()) => {
+                            peer.seen = now;
+                            continue;
+                        }
+                        // waiting for a connection that does not keep up would stall receiving from all peers
+                        Err(TrySendError::Full(_)) => {
+                            peer.seen = now;
+                            peer.dropped += 1;
+                            if peer
+                                .warned
+                                .is_none_or(|warned| now.duration_since(warned) >= DROP_WARNING_INTERVAL)
+                            {
+                                log::warn!(
+                                    "dropped {} datagrams from udp://{} as they are not processed in time",
+                                    peer.dropped,
+                                    address
+                                );
+                                peer.dropped = 0;
+                                peer.warned = Some(now);
+                            }
+                            continue;
+                        }
+                        // the connection was dropped, so the peer is new again
+                        Err(TrySendError::Disconnected(d)) => datagram = d,
+                    }
+                }
+                let (tx, rx) = channel::bounded(*queue_size);
+                tx.send(datagram).ok();
+                peers.insert(
+                    address,
+                    UdpPeer {
+                        tx,
+                        seen: now,
+                        dropped: 0,
+                        warned: None,
+                    },
+                );
+                return Ok(Connection {
+                    peer: Peer::new(format!("udp://{}", address)),
+                    stream: Stream::Udp(Datagrams::new(rx)),
+                });
+            },
+            Socket::Tcp(listener) => {
+                let (stream, address) = listener.accept()?;
+                Ok(Connection {
+                    peer: Peer::new(format!("tcp://{}", address)),
+                    stream: Stream::Tcp(stream),
+                })
+            }
+            #[cfg(unix)]
+            Socket::Unix { listener, connections } => {
+                // peers of unix sockets are usually unnamed, so they are numbered instead
+                let (stream, _) = listener.accept()?;
+                *connections += 1;
+                Ok(Connection {
+                    peer: Peer::new(format!("{}#{}", self.endpoint, connections)),
+                    stream: Stream::Unix(stream),
+                })
+            }
+        }
+    }
+}
+
+enum Socket {
+    Udp {
+        socket: UdpSocket,
+        peers: HashMap<SocketAddr, UdpPeer>,
+        buf: Vec<u8>,
+        idle_timeout: Duration,
+        // time the idle peers were expired last
+        expired: Instant,
+        // number of datagrams kept for a connection until it reads them
+        queue_size: usize,
+    },
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix {
+        listener: UnixListener,
+        connections: usize,
+    },
+}
+
+/// Known source of datagrams along with the time it sent the last one.
+///
+/// Datagrams that do not fit in the queue of its connection are dropped and counted until the next warning.
+struct UdpPeer {
+    tx: Sender<Vec<u8>>,
+    seen: Instant,
+    dropped: usize,
+    warned: Option<Instant>,
+}
+
+/// Waker makes a pending `accept` of a Listener return by connecting to its socket or sending an empty datagram to it,
+/// the resulting Connection is expected to be dropped.
+#[derive(Debug, Clone)]
+pub enum Waker {
+    Udp(SocketAddr),
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl Waker {
+    pub fn wake(&self) -> io::Result<()> {
+        match self {
+            Self::Udp(address) => {
+                let local: SocketAddr = match address {
+                    SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+                    SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+                };
+                UdpSocket::bind(local)?.send_to(&[], address)?;
+            }
+            Self::Tcp(address) => drop(TcpStream::connect(address)?),
+            #[cfg(unix)]
+            Self::Unix(path) => drop(UnixStream::connect(path)?),
+        }
+        Ok(())
+    }
+}
+
+// ---
+
+/// A peer sending entries to a socket, it is named after its address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peer {
+    name: String,
+}
+
+impl Peer {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+
+    /// Returns the name of the peer used to name the input.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A new peer along with the data it sends.
+pub struct Connection {
+    pub peer: Peer,
+    pub stream: Stream,
+}
+
+/// Data sent by a peer, it ends when the peer closes the connection.
+///
+/// Datagrams end when the peer sends nothing for a while, and a newline is added to each datagram that does not end with it,
+/// so that a datagram containing a single entry without a trailing newline is complete.
+pub enum Stream {
+    Udp(Datagrams),
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    /// Returns a Closer ending the stream from another thread, so that a pending read returns.
+    pub fn closer(&self) -> io::Result<Closer> {
+        Ok(match self {
+            Self::Udp(_) => Closer::Udp,
+            Self::Tcp(stream) => Closer::Tcp(stream.try_clone()?),
+            #[cfg(unix)]
+            Self::Unix(stream) => Closer::Unix(stream.try_clone()?),
+        })
+    }
+}
+
+impl Read for Stream {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Udp(stream) => stream.read(buf),
+            Self::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Meta for Stream {
+    #[inline]
+    fn metadata(&self) -> io::Result<Option<Metadata>> {
+        Ok(None)
+    }
+}
+
+/// Closer ends a Stream by shutting down its connection.
+///
+/// Datagrams are not affected, they end when the Listener receiving them is dropped.
+pub enum Closer {
+    Udp,
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Closer {
+    pub fn close(&self) -> io::Result<()> {
+        match self {
+            Self::Udp => Ok(()),
+            Self::Tcp(stream) => stream.shutdown(Shutdown::Both),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.shutdown(Shutdown::Both),
+        }
+    }
+}
+
+// ---
+
+/// Datagrams received from a peer, read one after another.
+pub struct Datagrams {
+    rx: Receiver<Vec<u8>>,
+    current: Vec<u8>,
+    pos: usize,
+}
+
+impl Datagrams {
+    fn new(rx: Receiver<Vec<u8>>) -> Self {
+        Self {
+            rx,
+            current: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl Read for Datagrams {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.current.len() {
+            let Ok(mut datagram) = self.rx.recv() else {
+                return Ok(0);
+            };
+            if datagram.last().is_some_and(|&b| b != b'\n') {
+                datagram.push(b'\n');
+            }
+            self.current = datagram;
+            self.pos = 0;
+        }
+
+        let n = buf.len().min(self.current.len() - self.pos);
+        buf[..n].copy_from_slice(&self.current[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+// ---
+
+const MAX_DATAGRAM_SIZE: usize = 64 * 1024;
+const DATAGRAM_QUEUE_SIZE: usize = 256;
+const UDP_PEER_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+const DROP_WARNING_INTERVAL: Duration = Duration::from_secs(10);
+
+#[cfg(test)]
+mod tests;
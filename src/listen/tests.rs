@@ -0,0 +1,220 @@
+use super::*;
+
+use std::io::Write;
+
+fn read_line(stream: &mut Stream) -> String {
+    let mut line = Vec::new();
+    let mut byte = [0];
+    while line.last() != Some(&b'\n') {
+        assert_eq!(stream.read(&mut byte).unwrap(), 1);
+        line.push(byte[0]);
+    }
+    String::from_utf8(line).unwrap()
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        Endpoint::parse("udp://0.0.0.0:5514").unwrap(),
+        Endpoint::Udp("0.0.0.0:5514".into())
+    );
+    assert_eq!(
+        Endpoint::parse("tcp://[::1]:5514").unwrap(),
+        Endpoint::Tcp("[::1]:5514".into())
+    );
+    assert_eq!(
+        Endpoint::parse("tcp://localhost:5514").unwrap().to_string(),
+        "tcp://localhost:5514"
+    );
+    #[cfg(unix)]
+    assert_eq!(
+        Endpoint::parse("unix:///tmp/hl.sock").unwrap(),
+        Endpoint::Unix("/tmp/hl.sock".into())
+    );
+
+    for url in [
+        "",
+        "localhost:5514",
+        "tcp://localhost",
+        "tcp://:5514",
+        "udp://",
+        "unix://",
+        "http://localhost:80",
+    ] {
+        assert_eq!(
+            Endpoint::parse(url).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput,
+            "{url}"
+        );
+    }
+}
+
+#[test]
+fn test_tcp() {
+    let mut listener = Endpoint::parse("tcp://127.0.0.1:0").unwrap().bind().unwrap();
+    let address = listener.address();
+    let mut client = TcpStream::connect(address.strip_prefix("tcp://").unwrap()).unwrap();
+    client.write_all(b"a\nb\n").unwrap();
+
+    let mut connection = listener.accept().unwrap();
+    assert_eq!(
+        connection.peer.name(),
+        format!("tcp://{}", client.local_addr().unwrap())
+    );
+    assert!(connection.stream.metadata().unwrap().is_none());
+    assert_eq!(read_line(&mut connection.stream), "a\n");
+    assert_eq!(read_line(&mut connection.stream), "b\n");
+
+    drop(client);
+    assert_eq!(connection.stream.read(&mut [0; 16]).unwrap(), 0);
+}
+
+#[test]
+fn test_udp() {
+    let mut listener = Endpoint::parse("udp://127.0.0.1:0").unwrap().bind().unwrap();
+    let address = listener.address();
+    let address = address.strip_prefix("udp://").unwrap();
+    let client1 = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let client2 = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    client1.send_to(b"a", address).unwrap();
+    let mut connection1 = listener.accept().unwrap();
+    assert_eq!(
+        connection1.peer.name(),
+        format!("udp://{}", client1.local_addr().unwrap())
+    );
+
+    // datagrams of the known peer are passed to its connection while waiting for a new peer
+    client1.send_to(b"b\n", address).unwrap();
+    client2.send_to(b"c", address).unwrap();
+    let mut connection2 = listener.accept().unwrap();
+    assert_eq!(
+        connection2.peer.name(),
+        format!("udp://{}", client2.local_addr().unwrap())
+    );
+
+    assert_eq!(read_line(&mut connection1.stream), "a\n");
+    assert_eq!(read_line(&mut connection1.stream), "b\n");
+    assert_eq!(read_line(&mut connection2.stream), "c\n");
+}
+
+#[test]
+fn test_udp_overflow() {
+    let mut listener = Endpoint::parse("udp://127.0.0.1:0").unwrap().bind().unwrap();
+    let Socket::Udp { queue_size, .. } = &mut listener.socket else {
+        unreachable!();
+    };
+    *queue_size = 2;
+    let address = listener.address();
+    let address = address.strip_prefix("udp://").unwrap();
+    let client1 = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let client2 = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    client1.send_to(b"a", address).unwrap();
+    let mut connection1 = listener.accept().unwrap();
+
+    // datagrams that do not fit in the queue of the connection are dropped without blocking other peers
+    for datagram in [b"b", b"c", b"d"] {
+        client1.send_to(datagram, address).unwrap();
+    }
+    client2.send_to(b"e", address).unwrap();
+    let mut connection2 = listener.accept().unwrap();
+    assert_eq!(read_line(&mut connection2.stream), "e\n");
+
+    drop(listener);
+    assert_eq!(read_line(&mut connection1.stream), "a\n");
+    assert_eq!(read_line(&mut connection1.stream), "b\n");
+    assert_eq!(connection1.stream.read(&mut [0; 16]).unwrap(), 0);
+}
+
+#[test]
+fn test_udp_idle() {
+    let mut listener = Endpoint::parse("udp://127.0.0.1:0").unwrap().bind().unwrap();
+    let Socket::Udp {
+        socket, idle_timeout, ..
+    } = &mut listener.socket
+    else {
+        unreachable!();
+    };
+    *idle_timeout = Duration::from_millis(50);
+    socket.set_read_timeout(Some(*idle_timeout)).unwrap();
+    let address = listener.address();
+    let address = address.strip_prefix("udp://").unwrap();
+    let client1 = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let client2 = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    client1.send_to(b"a", address).unwrap();
+    let mut connection1 = listener.accept().unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+
+    // the idle peer is forgotten while waiting for a new one, so its connection ends
+    client2.send_to(b"b", address).unwrap();
+    let mut connection2 = listener.accept().unwrap();
+    assert_eq!(read_line(&mut connection1.stream), "a\n");
+    assert_eq!(connection1.stream.read(&mut [0; 16]).unwrap(), 0);
+    assert_eq!(read_line(&mut connection2.stream), "b\n");
+
+    // the forgotten peer becomes a new one if it sends more
+    client1.send_to(b"c", address).unwrap();
+    let mut connection3 = listener.accept().unwrap();
+    assert_eq!(connection3.peer, connection1.peer);
+    assert_eq!(read_line(&mut connection3.stream), "c\n");
+}
+
+#[test]
+fn test_waker() {
+    for url in ["tcp://0.0.0.0:0", "udp://0.0.0.0:0"] {
+        let mut listener = Endpoint::parse(url).unwrap().bind().unwrap();
+        let waker = listener.waker().unwrap();
+        let accepted = std::thread::scope(|scope| {
+            let accepting = scope.spawn(|| listener.accept().map(|_| ()));
+            waker.wake().unwrap();
+            accepting.join().unwrap()
+        });
+        assert!(accepted.is_ok(), "{}", url);
+    }
+}
+
+#[test]
+fn test_closer() {
+    let mut listener = Endpoint::parse("tcp://127.0.0.1:0").unwrap().bind().unwrap();
+    let address = listener.address();
+    let _client = TcpStream::connect(address.strip_prefix("tcp://").unwrap()).unwrap();
+    let mut connection = listener.accept().unwrap();
+    let closer = connection.stream.closer().unwrap();
+
+    // the pending read ends even though the peer is still connected
+    let n = std::thread::scope(|scope| {
+        let reading = scope.spawn(|| connection.stream.read(&mut [0; 16]).unwrap());
+        std::thread::sleep(Duration::from_millis(50));
+        closer.close().unwrap();
+        reading.join().unwrap()
+    });
+    assert_eq!(n, 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unix() {
+    use crate::testing::TempDir;
+
+    let dir = TempDir::new("listen");
+    let path = dir.path().join("test.sock");
+    // a socket file left by a previous process is replaced
+    drop(UnixListener::bind(&path).unwrap());
+
+    let endpoint = Endpoint::Unix(path.clone());
+    let mut listener = endpoint.bind().unwrap();
+    assert_eq!(listener.address(), format!("unix://{}", path.display()));
+
+    for n in 1..=2 {
+        let mut client = UnixStream::connect(&path).unwrap();
+        client.write_all(format!("{n}\n").as_bytes()).unwrap();
+        let mut connection = listener.accept().unwrap();
+        assert_eq!(connection.peer.name(), format!("unix://{}#{}", path.display(), n));
+        assert_eq!(read_line(&mut connection.stream), format!("{n}\n"));
+    }
+
+    // the socket is in use while the listener is alive
+    assert_eq!(endpoint.bind().err().unwrap().kind(), io::ErrorKind::AddrInUse);
+}
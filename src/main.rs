@@ -1,5 +1,6 @@
 // std imports
 use std::{
+    collections::HashSet,
     default::Default,
     io::{IsTerminal, Write, stdin, stdout},
     path::PathBuf,
@@ -20,16 +21,23 @@ use utf8_supported::{Utf8Support, utf8_supported};
 
 // local imports
 use hl::{
-    Delimiter, IncludeExcludeKeyFilter, KeyMatchOptions, app, cli, config,
+    Delimiter, EmbeddedJsonExpansion, IncludeExcludeKeyFilter, KeyMatchOptions, app, cli,
+    command::Command,
+    config,
+    container::Wrapper,
     datefmt::LinuxDateFormat,
     error::*,
+    formatting::Template,
     help,
-    input::InputReference,
+    index::cache::Limits as IndexCacheLimits,
+    input::{InputDir, InputReference},
+    listen::Endpoint,
     output::{OutputDelimiter, OutputStream},
     pager::{PagerRole, PagerSelector, PagerWatcher},
     query::Query,
     settings::{AsciiModeOpt, InputInfo, Settings},
     signal::SignalHandler,
+    textformat::TextFormat,
     theme::Theme,
     timeparse::parse_time,
     timezone::Tz,
@@ -115,7 +123,11 @@ fn run() -> Result<()> {
         cli::PagingOption::Always => true,
         cli::PagingOption::Never => false,
     };
-    let paging = if opt.paging_never { false } else { paging };
+    let paging = if opt.paging_never || opt.index_cache.is_some() {
+        false
+    } else {
+        paging
+    };
     let role = if opt.follow { PagerRole::Follow } else { PagerRole::View };
     let selector = PagerSelector::new(&settings.pager);
     let start_pager = |role: PagerRole| -> Result<Option<(StartedPager, Option<OutputDelimiter>)>> {
@@ -161,6 +173,17 @@ fn run() -> Result<()> {
         return app::list_themes(&app_dirs, tags.map(|t| *t), help::Formatter::new(stdout()));
     }
 
+    let index_cache_action = match opt.index_cache {
+        Some(cli::IndexCacheAction::List) => Some(app::IndexCacheAction::List),
+        Some(cli::IndexCacheAction::Prune) => Some(app::IndexCacheAction::Prune),
+        Some(cli::IndexCacheAction::Clear) => Some(app::IndexCacheAction::Clear),
+        Some(cli::IndexCacheAction::Build) | None => None,
+    };
+    if let Some(action) = index_cache_action {
+        return app::manage_index_cache(&app_dirs, action, stdout());
+    }
+    let build_indexes = opt.index_cache == Some(cli::IndexCacheAction::Build);
+
     let theme = if use_colors {
         let theme = &opt.theme;
         Theme::load_with_overlays(&app_dirs, theme, &settings.theme_overlays)?
@@ -229,12 +252,36 @@ fn run() -> Result<()> {
         }
     }
 
+    let alert = match &opt.on_match {
+        Some(q) => Some(app::AlertOptions {
+            query: Query::parse(q)?,
+            command: opt.exec.as_deref().map(Command::parse).transpose()?,
+            bell: opt.bell || opt.exec.is_none(),
+            debounce: opt.alert_debounce,
+            interval: opt.alert_interval,
+        }),
+        None => None,
+    };
+
+    let template = match opt.format.as_ref().or(settings.formatting.template.as_ref()) {
+        Some(template) => Some(Arc::new(Template::parse(template)?)),
+        None => None,
+    };
+
+    let text_formats = settings
+        .input
+        .formats
+        .iter()
+        .map(|format| TextFormat::new(format, &settings.fields.predefined.time.names))
+        .collect::<Result<Arc<[_]>>>()?;
+
     let delimiter = match opt.delimiter {
         cli::Delimiter::Nul => Delimiter::Byte(0),
         cli::Delimiter::Lf => Delimiter::Byte(b'\n'),
         cli::Delimiter::Cr => Delimiter::Byte(b'\r'),
         cli::Delimiter::Crlf => Delimiter::Str("\r\n".into()),
         cli::Delimiter::Newline => Delimiter::Newline,
+        cli::Delimiter::Auto if opt.unwrap != cli::Unwrap::None => Delimiter::Newline,
         cli::Delimiter::Auto => match opt.input_format {
             cli::InputFormat::Auto => {
                 if opt.allow_prefix {
@@ -243,7 +290,7 @@ fn run() -> Result<()> {
                     Delimiter::PrettyCompatible
                 }
             }
-            cli::InputFormat::Logfmt => Delimiter::Newline,
+            cli::InputFormat::Logfmt | cli::InputFormat::Syslog | cli::InputFormat::AccessLog => Delimiter::Newline,
             cli::InputFormat::Json => {
                 if opt.allow_prefix {
                     Delimiter::Newline
@@ -290,18 +337,32 @@ fn run() -> Result<()> {
     let ascii = ascii_opt.resolve(utf8_is_supported);
 
     // Configure the input.
-    let mut inputs = opt
-        .files
+    let files = if build_indexes {
+        files_in_dirs(&opt.files)?
+    } else {
+        opt.files.clone()
+    };
+    let mut inputs = Vec::with_capacity(files.len());
+    let mut follow_dirs = Vec::new();
+    for x in &files {
+        if x.to_str() == Some("-") {
+            inputs.push(InputReference::Stdin);
+        } else if let Some(dir) = opt.follow.then(|| InputDir::parse(x)).flatten() {
+            follow_dirs.push(dir);
+        } else {
+            inputs.extend(InputReference::expand(x.clone())?);
+        }
+    }
+    for line in &opt.commands {
+        inputs.push(InputReference::Process(Command::parse(line)?));
+    }
+    inputs.extend(Command::new(opt.command.clone()).map(InputReference::Process));
+    let listen = opt
+        .listen
         .iter()
-        .map(|x| {
-            if x.to_str() == Some("-") {
-                Ok::<_, std::io::Error>(InputReference::Stdin)
-            } else {
-                Ok(InputReference::File(x.clone().try_into()?))
-            }
-        })
-        .collect::<std::result::Result<Vec<_>, _>>()?;
-    if inputs.is_empty() {
+        .map(|url| Endpoint::parse(url))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    if inputs.is_empty() && follow_dirs.is_empty() && listen.is_empty() {
         if stdin().is_terminal() {
             let mut cmd = cli::Opt::command();
             return cmd.print_help().map_err(Error::Io);
@@ -353,6 +414,19 @@ fn run() -> Result<()> {
         max_message_size,
         concurrency,
         filter: app::AdvancedFilter::new(filter, query).into(),
+        context: app::ContextOptions {
+            before: opt.before_context.or(opt.context).unwrap_or(0),
+            after: opt.after_context.or(opt.context).unwrap_or(0),
+        },
+        correlate_by: opt.correlate_by.clone(),
+        stats: (opt.stats || !opt.stats_field.is_empty()).then(|| app::StatsOptions {
+            fields: opt.stats_field.clone(),
+            top: opt.stats_top,
+        }),
+        histogram: opt.histogram.map(|bucket| app::HistogramOptions {
+            bucket,
+            width: terminal_size_of(stdout()).map(|(w, _)| usize::from(w.0)).unwrap_or(80),
+        }),
         fields: hl::FieldOptions {
             settings: settings.fields.clone(),
             filter: Arc::new(fields),
@@ -360,16 +434,59 @@ fn run() -> Result<()> {
         formatting: settings.formatting.clone(),
         time_zone: tz,
         hide_empty_fields,
-        sort: opt.sort,
+        sort: opt.sort || opt.reverse || opt.last.is_some(),
+        reverse: opt.reverse,
+        max_count: opt.max_count,
+        last: opt.last,
+        sort_by: opt.sort_by.as_ref().map(|key| match key.strip_prefix('-') {
+            Some(key) => app::SortBy {
+                key: key.to_owned(),
+                descending: true,
+            },
+            None => app::SortBy {
+                key: key.clone(),
+                descending: false,
+            },
+        }),
         follow: opt.follow,
+        follow_dirs,
+        listen,
+        restart: opt.restart,
+        resume: opt.resume.clone(),
+        alert,
         sync_interval: Duration::from_millis(opt.sync_interval_ms),
         input_info,
         input_format: match opt.input_format {
             cli::InputFormat::Auto => None,
             cli::InputFormat::Json => Some(app::InputFormat::Json),
             cli::InputFormat::Logfmt => Some(app::InputFormat::Logfmt),
+            cli::InputFormat::Syslog => Some(app::InputFormat::Syslog),
+            cli::InputFormat::AccessLog => Some(app::InputFormat::AccessLog),
         },
+        status_levels: settings.input.access_log.status_levels,
+        text_formats,
+        unwrap: match opt.unwrap {
+            cli::Unwrap::None => None,
+            cli::Unwrap::Cri => Some(Wrapper::Cri),
+            cli::Unwrap::Docker => Some(Wrapper::Docker),
+        },
+        embedded_json: match opt.expand_embedded_json {
+            None => EmbeddedJsonExpansion::Disabled,
+            Some(keys) if keys.is_empty() => EmbeddedJsonExpansion::All,
+            Some(keys) => EmbeddedJsonExpansion::Fields(keys),
+        },
+        output_format: match opt.output_format {
+            cli::OutputFormat::Text => None,
+            cli::OutputFormat::Json => Some(app::OutputFormat::Json),
+            cli::OutputFormat::Logfmt => Some(app::OutputFormat::Logfmt),
+        },
+        template,
         dump_index: opt.dump_index,
+        indexed_fields: settings.index.fields.clone(),
+        index_cache_limits: IndexCacheLimits {
+            max_size: settings.index.max_cache_size,
+            max_age: settings.index.max_cache_age,
+        },
         app_dirs: Some(app_dirs),
         tail: opt.tail,
         delimiter,
@@ -399,7 +516,11 @@ fn run() -> Result<()> {
     log::debug!("run the app");
 
     // Run the app.
-    let run = || match app.run(inputs, output.as_mut()) {
+    let run = || match if build_indexes {
+        app.build_indexes(inputs, output.as_mut())
+    } else {
+        app.run(inputs, output.as_mut())
+    } {
         Ok(()) => Ok(()),
         Err(Error::Io(ref e)) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
         Err(err) => Err(err),
@@ -415,10 +536,38 @@ fn run() -> Result<()> {
     SignalHandler::run(interrupt_ignore_count, std::time::Duration::from_secs(1), run)
 }
 
+/// Replaces directories with the files found in them recursively.
+fn files_in_dirs(paths: &[PathBuf]) -> std::io::Result<Vec<PathBuf>> {
+    let mut result = Vec::new();
+    collect_files(paths, &mut HashSet::new(), &mut result)?;
+    Ok(result)
+}
+
+/// Collects the files and files in the directories recursively.
+///
+/// Directories already visited are skipped, so symbolic links to parent directories do not cause endless recursion.
+fn collect_files(paths: &[PathBuf], visited: &mut HashSet<PathBuf>, result: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for path in paths {
+        if !path.is_dir() {
+            result.push(path.clone());
+            continue;
+        }
+        if !visited.insert(std::fs::canonicalize(path)?) {
+            continue;
+        }
+        let mut items = std::fs::read_dir(path)?
+            .map(|item| item.map(|item| item.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        items.sort();
+        collect_files(&items, visited, result)?;
+    }
+    Ok(())
+}
+
 fn main() {
     if let Err(err) = run() {
         err.log(&AppInfo);
-        process::exit(1);
+        process::exit(err.exit_code());
     }
 }
 
@@ -25,11 +25,15 @@ use serde_logfmt::logfmt;
 
 // local imports
 use crate::{
+    accesslog,
     app::{InputFormat, UnixTimestampUnit},
+    container::Envelope,
     error::{Error, Result},
     level::{self},
     serdex::StreamDeserializerWithOffsets,
-    settings::PredefinedFields,
+    settings::{PredefinedFields, StatusLevels},
+    syslog,
+    textformat::TextFormat,
     timestamp::Timestamp,
     types::FieldKind,
 };
@@ -433,6 +437,12 @@ impl<'r, 's> RecordWithSourceConstructor<'r, 's> for Record<'s> {
 pub trait RecordFilter {
     fn apply<'a>(&self, record: &Record<'a>) -> bool;
 
+    /// Returns false if none of the records in a group can match, judging by the field values the group may have.
+    #[inline]
+    fn may_match(&self, _: &dyn FieldMembership) -> bool {
+        true
+    }
+
     #[inline]
     fn and<F>(self, rhs: F) -> RecordFilterAnd<Self, F>
     where
@@ -457,6 +467,11 @@ impl<T: RecordFilter + ?Sized> RecordFilter for Box<T> {
     fn apply<'a>(&self, record: &Record<'a>) -> bool {
         (**self).apply(record)
     }
+
+    #[inline]
+    fn may_match(&self, fields: &dyn FieldMembership) -> bool {
+        (**self).may_match(fields)
+    }
 }
 
 impl<T: RecordFilter + ?Sized> RecordFilter for Arc<T> {
@@ -464,6 +479,11 @@ impl<T: RecordFilter + ?Sized> RecordFilter for Arc<T> {
     fn apply<'a>(&self, record: &Record<'a>) -> bool {
         (**self).apply(record)
     }
+
+    #[inline]
+    fn may_match(&self, fields: &dyn FieldMembership) -> bool {
+        (**self).may_match(fields)
+    }
 }
 
 impl<T: RecordFilter> RecordFilter for &T {
@@ -471,6 +491,11 @@ impl<T: RecordFilter> RecordFilter for &T {
     fn apply<'a>(&self, record: &Record<'a>) -> bool {
         (**self).apply(record)
     }
+
+    #[inline]
+    fn may_match(&self, fields: &dyn FieldMembership) -> bool {
+        (**self).may_match(fields)
+    }
 }
 
 impl RecordFilter for Level {
@@ -489,6 +514,19 @@ impl<T: RecordFilter> RecordFilter for Option<T> {
             true
         }
     }
+
+    #[inline]
+    fn may_match(&self, fields: &dyn FieldMembership) -> bool {
+        self.as_ref().is_none_or(|filter| filter.may_match(fields))
+    }
+}
+
+// ---
+
+/// FieldMembership tells which field values a group of records, like a block of a file, may have.
+pub trait FieldMembership {
+    /// Returns false if none of the records has a field with the given normalized key and the given value.
+    fn may_contain(&self, key: &str, value: &str) -> bool;
 }
 
 // ---
@@ -503,6 +541,11 @@ impl<L: RecordFilter, R: RecordFilter> RecordFilter for RecordFilterAnd<L, R> {
     fn apply<'a>(&self, record: &Record<'a>) -> bool {
         self.lhs.apply(record) && self.rhs.apply(record)
     }
+
+    #[inline]
+    fn may_match(&self, fields: &dyn FieldMembership) -> bool {
+        self.lhs.may_match(fields) && self.rhs.may_match(fields)
+    }
 }
 
 // ---
@@ -517,6 +560,11 @@ impl<L: RecordFilter, R: RecordFilter> RecordFilter for RecordFilterOr<L, R> {
     fn apply<'a>(&self, record: &Record<'a>) -> bool {
         self.lhs.apply(record) || self.rhs.apply(record)
     }
+
+    #[inline]
+    fn may_match(&self, fields: &dyn FieldMembership) -> bool {
+        self.lhs.may_match(fields) || self.rhs.may_match(fields)
+    }
 }
 
 // ---
@@ -886,10 +934,12 @@ impl Parser {
 
     #[inline]
     pub fn parse<'a>(&self, record: &RawRecord<'a>) -> Record<'a> {
+        let header = &record.header;
         let fields = record.fields();
         let count = fields.size_hint().1.unwrap_or(0);
         let mut record = Record::<'a>::with_capacity(count);
 
+        header.apply(&mut record);
         self.settings.apply_each(fields, &mut record);
 
         record
@@ -901,6 +951,7 @@ impl Parser {
 #[derive(Default)]
 pub struct RawRecord<'a> {
     fields: RawRecordFields<'a>,
+    header: RawRecordHeader<'a>,
 }
 
 impl<'a> RawRecord<'a> {
@@ -910,7 +961,7 @@ impl<'a> RawRecord<'a> {
     }
 
     #[inline]
-    pub fn parser() -> RawRecordParser {
+    pub fn parser() -> RawRecordParser<'static> {
         RawRecordParser::new()
     }
 }
@@ -938,29 +989,130 @@ impl<'a> Deserialize<'a> for RawRecord<'a> {
 
 // ---
 
+/// Values of predefined fields provided by the input format outside of record fields, like in syslog messages.
+///
+/// Record fields mapped to the same predefined fields take precedence over these values.
+#[derive(Default, Debug)]
+pub struct RawRecordHeader<'a> {
+    pub ts: Option<Timestamp<'a>>,
+    pub level: Option<Level>,
+    pub logger: Option<&'a str>,
+    pub message: Option<RawValue<'a>>,
+}
+
+impl<'a> RawRecordHeader<'a> {
+    #[inline]
+    fn apply(&self, to: &mut Record<'a>) {
+        to.ts = self.ts.clone();
+        to.level = self.level;
+        to.logger = self.logger;
+        to.message = self.message;
+    }
+}
+
+// ---
+
 pub type RawRecordFields<'a> = ObjectFields<'a, RAW_RECORD_FIELDS_CAPACITY>;
 
 type ObjectFields<'a, const N: usize> = heapopt::Vec<(&'a str, RawValue<'a>), N>;
 
 // ---
 
-pub struct RawRecordParser {
+/// Defines which string fields of raw records are checked for embedded JSON objects and arrays.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EmbeddedJsonExpansion {
+    #[default]
+    Disabled,
+    All,
+    Fields(Vec<String>),
+}
+
+impl EmbeddedJsonExpansion {
+    /// Replaces string values containing valid JSON objects or arrays with the parsed values,
+    /// so that they are formatted as nested objects and their sub-fields can be addressed by filters.
+    ///
+    /// Decoded strings are stored in the buffer, which has to outlive the resulting record.
+    pub fn expand<'a: 'b, 'b>(&self, record: RawRecord<'a>, buf: &'b mut Vec<u8>) -> RawRecord<'b> {
+        if *self == Self::Disabled {
+            return record;
+        }
+
+        buf.clear();
+        let mut found = heapopt::Vec::<(usize, Range<usize>), 8>::new();
+        for (i, (key, value)) in record.fields.iter().enumerate() {
+            match value {
+                RawValue::String(value) if self.includes(key) && Self::looks_like_json(value) => {
+                    let begin = buf.len();
+                    if value.decode(&mut *buf).is_ok() {
+                        found.push((i, begin..buf.len()));
+                    } else {
+                        buf.truncate(begin);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let buf: &'b Vec<u8> = buf;
+        let mut record: RawRecord<'b> = record;
+        for (i, range) in found.iter() {
+            let value = std::str::from_utf8(&buf[range.clone()])
+                .ok()
+                .and_then(|s| json::from_str::<&json::value::RawValue>(s.trim()).ok());
+            if let Some(value) = value {
+                record.fields[*i].1 = value.into();
+            }
+        }
+
+        record
+    }
+
+    #[inline]
+    fn includes(&self, key: &str) -> bool {
+        match self {
+            Self::Disabled => false,
+            Self::All => true,
+            Self::Fields(keys) => keys
+                .iter()
+                .any(|k| matches!(KeyMatcher::new(k).match_key(key), Some(KeyMatch::Full))),
+        }
+    }
+
+    #[inline]
+    fn looks_like_json(value: &EncodedString) -> bool {
+        let s = match value {
+            EncodedString::Json(_) => &value.source()[1..],
+            EncodedString::Raw(_) => value.source(),
+        };
+        s.trim_start().starts_with(['{', '['])
+    }
+}
+
+// ---
+
+pub struct RawRecordParser<'f> {
     allow_prefix: bool,
     format: Option<InputFormat>,
+    status_levels: StatusLevels,
+    text_formats: &'f [TextFormat],
+    wrapped: bool,
 }
 
-impl Default for RawRecordParser {
+impl Default for RawRecordParser<'_> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl RawRecordParser {
+impl<'f> RawRecordParser<'f> {
     #[inline]
     pub fn new() -> Self {
         Self {
             allow_prefix: false,
             format: None,
+            status_levels: StatusLevels::default(),
+            text_formats: &[],
+            wrapped: false,
         }
     }
 
@@ -977,11 +1129,44 @@ impl RawRecordParser {
         Self { format, ..self }
     }
 
+    #[inline]
+    pub fn status_levels(self, status_levels: StatusLevels) -> Self {
+        Self { status_levels, ..self }
+    }
+
+    /// Sets user-defined formats to try for lines that are not valid logfmt when the input format is detected automatically.
+    #[inline]
+    pub fn text_formats(self, text_formats: &'f [TextFormat]) -> Self {
+        Self { text_formats, ..self }
+    }
+
+    /// Enables parsing of lines in CRI format produced by [`crate::container::Unwrapper`].
+    ///
+    /// The payload of such lines is parsed in the configured input format, and lines in other formats are parsed as is.
+    #[inline]
+    pub fn wrapped(self, wrapped: bool) -> Self {
+        Self { wrapped, ..self }
+    }
+
     #[inline]
     pub fn parse<'a>(
         &self,
         chunk: &'a [u8],
-    ) -> RawRecordStream<impl RawRecordIterator<'a> + use<'a>, impl RawRecordIterator<'a> + use<'a>> {
+    ) -> RawRecordStream<
+        impl RawRecordIterator<'a> + use<'a>,
+        impl RawRecordIterator<'a> + use<'a>,
+        impl RawRecordIterator<'a> + use<'a>,
+        impl RawRecordIterator<'a> + use<'a>,
+    >
+    where
+        'f: 'a,
+    {
+        let envelope = if self.wrapped { Envelope::parse(chunk) } else { None };
+        let chunk = match &envelope {
+            Some(envelope) => envelope.payload,
+            None => chunk,
+        };
+
         let prefix = if self.allow_prefix && chunk.last() == Some(&b'}') {
             memchr::memchr(b'{', chunk).map_or(chunk, |pos| &chunk[..pos])
         } else {
@@ -996,6 +1181,8 @@ impl RawRecordParser {
                 None
             } else if data[0] == b'{' {
                 Some(InputFormat::Json)
+            } else if data[0] == b'<' && data.get(1).is_some_and(|b| b.is_ascii_digit()) {
+                Some(InputFormat::Syslog)
             } else {
                 Some(InputFormat::Logfmt)
             }
@@ -1003,18 +1190,37 @@ impl RawRecordParser {
 
         match format {
             None => RawRecordStream::Empty,
-            Some(InputFormat::Json) => RawRecordStream::Json(RawRecordJsonStream {
-                prefix,
-                xn,
-                delegate: StreamDeserializerWithOffsets {
-                    inner: json::Deserializer::from_slice(data).into_iter::<RawRecord>(),
-                    source: data,
+            Some(InputFormat::Json) => RawRecordStream::Json(RawRecordEnvelopedStream {
+                inner: RawRecordJsonStream {
+                    prefix,
+                    xn,
+                    delegate: StreamDeserializerWithOffsets {
+                        inner: json::Deserializer::from_slice(data).into_iter::<RawRecord>(),
+                        source: data,
+                    },
                 },
+                envelope,
             }),
-            Some(InputFormat::Logfmt) => RawRecordStream::Logfmt(RawRecordLogfmtStream {
-                chunk,
-                prefix,
-                done: false,
+            Some(InputFormat::Logfmt) => RawRecordStream::Logfmt(RawRecordEnvelopedStream {
+                inner: RawRecordLogfmtStream {
+                    chunk,
+                    prefix,
+                    fallback: if self.format.is_none() { self.text_formats } else { &[] },
+                    done: false,
+                },
+                envelope,
+            }),
+            Some(InputFormat::Syslog) => RawRecordStream::Syslog(RawRecordEnvelopedStream {
+                inner: RawRecordSyslogStream { chunk, done: false },
+                envelope,
+            }),
+            Some(InputFormat::AccessLog) => RawRecordStream::AccessLog(RawRecordEnvelopedStream {
+                inner: RawRecordAccessLogStream {
+                    chunk,
+                    status_levels: self.status_levels,
+                    done: false,
+                },
+                envelope,
             }),
         }
     }
@@ -1023,16 +1229,20 @@ impl RawRecordParser {
 // ---
 
 #[derive(Debug)]
-pub enum RawRecordStream<Json, Logfmt> {
+pub enum RawRecordStream<Json, Logfmt, Syslog, AccessLog> {
     Empty,
     Json(Json),
     Logfmt(Logfmt),
+    Syslog(Syslog),
+    AccessLog(AccessLog),
 }
 
-impl<'a, Json, Logfmt> RawRecordStream<Json, Logfmt>
+impl<'a, Json, Logfmt, Syslog, AccessLog> RawRecordStream<Json, Logfmt, Syslog, AccessLog>
 where
     Json: RawRecordIterator<'a>,
     Logfmt: RawRecordIterator<'a>,
+    Syslog: RawRecordIterator<'a>,
+    AccessLog: RawRecordIterator<'a>,
 {
     #[inline]
     pub fn next(&mut self) -> Option<Result<AnnotatedRawRecord<'a>>> {
@@ -1040,6 +1250,8 @@ where
             Self::Empty => None,
             Self::Json(stream) => stream.next(),
             Self::Logfmt(stream) => stream.next(),
+            Self::Syslog(stream) => stream.next(),
+            Self::AccessLog(stream) => stream.next(),
         }
     }
 
@@ -1069,6 +1281,37 @@ pub struct AnnotatedRawRecord<'a> {
 
 // ---
 
+/// Adds the stream name and the fallback timestamp from the envelope to the records of the payload, if any.
+struct RawRecordEnvelopedStream<'a, S> {
+    inner: S,
+    envelope: Option<Envelope<'a>>,
+}
+
+impl<'a, S: RawRecordIterator<'a>> RawRecordIterator<'a> for RawRecordEnvelopedStream<'a, S> {
+    #[inline]
+    fn next(&mut self) -> Option<Result<AnnotatedRawRecord<'a>>> {
+        let item = self.inner.next();
+        let Some(envelope) = &self.envelope else {
+            return item;
+        };
+
+        item.map(|item| {
+            item.map(|mut ar| {
+                let header = &mut ar.record.header;
+                if header.ts.is_none() {
+                    header.ts = Some(Timestamp::new(envelope.time));
+                }
+                let stream = RawValue::String(EncodedString::raw(envelope.stream));
+                ar.record.fields.push(("stream", stream));
+                ar.offsets = ar.offsets.start + envelope.offset..ar.offsets.end + envelope.offset;
+                ar
+            })
+        })
+    }
+}
+
+// ---
+
 struct RawRecordJsonStream<'a, R> {
     prefix: &'a [u8],
     xn: usize,
@@ -1100,9 +1343,25 @@ where
 struct RawRecordLogfmtStream<'a> {
     chunk: &'a [u8],
     prefix: &'a [u8],
+    fallback: &'a [TextFormat],
     done: bool,
 }
 
+impl<'a> RawRecordLogfmtStream<'a> {
+    /// Parses the chunk using the first matching fallback format.
+    fn parse_fallback(&self) -> Option<RawRecord<'a>> {
+        let line = std::str::from_utf8(self.chunk).ok()?.trim_end_matches(['\r', '\n']);
+        let (ts, fields) = self.fallback.iter().find_map(|format| format.parse(line))?;
+        Some(RawRecord {
+            fields,
+            header: RawRecordHeader {
+                ts,
+                ..Default::default()
+            },
+        })
+    }
+}
+
 impl<'a> RawRecordIterator<'a> for RawRecordLogfmtStream<'a> {
     #[inline]
     fn next(&mut self) -> Option<Result<AnnotatedRawRecord<'a>>> {
@@ -1117,13 +1376,171 @@ impl<'a> RawRecordIterator<'a> for RawRecordLogfmtStream<'a> {
                 record: record.0,
                 offsets: 0..self.chunk.len(),
             })),
-            Err(err) => Some(Err(err.into())),
+            Err(err) => match self.parse_fallback() {
+                Some(record) => Some(Ok(AnnotatedRawRecord {
+                    prefix: b"",
+                    record,
+                    offsets: 0..self.chunk.len(),
+                })),
+                None => Some(Err(err.into())),
+            },
         }
     }
 }
 
 // ---
 
+struct RawRecordSyslogStream<'a> {
+    chunk: &'a [u8],
+    done: bool,
+}
+
+impl<'a> RawRecordSyslogStream<'a> {
+    fn parse(chunk: &'a [u8]) -> Result<RawRecord<'a>> {
+        let message = syslog::Message::parse(chunk)?;
+
+        let mut header = RawRecordHeader {
+            ts: message.timestamp.map(|ts| match ts {
+                syslog::Timestamp::Rfc3339(raw) => Timestamp::new(raw),
+                syslog::Timestamp::Bsd { raw, .. } => match ts.resolve(&*syslog::NOW) {
+                    Some(value) => Timestamp::with_value(raw, value),
+                    None => Timestamp::new(raw),
+                },
+            }),
+            level: message.level(),
+            logger: message.app_name,
+            message: None,
+        };
+
+        let mut fields = RawRecordFields::default();
+        if let Some(hostname) = message.hostname {
+            fields.push(("host", RawValue::String(EncodedString::raw(hostname))));
+        }
+        if let Some(proc_id) = message.proc_id {
+            fields.push(("pid", RawValue::auto(proc_id)));
+        }
+        if let Some(msg_id) = message.msg_id {
+            fields.push(("msgid", RawValue::String(EncodedString::raw(msg_id))));
+        }
+        for param in &message.params {
+            let value = match param.escaping {
+                syslog::Escaping::JsonCompatible => EncodedString::json(param.quoted),
+                _ => EncodedString::raw(param.value()),
+            };
+            fields.push((param.name, RawValue::String(value)));
+        }
+
+        match Self::parse_msg(message.msg) {
+            Some(msg_fields) => fields.extend(msg_fields.iter().copied()),
+            None if !message.msg.is_empty() => {
+                header.message = Some(RawValue::String(EncodedString::raw(message.msg)));
+            }
+            None => {}
+        }
+
+        Ok(RawRecord { fields, header })
+    }
+
+    /// Parses the message part of a syslog message as a JSON or logfmt record if possible.
+    fn parse_msg(msg: &'a str) -> Option<RawRecordFields<'a>> {
+        let record = if msg.starts_with('{') {
+            json::from_str::<RawRecord>(msg).ok()?
+        } else if msg.contains('=') {
+            logfmt::from_str::<LogfmtRawRecord>(msg).ok()?.0
+        } else {
+            return None;
+        };
+        Some(record.fields)
+    }
+}
+
+impl<'a> RawRecordIterator<'a> for RawRecordSyslogStream<'a> {
+    #[inline]
+    fn next(&mut self) -> Option<Result<AnnotatedRawRecord<'a>>> {
+        if self.done {
+            return None;
+        }
+
+        self.done = true;
+        Some(Self::parse(self.chunk).map(|record| AnnotatedRawRecord {
+            prefix: b"",
+            record,
+            offsets: 0..self.chunk.len(),
+        }))
+    }
+}
+
+// ---
+
+struct RawRecordAccessLogStream<'a> {
+    chunk: &'a [u8],
+    status_levels: StatusLevels,
+    done: bool,
+}
+
+impl<'a> RawRecordAccessLogStream<'a> {
+    fn parse(&self) -> Result<RawRecord<'a>> {
+        let entry = accesslog::Entry::parse(self.chunk)?;
+        let raw = |value: &'a str| RawValue::String(EncodedString::raw(value));
+
+        let header = RawRecordHeader {
+            ts: Some(Timestamp::with_value(entry.timestamp, entry.time)),
+            level: self.status_levels.level(entry.status_code()),
+            ..Default::default()
+        };
+
+        let mut fields = RawRecordFields::default();
+        fields.push(("remote_addr", raw(entry.remote_addr)));
+        if let Some(user) = entry.user {
+            fields.push(("remote_user", raw(user)));
+        }
+        match entry.request_parts() {
+            Some((method, path, protocol)) => {
+                fields.push(("method", raw(method)));
+                fields.push(("path", raw(path)));
+                if let Some(protocol) = protocol {
+                    fields.push(("protocol", raw(protocol)));
+                }
+            }
+            None => {
+                if let Some(request) = entry.request {
+                    fields.push(("request", raw(request)));
+                }
+            }
+        }
+        fields.push(("status", RawValue::Number(entry.status)));
+        if let Some(bytes) = entry.bytes {
+            fields.push(("bytes", RawValue::Number(bytes)));
+        }
+        if let Some(referer) = entry.referer {
+            fields.push(("referer", raw(referer)));
+        }
+        if let Some(user_agent) = entry.user_agent {
+            fields.push(("user_agent", raw(user_agent)));
+        }
+
+        Ok(RawRecord { fields, header })
+    }
+}
+
+impl<'a> RawRecordIterator<'a> for RawRecordAccessLogStream<'a> {
+    #[inline]
+    fn next(&mut self) -> Option<Result<AnnotatedRawRecord<'a>>> {
+        if self.done {
+            return None;
+        }
+
+        self.done = true;
+        Some(self.parse().map(|record| AnnotatedRawRecord {
+            prefix: b"",
+            record,
+            offsets: 0..self.chunk.len(),
+        }))
+    }
+}
+
+// ---
+
 struct ObjectVisitor<'a, 't, RV, const N: usize>
 where
     RV: ?Sized + 'a,
@@ -1433,12 +1850,16 @@ impl FieldFilterKey<&str> {
     }
 
     pub fn parse(text: &str) -> Result<FieldFilterKey<&str>> {
-        Ok(match text {
+        Ok(FieldFilterKey::new(text))
+    }
+
+    pub fn new(text: &str) -> FieldFilterKey<&str> {
+        match text {
             "message" | "msg" => FieldFilterKey::Predefined(FieldKind::Message),
             "logger" => FieldFilterKey::Predefined(FieldKind::Logger),
             "caller" => FieldFilterKey::Predefined(FieldKind::Caller),
             _ => FieldFilterKey::Custom(text.trim_start_matches('.')),
-        })
+        }
     }
 }
 
@@ -1673,6 +2094,189 @@ impl RecordFilter for FieldFilter {
             }
         }
     }
+
+    fn may_match(&self, fields: &dyn FieldMembership) -> bool {
+        // negated filters and filters including records without the field match records not having the value
+        if !self.flags.is_empty() || !self.flat_key {
+            return true;
+        }
+        let FieldFilterKey::Custom(key) = &self.key else {
+            return true;
+        };
+        match &self.match_policy {
+            ValueMatchPolicy::Exact(value) => fields.may_contain(key, value),
+            ValueMatchPolicy::In(values) => values.iter().any(|value| fields.may_contain(key, value)),
+            _ => true,
+        }
+    }
+}
+
+// ---
+
+/// Extracts a value of a field addressed by a key in the same way as in field filters.
+pub struct FieldValueExtractor {
+    key: FieldFilterKey<String>,
+    flat_key: bool,
+}
+
+impl FieldValueExtractor {
+    pub fn new(key: &str) -> Self {
+        let key = FieldFilterKey::new(key);
+        Self {
+            flat_key: match key {
+                FieldFilterKey::Predefined(_) => true,
+                FieldFilterKey::Custom(key) => !key.contains('.'),
+            },
+            key: match key {
+                FieldFilterKey::Predefined(kind) => FieldFilterKey::Predefined(kind),
+                FieldFilterKey::Custom(key) => FieldFilterKey::Custom(key.chars().map(KeyMatcher::norm).collect()),
+            },
+        }
+    }
+
+    /// Returns the value of the field in the record, string values are decoded.
+    pub fn extract<'a>(&self, record: &Record<'a>) -> Option<String> {
+        match &self.key {
+            FieldFilterKey::Predefined(kind) => match kind {
+                FieldKind::Time => record.ts.as_ref().map(|ts| ts.raw().to_owned()),
+                FieldKind::Logger => record.logger.map(|logger| logger.to_owned()),
+                FieldKind::Caller => (!record.caller.name.is_empty()).then(|| record.caller.name.to_owned()),
+                _ => self.extract_raw(record).map(Self::value),
+            },
+            FieldFilterKey::Custom(_) => self.extract_raw(record).map(Self::value),
+        }
+    }
+
+    /// Returns the raw value of the field in the record.
+    ///
+    /// Predefined fields other than the message are not supported and yield `None`.
+    pub fn extract_raw<'a>(&self, record: &Record<'a>) -> Option<RawValue<'a>> {
+        match &self.key {
+            FieldFilterKey::Predefined(FieldKind::Message) => record.message,
+            FieldFilterKey::Predefined(_) => None,
+            FieldFilterKey::Custom(key) => {
+                let matcher = KeyMatcher::new(key);
+                for (k, v) in record.fields_for_search() {
+                    if self.flat_key && key.len() != k.len() {
+                        continue;
+                    }
+                    match matcher.match_key(k) {
+                        None => {}
+                        Some(KeyMatch::Full) => return Some(*v),
+                        Some(KeyMatch::Partial(subkey)) => {
+                            if let Some(value) = Self::extract_partial(subkey, *v) {
+                                return Some(value);
+                            }
+                        }
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    fn extract_partial<'a>(subkey: KeyMatcher, value: RawValue<'a>) -> Option<RawValue<'a>> {
+        match value {
+            RawValue::Object(value) => {
+                let mut item = Object::default();
+                value.parse_into(&mut item).ok();
+                for (k, v) in item.fields.iter() {
+                    match subkey.match_key(k) {
+                        None => continue,
+                        Some(KeyMatch::Full) => return Some(*v),
+                        Some(KeyMatch::Partial(subkey)) => return Self::extract_partial(subkey, *v),
+                    }
+                }
+                None
+            }
+            RawValue::Array(value) => {
+                let (IndexMatcher::Exact(idx), tail) = subkey.index_matcher()? else {
+                    return None;
+                };
+                let value = value.parse::<128>().ok()?;
+                let item = *value.items.get(idx)?;
+                match tail {
+                    Some(tail) => Self::extract_partial(tail, item),
+                    None => Some(item),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn value(value: RawValue) -> String {
+        match value {
+            RawValue::String(_) => {
+                let mut buf = Vec::new();
+                value.format_readable(&mut buf);
+                String::from_utf8_lossy(&buf).into_owned()
+            }
+            _ => value.raw_str().to_owned(),
+        }
+    }
+}
+
+// ---
+
+/// IndexedFields selects values of fields to be indexed, so that groups of records without the value
+/// an exact field filter looks for can be skipped.
+///
+/// Only flat custom fields can be indexed, their keys are normalized in the same way as keys of field filters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexedFields {
+    keys: Vec<String>,
+}
+
+impl IndexedFields {
+    pub fn new<T: AsRef<str>, I: IntoIterator<Item = T>>(keys: I) -> Self {
+        let mut keys = keys
+            .into_iter()
+            .filter_map(|key| match FieldFilterKey::new(key.as_ref()) {
+                FieldFilterKey::Custom(key) if !key.contains('.') => Some(key.chars().map(KeyMatcher::norm).collect()),
+                _ => None,
+            })
+            .collect::<Vec<String>>();
+        keys.sort();
+        keys.dedup();
+        Self { keys }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns normalized keys of the indexed fields.
+    #[inline]
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Returns true if the field with the given normalized key is indexed.
+    #[inline]
+    pub fn contains(&self, key: &str) -> bool {
+        self.keys.binary_search_by(|k| k.as_str().cmp(key)).is_ok()
+    }
+
+    /// Calls the function with the normalized key and the value of each indexed field of the record,
+    /// the value being in the same form as exact field filters compare it.
+    pub fn for_each<'a>(&self, record: &Record<'a>, mut f: impl FnMut(&str, &str)) {
+        if self.keys.is_empty() {
+            return;
+        }
+        for (k, v) in record.fields_for_search() {
+            for key in &self.keys {
+                if key.len() != k.len() || !matches!(KeyMatcher::new(key).match_key(k), Some(KeyMatch::Full)) {
+                    continue;
+                }
+                let s = v.raw_str();
+                match s.starts_with('"').then(|| json::from_str::<String>(s).ok()).flatten() {
+                    Some(value) => f(key, &value),
+                    None => f(key, s),
+                }
+            }
+        }
+    }
 }
 
 // ---
@@ -1695,6 +2299,11 @@ impl RecordFilter for FieldFilterSet {
     fn apply<'a>(&self, record: &Record<'a>) -> bool {
         self.0.iter().all(|field| field.apply(record))
     }
+
+    #[inline]
+    fn may_match(&self, fields: &dyn FieldMembership) -> bool {
+        self.0.iter().all(|field| field.may_match(fields))
+    }
 }
 
 // ---
@@ -1747,6 +2356,11 @@ impl RecordFilter for Filter {
 
         true
     }
+
+    #[inline]
+    fn may_match(&self, fields: &dyn FieldMembership) -> bool {
+        self.fields.may_match(fields)
+    }
 }
 
 // ---
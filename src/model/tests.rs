@@ -5,7 +5,7 @@ use maplit::hashmap;
 use rstest::rstest;
 use serde_logfmt::logfmt;
 
-use crate::settings::{Field, FieldShowOption};
+use crate::settings::{Field, FieldShowOption, TextInputFormat};
 
 #[test]
 fn test_raw_record_parser_empty_line() {
@@ -52,7 +52,9 @@ fn test_raw_record_parser_default() {
 
     // Verify they both parse empty JSON correctly
     // Use a helper function to check discriminant without requiring Debug on inner types
-    fn is_json_stream<Json, Logfmt>(stream: &RawRecordStream<Json, Logfmt>) -> bool {
+    fn is_json_stream<Json, Logfmt, Syslog, AccessLog>(
+        stream: &RawRecordStream<Json, Logfmt, Syslog, AccessLog>,
+    ) -> bool {
         matches!(stream, RawRecordStream::Json(_))
     }
 
@@ -854,6 +856,21 @@ fn test_field_filterlogger(#[case] raw_filter: &str, #[case] input: &str, #[case
     );
 }
 
+#[rstest]
+#[case("trace", r#"{"trace":"a\"b"}"#, Some("a\"b"))]
+#[case("trace", r#"{"trace":42}"#, Some("42"))]
+#[case("trace", r#"{"span":"a"}"#, None)]
+#[case("trace_id", r#"{"trace-id":"a"}"#, Some("a"))]
+#[case("span.trace", r#"{"span":{"trace":"a"}}"#, Some("a"))]
+#[case("spans.[1].trace", r#"{"spans":[{"trace":"a"},{"trace":"b"}]}"#, Some("b"))]
+#[case("logger", r#"{"logger":"x"}"#, Some("x"))]
+#[case("msg", "msg=text", Some("text"))]
+fn test_field_value_extractor(#[case] key: &str, #[case] input: &str, #[case] expected: Option<&str>) {
+    let extractor = FieldValueExtractor::new(key);
+    let record = parse(input);
+    assert_eq!(extractor.extract(&record).as_deref(), expected);
+}
+
 #[test]
 fn test_wrong_field_filter() {
     let result = FieldFilter::parse("xx");
@@ -938,3 +955,334 @@ fn test_object_parser_invalid_type() {
     let result = obj.parse();
     assert!(matches!(result, Err(Error::JsonParseError(_))));
 }
+
+#[test]
+fn test_syslog_rfc5424() {
+    let record = parse(concat!(
+        r#"<11>1 2024-01-25T18:00:01.123Z host1 app - ID47 "#,
+        r#"[exampleSDID@32473 iut="3" src="a \"b\""][meta seq="\]"] request failed"#,
+    ));
+    assert_eq!(record.ts.as_ref().map(|ts| ts.raw()), Some("2024-01-25T18:00:01.123Z"));
+    assert_eq!(record.level, Some(Level::Error));
+    assert_eq!(record.logger, Some("app"));
+    assert_eq!(
+        record.message,
+        Some(RawValue::String(EncodedString::raw("request failed")))
+    );
+    let fields = record.fields().copied().collect::<Vec<_>>();
+    assert_eq!(
+        fields,
+        vec![
+            ("host", RawValue::String(EncodedString::raw("host1"))),
+            ("msgid", RawValue::String(EncodedString::raw("ID47"))),
+            ("iut", RawValue::String(EncodedString::raw("3"))),
+            ("src", RawValue::String(EncodedString::json(r#""a \"b\"""#))),
+            ("seq", RawValue::String(EncodedString::raw(r#"\]"#))),
+        ]
+    );
+}
+
+#[test]
+fn test_syslog_rfc3164() {
+    let record = parse("<30>Jan  5 18:00:01 host1 sshd[123]: Connection closed");
+    let ts = record.ts.as_ref().unwrap();
+    assert_eq!(ts.raw(), "Jan  5 18:00:01");
+    let ts = ts.parse().unwrap().with_timezone(&chrono::Local);
+    assert_eq!(ts.format("%m-%d %T").to_string(), "01-05 18:00:01");
+    assert_eq!(record.level, Some(Level::Info));
+    assert_eq!(record.logger, Some("sshd"));
+    assert_eq!(
+        record.message,
+        Some(RawValue::String(EncodedString::raw("Connection closed")))
+    );
+    let fields = record.fields().copied().collect::<Vec<_>>();
+    assert_eq!(
+        fields,
+        vec![
+            ("host", RawValue::String(EncodedString::raw("host1"))),
+            ("pid", RawValue::Number("123")),
+        ]
+    );
+}
+
+#[test]
+fn test_syslog_structured_msg() {
+    let parser = Parser::new(ParserSettings::default());
+    let record = RawRecord::parser()
+        .format(Some(InputFormat::Syslog))
+        .parse(br#"2024-01-25T18:00:01Z host1 app: {"level":"debug","msg":"m1","x":1}"#)
+        .next()
+        .unwrap()
+        .unwrap();
+    let record = parser.parse(&record.record);
+    assert_eq!(record.level, Some(Level::Debug));
+    assert_eq!(record.logger, Some("app"));
+    assert_eq!(record.message.map(|m| m.raw_str()), Some(r#""m1""#));
+    assert_eq!(record.fields().map(|(k, _)| *k).collect::<Vec<_>>(), vec!["host", "x"]);
+
+    let record = RawRecord::parser()
+        .format(Some(InputFormat::Syslog))
+        .parse(b"Jan 25 18:00:01 host1 app: status=ok x=\"a b\"")
+        .next()
+        .unwrap()
+        .unwrap();
+    let record = parser.parse(&record.record);
+    assert_eq!(record.message, None);
+    assert_eq!(
+        record.fields().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec!["host", "status", "x"]
+    );
+
+    let mut stream = RawRecord::parser()
+        .format(Some(InputFormat::Syslog))
+        .parse(b"not a syslog message");
+    assert!(matches!(
+        stream.next(),
+        Some(Err(Error::SyslogParseError(syslog::Error::MissingHeader)))
+    ));
+}
+
+#[test]
+fn test_access_log() {
+    let parser = Parser::new(ParserSettings::default());
+    let status_levels = StatusLevels {
+        client_error: Some(Level::Warning),
+        server_error: Some(Level::Error),
+        ..Default::default()
+    };
+    let parse = |line: &'static [u8]| {
+        RawRecord::parser()
+            .format(Some(InputFormat::AccessLog))
+            .status_levels(status_levels)
+            .parse(line)
+            .next()
+            .unwrap()
+    };
+
+    let record =
+        parse(br#"10.0.0.1 - frank [25/Jan/2024:18:00:01 +0000] "GET /api/items?id=1 HTTP/1.1" 503 12 "-" "curl/8.0""#)
+            .unwrap();
+    let record = parser.parse(&record.record);
+    assert_eq!(
+        record.ts.as_ref().and_then(|ts| ts.parse()).map(|ts| ts.to_rfc3339()),
+        Some("2024-01-25T18:00:01+00:00".into())
+    );
+    assert_eq!(record.level, Some(Level::Error));
+    assert_eq!(record.message, None);
+    assert_eq!(
+        record.fields().copied().collect::<Vec<_>>(),
+        vec![
+            ("remote_addr", RawValue::String(EncodedString::raw("10.0.0.1"))),
+            ("remote_user", RawValue::String(EncodedString::raw("frank"))),
+            ("method", RawValue::String(EncodedString::raw("GET"))),
+            ("path", RawValue::String(EncodedString::raw("/api/items?id=1"))),
+            ("protocol", RawValue::String(EncodedString::raw("HTTP/1.1"))),
+            ("status", RawValue::Number("503")),
+            ("bytes", RawValue::Number("12")),
+            ("user_agent", RawValue::String(EncodedString::raw("curl/8.0"))),
+        ]
+    );
+
+    let record = parse(br#"10.0.0.1 - - [25/Jan/2024:18:00:01 +0000] "\x16\x03" 200 -"#).unwrap();
+    let record = parser.parse(&record.record);
+    assert_eq!(record.level, None);
+    assert_eq!(
+        record.fields().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec!["remote_addr", "request", "status"]
+    );
+
+    assert!(matches!(
+        parse(b"key=value"),
+        Err(Error::AccessLogParseError(accesslog::Error::UnexpectedEnd))
+    ));
+}
+
+#[test]
+fn test_text_formats() {
+    let formats = vec![
+        TextFormat::new(
+            &TextInputFormat {
+                name: "test".into(),
+                regex: r"^(?P<ts>\S+ \S+) \[(?P<level>\w+)\] (?P<msg>.*)$".into(),
+                timestamp_format: Some("%Y/%m/%d %H:%M:%S".into()),
+            },
+            &["ts".into()],
+        )
+        .unwrap(),
+    ];
+
+    let parser = Parser::new(ParserSettings::default());
+    let line = b"2024/01/25 18:00:01 [warning] disk is almost full";
+
+    let record = RawRecord::parser()
+        .text_formats(&formats)
+        .parse(line)
+        .next()
+        .unwrap()
+        .unwrap();
+    let record = parser.parse(&record.record);
+    assert_eq!(
+        record.ts.as_ref().and_then(|ts| ts.parse()).map(|ts| ts.to_rfc3339()),
+        Some("2024-01-25T18:00:01+00:00".into())
+    );
+    assert_eq!(record.level, Some(Level::Warning));
+    assert_eq!(record.message.map(|m| m.raw_str()), Some("disk is almost full"));
+
+    let record = RawRecord::parser()
+        .text_formats(&formats)
+        .parse(b"level=info msg=ok")
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(parser.parse(&record.record).level, Some(Level::Info));
+
+    let mut stream = RawRecord::parser()
+        .format(Some(InputFormat::Logfmt))
+        .text_formats(&formats)
+        .parse(line);
+    assert!(matches!(stream.next(), Some(Err(Error::LogfmtParseError(_)))));
+
+    let mut stream = RawRecord::parser().text_formats(&formats).parse(b"unknown line");
+    assert!(matches!(stream.next(), Some(Err(Error::LogfmtParseError(_)))));
+}
+
+#[test]
+fn test_wrapped() {
+    let parser = Parser::new(ParserSettings::default());
+    let parse = |line: &'static [u8]| RawRecord::parser().wrapped(true).parse(line).next().unwrap().unwrap();
+
+    let line = br#"2024-01-25T18:00:01Z stderr F {"ts":"2024-01-25T18:00:00Z","msg":"m1"}"#;
+    let ar = parse(line);
+    assert_eq!(
+        &line[ar.offsets.clone()],
+        br#"{"ts":"2024-01-25T18:00:00Z","msg":"m1"}"#
+    );
+    let record = parser.parse(&ar.record);
+    assert_eq!(record.ts.map(|ts| ts.raw()), Some("2024-01-25T18:00:00Z"));
+    assert_eq!(record.message.map(|m| m.raw_str()), Some("m1"));
+    assert_eq!(
+        record.fields().copied().collect::<Vec<_>>(),
+        vec![("stream", RawValue::String(EncodedString::raw("stderr")))]
+    );
+
+    let record = parse(b"2024-01-25T18:00:01Z stdout F level=info msg=m2");
+    let record = parser.parse(&record.record);
+    assert_eq!(record.ts.map(|ts| ts.raw()), Some("2024-01-25T18:00:01Z"));
+    assert_eq!(record.level, Some(Level::Info));
+    assert_eq!(record.message.map(|m| m.raw_str()), Some("m2"));
+
+    let record = parse(b"level=info msg=m3");
+    let record = parser.parse(&record.record);
+    assert!(record.ts.is_none());
+    assert_eq!(record.fields().count(), 0);
+}
+
+#[test]
+fn test_embedded_json() {
+    let parser = Parser::new(ParserSettings::default());
+    let line = br#"{"msg":"m1","payload":"{\"user\":\"alice\",\"n\":1}","list":" [1,2]","bad":"{oops","text":"x {}"}"#;
+    let mut buf = Vec::new();
+
+    let expand = |expansion: EmbeddedJsonExpansion, buf: &mut Vec<u8>| {
+        let ar = RawRecord::parser().parse(line).next().unwrap().unwrap();
+        let record = expansion.expand(ar.record, buf);
+        record
+            .fields()
+            .map(|(k, v)| (*k, v.raw_str().to_owned()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        expand(EmbeddedJsonExpansion::All, &mut buf),
+        vec![
+            ("msg", r#""m1""#.to_owned()),
+            ("payload", r#"{"user":"alice","n":1}"#.to_owned()),
+            ("list", "[1,2]".to_owned()),
+            ("bad", r#""{oops""#.to_owned()),
+            ("text", r#""x {}""#.to_owned()),
+        ]
+    );
+    assert_eq!(
+        expand(EmbeddedJsonExpansion::Fields(vec!["list".into()]), &mut buf)[1..3],
+        [
+            ("payload", r#""{\"user\":\"alice\",\"n\":1}""#.to_owned()),
+            ("list", "[1,2]".to_owned()),
+        ]
+    );
+    assert_eq!(
+        expand(EmbeddedJsonExpansion::Fields(vec!["LIST".into()]), &mut buf)[2],
+        ("list", "[1,2]".to_owned())
+    );
+    assert_eq!(
+        expand(EmbeddedJsonExpansion::Disabled, &mut buf)[2],
+        ("list", r#"" [1,2]""#.to_owned())
+    );
+
+    let ar = RawRecord::parser().parse(line).next().unwrap().unwrap();
+    let record = EmbeddedJsonExpansion::All.expand(ar.record, &mut buf);
+    let record = parser.parse(&record);
+    assert!(FieldFilter::parse("payload.user=alice").unwrap().apply(&record));
+    assert!(!FieldFilter::parse("payload.user=bob").unwrap().apply(&record));
+    assert!(matches!(
+        record.fields().find(|(k, _)| *k == "payload"),
+        Some((_, RawValue::Object(_)))
+    ));
+}
+
+#[test]
+fn test_indexed_fields() {
+    let fields = IndexedFields::new(["Request_ID", "request-id", "span.id", "msg", "trace-id"]);
+    assert_eq!(fields.keys(), ["request-id", "trace-id"]);
+    assert!(fields.contains("request-id"));
+    assert!(!fields.contains("span.id"));
+    assert!(IndexedFields::default().is_empty());
+
+    let record = parse(r#"{"request_id":"a\"b","trace-id":42,"span":{"trace-id":"x"},"msg":"m"}"#);
+    let mut values = Vec::new();
+    fields.for_each(&record, |k, v| values.push((k.to_owned(), v.to_owned())));
+    assert_eq!(
+        values,
+        vec![
+            ("request-id".to_owned(), r#"a"b"#.to_owned()),
+            ("trace-id".to_owned(), "42".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn test_field_filter_may_match() {
+    struct Values(&'static [(&'static str, &'static str)]);
+
+    impl FieldMembership for Values {
+        fn may_contain(&self, key: &str, value: &str) -> bool {
+            self.0.contains(&(key, value))
+        }
+    }
+
+    let values = Values(&[("request-id", "r1"), ("trace-id", "t1")]);
+    let may_match = |filter: &str| FieldFilter::parse(filter).unwrap().may_match(&values);
+
+    assert!(may_match("request_id=r1"));
+    assert!(!may_match("request-id=r2"));
+    assert!(may_match("request-id!=r2"));
+    assert!(may_match("request-id~=r2"));
+    assert!(may_match("request-id?=r2"));
+    assert!(may_match("span.request-id=r2"));
+
+    let set = FieldFilterSet::new(["request-id=r1", "trace-id=t1"]).unwrap();
+    assert!(set.may_match(&values));
+    let set = FieldFilterSet::new(["request-id=r1", "trace-id=t2"]).unwrap();
+    assert!(!set.may_match(&values));
+    assert!(
+        FieldFilter::parse("request-id=r2")
+            .unwrap()
+            .or(Level::Info)
+            .may_match(&values)
+    );
+    assert!(
+        !FieldFilter::parse("request-id=r2")
+            .unwrap()
+            .and(Level::Info)
+            .may_match(&values)
+    );
+}
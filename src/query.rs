@@ -16,7 +16,8 @@ use crate::{
     error::{Error, Result},
     level::RelaxedLevel,
     model::{
-        FieldFilter, FieldFilterKey, Level, Number, NumericOp, Record, RecordFilter, RecordFilterNone, ValueMatchPolicy,
+        FieldFilter, FieldFilterKey, FieldMembership, Level, Number, NumericOp, Record, RecordFilter, RecordFilterNone,
+        ValueMatchPolicy,
     },
     model::{FieldFilterFlag, FieldFilterFlags},
 };
@@ -82,6 +83,11 @@ impl RecordFilter for Query {
     fn apply<'a>(&self, record: &Record<'a>) -> bool {
         self.filter.apply(record)
     }
+
+    #[inline]
+    fn may_match(&self, fields: &dyn FieldMembership) -> bool {
+        self.filter.may_match(fields)
+    }
 }
 
 impl Default for Query {
@@ -375,6 +381,11 @@ impl RecordFilter for OpOr {
     fn apply<'a>(&self, record: &Record<'a>) -> bool {
         self.lhs.apply(record) || self.rhs.apply(record)
     }
+
+    #[inline]
+    fn may_match(&self, fields: &dyn FieldMembership) -> bool {
+        self.lhs.may_match(fields) || self.rhs.may_match(fields)
+    }
 }
 
 impl BinaryOp for OpOr {
@@ -395,6 +406,11 @@ impl RecordFilter for OpAnd {
     fn apply<'a>(&self, record: &Record<'a>) -> bool {
         self.lhs.apply(record) && self.rhs.apply(record)
     }
+
+    #[inline]
+    fn may_match(&self, fields: &dyn FieldMembership) -> bool {
+        self.lhs.may_match(fields) && self.rhs.may_match(fields)
+    }
 }
 
 impl BinaryOp for OpAnd {
@@ -0,0 +1,464 @@
+// std imports
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    fs::{self, Metadata},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+// unix-only std imports
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+
+// third-party imports
+use serde::{Deserialize, Serialize};
+
+// local imports
+#[cfg(windows)]
+use crate::win_file_id;
+use crate::{
+    error::{Error, Result},
+    input::{Input, InputReference, Meta, Stream},
+    scanning::Delimiter,
+};
+
+// ---
+
+/// Resume keeps track of read positions in input files and saves them under a name in the cache directory,
+/// so that the next run with the same name continues from where the previous one stopped.
+///
+/// Files are identified by device and inode numbers rather than by path,
+/// so a file renamed by log rotation is continued under its new name.
+///
+/// In follow mode, a position reached after reading a segment of an input is committed only when
+/// all entries of the segment and the segments before it are written to the output, see [`Progress`].
+pub struct Resume {
+    path: PathBuf,
+    continued: bool,
+    saved: Mutex<HashMap<FileId, u64>>,
+    state: Mutex<State>,
+}
+
+impl Resume {
+    /// Loads positions saved under the name in the cache directory.
+    pub fn load(cache_dir: &Path, name: &str) -> Result<Self> {
+        let valid = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+        if name.is_empty() || name.starts_with('.') || !name.chars().all(valid) {
+            return Err(Error::InvalidResumeName(name.into()));
+        }
+
+        let path = cache_dir.join("resume").join(format!("{name}.json"));
+        let mut continued = true;
+        let positions = match fs::read(&path) {
+            Ok(data) => {
+                json::from_slice::<Positions>(&data)
+                    .map_err(|source| Error::FailedToParseResumeState {
+                        path: path.clone(),
+                        source,
+                    })?
+                    .files
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                continued = false;
+                Vec::new()
+            }
+            Err(source) => {
+                return Err(Error::FailedToReadFile {
+                    path: path.to_string_lossy().into(),
+                    source,
+                });
+            }
+        };
+
+        Ok(Self {
+            path,
+            continued,
+            saved: Mutex::new(positions.iter().map(|p| (p.id, p.offset)).collect()),
+            state: Mutex::new(State {
+                positions,
+                cursors: Vec::new(),
+                changed: false,
+            }),
+        })
+    }
+
+    /// Returns true if the positions were saved by a previous run,
+    /// so the files without a saved position appeared after it.
+    pub fn continued(&self) -> bool {
+        self.continued
+    }
+
+    /// Starts tracking the read position in the file of the input with the given number.
+    ///
+    /// If a position was saved for the file, the input is moved to it and true is returned.
+    /// Each saved position is used only once, so the file reopened later is read from its current position.
+    /// Inputs other than files are returned as is.
+    pub fn track(&self, i: usize, mut input: Input, delimiter: &Delimiter) -> io::Result<(Input, bool)> {
+        let InputReference::File(path) = &input.reference else {
+            return Ok((input, false));
+        };
+        let Some(id) = input
+            .stream
+            .metadata()?
+            .filter(|meta| meta.is_file())
+            .and_then(|meta| FileId::new(&meta, &path.canonical))
+        else {
+            return Ok((input, false));
+        };
+
+        let saved = self.saved.lock().unwrap().remove(&id);
+        let mut resumed = false;
+        let mut position = 0;
+        match (&mut input.stream, saved) {
+            (Stream::RandomAccess(stream), Some(offset)) => {
+                // a file that became shorter was truncated, so it is read from the beginning
+                let size = stream.seek(SeekFrom::End(0))?;
+                position = if offset <= size { offset } else { 0 };
+                stream.seek(SeekFrom::Start(position))?;
+                resumed = true;
+            }
+            (Stream::RandomAccess(stream), None) => {
+                position = stream.stream_position()?;
+            }
+            (stream, Some(offset)) => {
+                position = io::copy(&mut stream.take(offset), &mut io::sink())?;
+                resumed = true;
+            }
+            (_, None) => {}
+        }
+
+        let cursor = Arc::new(Cursor {
+            input: i,
+            id,
+            path: path.canonical.clone(),
+            offset: AtomicU64::new(position),
+        });
+        self.state
+            .lock()
+            .unwrap()
+            .cursors
+            .push((cursor.clone(), History::new()));
+
+        let tracker = |cursor| Tracker {
+            cursor,
+            position,
+            delimiter: last_byte(delimiter),
+        };
+        input.stream = match input.stream {
+            Stream::RandomAccess(stream) => Stream::RandomAccess(Box::new(tracker(cursor).wrap(stream))),
+            Stream::Sequential(stream) => Stream::Sequential(Box::new(tracker(cursor).wrap(stream))),
+            Stream::Compressed(stream) => Stream::Sequential(Box::new(tracker(cursor).wrap(stream))),
+        };
+
+        Ok((input, resumed))
+    }
+
+    /// Remembers the position reached in the input after its segment with the given number is read.
+    ///
+    /// Segments of an input are numbered sequentially, and the position is committed by [`Self::written`].
+    pub fn reached(&self, i: usize, segment: usize) {
+        let mut state = self.state.lock().unwrap();
+        let Some((cursor, history)) = state.cursors.iter_mut().rev().find(|(cursor, _)| cursor.input == i) else {
+            return;
+        };
+        let offset = cursor.offset.load(Ordering::Relaxed);
+        if history.back().is_none_or(|&(_, last)| last != offset) {
+            history.push_back((segment, offset));
+        }
+    }
+
+    /// Commits the positions reached in the input up to its segment with the given number,
+    /// it is called when all entries of the segment and the segments before it are written to the output.
+    pub fn written(&self, i: usize, segment: usize) {
+        let mut state = self.state.lock().unwrap();
+        let State {
+            positions,
+            cursors,
+            changed,
+        } = &mut *state;
+
+        for (cursor, history) in cursors.iter_mut().filter(|(cursor, _)| cursor.input == i) {
+            let mut committed = None;
+            while let Some(&(_, offset)) = history.front().filter(|&&(s, _)| s <= segment) {
+                committed = Some(offset);
+                history.pop_front();
+            }
+            if let Some(offset) = committed {
+                *changed |= commit(positions, cursor, offset);
+            }
+        }
+    }
+
+    /// Saves the committed positions if they have changed since the last save.
+    pub fn save(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if !state.changed {
+            return Ok(());
+        }
+
+        // positions of the files that were removed are of no use anymore
+        state.positions.retain(|p| p.path.exists());
+
+        let data = json::to_vec_pretty(&Positions {
+            files: state.positions.clone(),
+        })?;
+        let write = || -> io::Result<()> {
+            fs::create_dir_all(self.path.parent().unwrap())?;
+            let tmp = self.path.with_extension("json.tmp");
+            fs::write(&tmp, &data)?;
+            fs::rename(&tmp, &self.path)
+        };
+        write().map_err(|source| Error::FailedToOpenFileForWriting {
+            path: self.path.clone(),
+            source,
+        })?;
+        state.changed = false;
+        Ok(())
+    }
+
+    /// Commits and saves the positions reached so far, it is called when all inputs are completely processed.
+    pub fn finish(&self) -> Result<()> {
+        {
+            let mut state = self.state.lock().unwrap();
+            let State {
+                positions,
+                cursors,
+                changed,
+            } = &mut *state;
+
+            for (cursor, history) in cursors.iter_mut() {
+                history.clear();
+                *changed |= commit(positions, cursor, cursor.offset.load(Ordering::Relaxed));
+            }
+        }
+        self.save()
+    }
+}
+
+// ---
+
+/// Progress tells which segments of the inputs have all their entries written to the output.
+///
+/// Segments of each input are expected to be numbered sequentially starting from zero,
+/// while they may be received in any order.
+#[derive(Default)]
+pub struct Progress {
+    inputs: HashMap<usize, InputProgress>,
+}
+
+impl Progress {
+    /// Registers the received segment of the input along with the number of its entries that are not written yet.
+    pub fn received(&mut self, i: usize, segment: usize, entries: usize) {
+        let input = self.inputs.entry(i).or_default();
+        if segment >= input.next {
+            input.received.insert(segment);
+            while input.received.remove(&input.next) {
+                input.next += 1;
+            }
+        }
+        if entries != 0 {
+            *input.pending.entry(segment).or_default() += entries;
+        }
+    }
+
+    /// Registers an entry of the segment of the input written to the output.
+    pub fn written(&mut self, i: usize, segment: usize) {
+        let Some(input) = self.inputs.get_mut(&i) else {
+            return;
+        };
+        if let Some(entries) = input.pending.get_mut(&segment) {
+            *entries -= 1;
+            if *entries == 0 {
+                input.pending.remove(&segment);
+            }
+        }
+    }
+
+    /// Returns the inputs with the last segments written completely along with all the segments before them,
+    /// only the ones that have changed since the previous call are returned.
+    pub fn complete(&mut self) -> Vec<(usize, usize)> {
+        let mut result = Vec::new();
+        for (&i, input) in self.inputs.iter_mut() {
+            let end = match input.pending.first_key_value() {
+                Some((&segment, _)) => segment.min(input.next),
+                None => input.next,
+            };
+            if end > input.complete {
+                input.complete = end;
+                result.push((i, end - 1));
+            }
+        }
+        result
+    }
+}
+
+#[derive(Default)]
+struct InputProgress {
+    // number of the first segment that is not received yet
+    next: usize,
+    // segments received ahead of the ones that are not received yet
+    received: BTreeSet<usize>,
+    // numbers of entries that are not written yet by segment
+    pending: BTreeMap<usize, usize>,
+    // number of the first segment that is not reported as complete yet
+    complete: usize,
+}
+
+// ---
+
+/// Identity of a file that does not change when the file is renamed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FileId {
+    device: u64,
+    inode: u64,
+}
+
+impl FileId {
+    /// Returns the identity of the file, or None if it is not supported on the platform.
+    #[cfg(unix)]
+    pub fn new(meta: &Metadata, _: &Path) -> Option<Self> {
+        Some(Self {
+            device: meta.dev(),
+            inode: meta.ino(),
+        })
+    }
+
+    /// Returns the identity of the file, or None if it is not supported on the platform.
+    #[cfg(windows)]
+    pub fn new(_: &Metadata, path: &Path) -> Option<Self> {
+        let info = win_file_id::query(&win_file_id::open_shared(path)?)?;
+        Some(Self {
+            device: info.id.volume_serial_number.into(),
+            inode: info.id.file_index,
+        })
+    }
+
+    /// Returns the identity of the file, or None if it is not supported on the platform.
+    #[cfg(not(any(unix, windows)))]
+    pub fn new(_: &Metadata, _: &Path) -> Option<Self> {
+        None
+    }
+}
+
+// ---
+
+/// Saved position in a file, the path is the one the file had when the position was saved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Position {
+    path: PathBuf,
+    id: FileId,
+    offset: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Positions {
+    files: Vec<Position>,
+}
+
+struct State {
+    positions: Vec<Position>,
+    cursors: Vec<(Arc<Cursor>, History)>,
+    changed: bool,
+}
+
+/// Offsets reached by a cursor that are not committed yet, along with the numbers of segments read before them.
+type History = VecDeque<(usize, u64)>;
+
+/// Cursor is the offset in a file of an input just after the last complete entry read from it.
+struct Cursor {
+    input: usize,
+    id: FileId,
+    path: PathBuf,
+    offset: AtomicU64,
+}
+
+// ---
+
+/// Tracker moves the cursor forward as the entries are read from the stream.
+struct Tracker {
+    cursor: Arc<Cursor>,
+    position: u64,
+    delimiter: u8,
+}
+
+impl Tracker {
+    fn wrap<S>(self, inner: S) -> Tracked<S> {
+        Tracked { inner, tracker: self }
+    }
+}
+
+struct Tracked<S> {
+    inner: S,
+    tracker: Tracker,
+}
+
+impl<S: Read> Read for Tracked<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        let tracker = &mut self.tracker;
+        if let Some(i) = memchr::memrchr(tracker.delimiter, &buf[..n]) {
+            tracker
+                .cursor
+                .offset
+                .store(tracker.position + i as u64 + 1, Ordering::Relaxed);
+        }
+        tracker.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<S: Seek> Seek for Tracked<S> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = self.inner.seek(pos)?;
+        self.tracker.position = position;
+        self.tracker.cursor.offset.store(position, Ordering::Relaxed);
+        Ok(position)
+    }
+}
+
+impl<S: Meta> Meta for Tracked<S> {
+    #[inline]
+    fn metadata(&self) -> io::Result<Option<Metadata>> {
+        self.inner.metadata()
+    }
+}
+
+// ---
+
+/// Updates the position of the cursor file, returns true if it has changed.
+fn commit(positions: &mut Vec<Position>, cursor: &Cursor, offset: u64) -> bool {
+    let position = Position {
+        path: cursor.path.clone(),
+        id: cursor.id,
+        offset,
+    };
+    match positions.iter_mut().find(|p| p.id == cursor.id) {
+        Some(p) if *p == position => false,
+        Some(p) => {
+            *p = position;
+            true
+        }
+        None => {
+            positions.push(position);
+            true
+        }
+    }
+}
+
+/// Returns the last byte of the delimiter, an entry is complete when it is read.
+fn last_byte(delimiter: &Delimiter) -> u8 {
+    match delimiter {
+        Delimiter::Byte(b) => *b,
+        Delimiter::Bytes(b) => b.last().copied().unwrap_or(b'\n'),
+        Delimiter::Char(c) => c.to_string().as_bytes().last().copied().unwrap_or(b'\n'),
+        Delimiter::Str(s) => s.as_bytes().last().copied().unwrap_or(b'\n'),
+        Delimiter::PrettyCompatible | Delimiter::Newline | Delimiter::Json => b'\n',
+    }
+}
+
+#[cfg(test)]
+mod tests;
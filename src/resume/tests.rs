@@ -0,0 +1,127 @@
+use std::io::Write;
+
+use super::*;
+use crate::testing::TempDir;
+
+fn read(resume: &Resume, path: &Path) -> (String, bool) {
+    let (mut input, resumed) = resume
+        .track(0, Input::open(path).unwrap(), &Delimiter::Newline)
+        .unwrap();
+    let mut buf = String::new();
+    input.stream.read_to_string(&mut buf).unwrap();
+    (buf, resumed)
+}
+
+fn run(dir: &TempDir, path: &Path) -> (String, bool) {
+    let resume = Resume::load(dir.path(), "test").unwrap();
+    let result = read(&resume, path);
+    resume.finish().unwrap();
+    result
+}
+
+fn append(path: &Path, data: &str) {
+    fs::OpenOptions::new()
+        .append(true)
+        .open(path)
+        .unwrap()
+        .write_all(data.as_bytes())
+        .unwrap();
+}
+
+#[test]
+fn test_invalid_name() {
+    let dir = TempDir::new("resume-name");
+    for name in ["", ".hidden", "a/b", "..", "a b"] {
+        assert!(
+            matches!(Resume::load(dir.path(), name), Err(Error::InvalidResumeName(_))),
+            "{name}"
+        );
+    }
+    assert!(Resume::load(dir.path(), "on-call_1.0").is_ok());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_resume() {
+    let dir = TempDir::new("resume-resume");
+    let path = dir.path().join("app.log");
+    fs::write(&path, "a\nb\n").unwrap();
+
+    assert_eq!(run(&dir, &path), ("a\nb\n".into(), false));
+    assert_eq!(run(&dir, &path), ("".into(), true));
+
+    // an incomplete entry is read again on the next run
+    append(&path, "c\nd");
+    assert_eq!(run(&dir, &path), ("c\nd".into(), true));
+    append(&path, "\ne\n");
+    assert_eq!(run(&dir, &path), ("d\ne\n".into(), true));
+
+    // a truncated file is read from the beginning
+    fs::write(&path, "f\n").unwrap();
+    assert_eq!(run(&dir, &path), ("f\n".into(), true));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_resume_rotated() {
+    let dir = TempDir::new("resume-rotated");
+    let path = dir.path().join("app.log");
+    let rotated = dir.path().join("app.log.1");
+    fs::write(&path, "a\n").unwrap();
+    assert_eq!(run(&dir, &path), ("a\n".into(), false));
+
+    append(&path, "b\n");
+    fs::rename(&path, &rotated).unwrap();
+    fs::write(&path, "c\n").unwrap();
+
+    let resume = Resume::load(dir.path(), "test").unwrap();
+    assert_eq!(read(&resume, &rotated), ("b\n".into(), true));
+    assert_eq!(read(&resume, &path), ("c\n".into(), false));
+    resume.finish().unwrap();
+
+    let resume = Resume::load(dir.path(), "test").unwrap();
+    assert_eq!(read(&resume, &rotated), ("".into(), true));
+    assert_eq!(read(&resume, &path), ("".into(), true));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_written() {
+    let dir = TempDir::new("resume-written");
+    let path = dir.path().join("app.log");
+    let state = dir.path().join("resume").join("test.json");
+    fs::write(&path, "a\n").unwrap();
+
+    // the position is saved only when the entries read before it are written
+    let resume = Resume::load(dir.path(), "test").unwrap();
+    read(&resume, &path);
+    resume.reached(0, 0);
+    resume.save().unwrap();
+    assert!(!state.exists());
+    resume.written(0, 0);
+    resume.save().unwrap();
+    assert!(state.exists());
+    assert!(!Resume::load(dir.path(), "other").unwrap().continued());
+
+    let resume = Resume::load(dir.path(), "test").unwrap();
+    assert!(resume.continued());
+    assert_eq!(read(&resume, &path), ("".into(), true));
+}
+
+#[test]
+fn test_progress() {
+    let mut progress = Progress::default();
+    progress.received(0, 1, 2);
+    progress.received(1, 0, 1);
+    assert_eq!(progress.complete(), []);
+
+    progress.received(0, 0, 0);
+    assert_eq!(progress.complete(), [(0, 0)]);
+    assert_eq!(progress.complete(), []);
+
+    progress.written(0, 1);
+    progress.written(1, 0);
+    assert_eq!(progress.complete(), [(1, 0)]);
+    progress.written(0, 1);
+    assert_eq!(progress.complete(), [(0, 1)]);
+}
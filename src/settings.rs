@@ -5,6 +5,7 @@ use std::{
     ops::Deref,
     path::{Path, PathBuf},
     str::FromStr,
+    time::Duration,
 };
 
 // third-party imports
@@ -50,6 +51,10 @@ pub struct Settings {
     #[serde(deserialize_with = "enumset_serde::deserialize")]
     pub input_info: InputInfoSet,
     pub ascii: AsciiModeOpt,
+    #[serde(default)]
+    pub input: Input,
+    #[serde(default)]
+    pub index: Index,
     /// Pager configuration (candidates and profiles).
     #[serde(default)]
     pub pager: PagerConfig,
@@ -139,6 +144,81 @@ impl SourceFile {
 
 // ---
 
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Input {
+    pub access_log: AccessLogInput,
+    #[serde(default)]
+    pub formats: Vec<TextInputFormat>,
+}
+
+// ---
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Index {
+    /// Fields with values indexed for each block, so that blocks without a value required by a filter are skipped.
+    #[serde(default)]
+    pub fields: Vec<String>,
+    /// Maximum total size of cached index files, like `1 GiB`, the least recently used ones are removed to fit it.
+    #[serde(default, deserialize_with = "size_deserialize", skip_serializing)]
+    pub max_cache_size: Option<u64>,
+    /// Maximum time since the last use of a cached index file, like `30d`, older ones are removed.
+    #[serde(default, deserialize_with = "duration_deserialize", skip_serializing)]
+    pub max_cache_age: Option<Duration>,
+}
+
+// ---
+
+/// User-defined format of text input lines.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TextInputFormat {
+    pub name: String,
+    /// Regular expression with named capture groups, each of them providing a field.
+    pub regex: String,
+    /// Format of the time field, see https://docs.rs/chrono/latest/chrono/format/strftime/index.html.
+    pub timestamp_format: Option<String>,
+}
+
+// ---
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct AccessLogInput {
+    pub status_levels: StatusLevels,
+}
+
+/// Levels of access log entries by the class of the response status code.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct StatusLevels {
+    #[serde(rename = "1xx")]
+    pub informational: Option<Level>,
+    #[serde(rename = "2xx")]
+    pub successful: Option<Level>,
+    #[serde(rename = "3xx")]
+    pub redirection: Option<Level>,
+    #[serde(rename = "4xx")]
+    pub client_error: Option<Level>,
+    #[serde(rename = "5xx")]
+    pub server_error: Option<Level>,
+}
+
+impl StatusLevels {
+    pub fn level(&self, status: u16) -> Option<Level> {
+        match status / 100 {
+            1 => self.informational,
+            2 => self.successful,
+            3 => self.redirection,
+            4 => self.client_error,
+            5 => self.server_error,
+            _ => None,
+        }
+    }
+}
+
+// ---
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 pub struct Fields {
     pub predefined: PredefinedFields,
@@ -400,6 +480,7 @@ pub struct Formatting {
     pub message: MessageFormatting,
     pub punctuation: Punctuation,
     pub prettify_field_keys: Option<bool>,
+    pub template: Option<String>,
 }
 
 #[cfg(test)]
@@ -413,6 +494,7 @@ impl Sample for Formatting {
             },
             punctuation: Punctuation::sample(),
             prettify_field_keys: None,
+            template: None,
         }
     }
 }
@@ -509,6 +591,7 @@ pub struct Punctuation {
     pub input_name_common_part: DisplayVariant,
     pub array_separator: DisplayVariant,
     pub message_delimiter: DisplayVariant,
+    pub context_separator: DisplayVariant,
 }
 
 impl Punctuation {
@@ -534,6 +617,7 @@ impl Punctuation {
             input_name_common_part: Self::resolve_field(&self.input_name_common_part, mode),
             array_separator: Self::resolve_field(&self.array_separator, mode),
             message_delimiter: Self::resolve_field(&self.message_delimiter, mode),
+            context_separator: Self::resolve_field(&self.context_separator, mode),
         }
     }
 
@@ -563,6 +647,7 @@ impl Default for Punctuation {
             input_name_common_part: "...".into(),
             array_separator: " ".into(),
             message_delimiter: "::".into(),
+            context_separator: "--".into(),
         }
     }
 }
@@ -589,6 +674,7 @@ impl Sample for Punctuation {
             input_name_common_part: DisplayVariant::ascii("**").unicode("★★"),
             array_separator: ", ".into(),
             message_delimiter: "::".into(),
+            context_separator: "--".into(),
         }
     }
 }
@@ -616,6 +702,7 @@ pub struct ResolvedPunctuation {
     pub input_name_common_part: String,
     pub array_separator: String,
     pub message_delimiter: String,
+    pub context_separator: String,
 }
 
 /// Configuration option for ASCII mode.
@@ -808,5 +895,29 @@ where
     }
 }
 
+/// Deserializes an optional size like `512 MiB`, an empty string means no value.
+fn size_deserialize<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match String::deserialize(deserializer)?.trim() {
+        "" => Ok(None),
+        value => bytefmt::parse(value).map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Deserializes an optional duration like `30d`, an empty string means no value.
+fn duration_deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match String::deserialize(deserializer)?.trim() {
+        "" => Ok(None),
+        value => humantime::parse_duration(value)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
 #[cfg(test)]
 mod tests;
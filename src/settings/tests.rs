@@ -233,3 +233,40 @@ fn test_punctuation_resolve() {
         assert_ne!(ascii_val, utf8_val, "ASCII and Unicode values should be different");
     }
 }
+
+#[test]
+fn test_status_levels() {
+    let levels = Settings::default().input.access_log.status_levels;
+    assert_eq!(levels.level(101), Some(Level::Info));
+    assert_eq!(levels.level(200), Some(Level::Info));
+    assert_eq!(levels.level(304), Some(Level::Info));
+    assert_eq!(levels.level(404), Some(Level::Warning));
+    assert_eq!(levels.level(503), Some(Level::Error));
+    assert_eq!(levels.level(600), None);
+
+    let settings = Settings::load([Source::string(
+        "[input.access-log.status-levels]\n4xx = \"debug\"",
+        FileFormat::Toml,
+    )])
+    .unwrap();
+    assert_eq!(settings.input.access_log.status_levels.level(404), Some(Level::Debug));
+    assert_eq!(settings.input.access_log.status_levels.level(500), Some(Level::Error));
+}
+
+#[test]
+fn test_index_cache_limits() {
+    let index = Settings::default().index;
+    assert_eq!(index.max_cache_size, Some(1 << 30));
+    assert_eq!(index.max_cache_age, Some(std::time::Duration::from_secs(30 * 86400)));
+
+    let settings = Settings::load([Source::string(
+        "[index]\nmax-cache-size = \"512 MiB\"\nmax-cache-age = \"\"",
+        FileFormat::Toml,
+    )])
+    .unwrap();
+    assert_eq!(settings.index.max_cache_size, Some(512 << 20));
+    assert_eq!(settings.index.max_cache_age, None);
+
+    let result = Settings::load([Source::string("[index]\nmax-cache-size = \"lots\"", FileFormat::Toml)]);
+    assert!(result.is_err());
+}
@@ -1,5 +1,6 @@
 // std imports
 use std::process::exit;
+use std::sync::{Arc, Mutex, Weak};
 use std::thread::{JoinHandle, spawn};
 use std::time::{Duration, Instant};
 
@@ -14,6 +15,36 @@ use crate::error::*;
 
 // ---
 
+type Hook = dyn Fn() + Send + Sync;
+
+static HOOKS: Mutex<Vec<Weak<Hook>>> = Mutex::new(Vec::new());
+
+/// Registers a function called before the signal handler exits the process, until the returned guard is dropped.
+pub fn on_exit<F>(f: F) -> ExitHook
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let hook: Arc<Hook> = Arc::new(f);
+    let mut hooks = HOOKS.lock().unwrap();
+    hooks.retain(|hook| hook.strong_count() != 0);
+    hooks.push(Arc::downgrade(&hook));
+    ExitHook { _hook: hook }
+}
+
+fn run_exit_hooks() {
+    let hooks: Vec<_> = HOOKS.lock().unwrap().iter().filter_map(Weak::upgrade).collect();
+    for hook in hooks {
+        hook();
+    }
+}
+
+/// Keeps the function registered by [`on_exit`] until dropped.
+pub struct ExitHook {
+    _hook: Arc<Hook>,
+}
+
+// ---
+
 pub struct SignalHandler {
     signals: Handle,
     thread: Option<JoinHandle<()>>,
@@ -47,6 +78,7 @@ impl SignalHandler {
                             count = 0;
                         }
                         if count == max_count {
+                            run_exit_hooks();
                             log::debug!("signal handler calling exit({})", 0x80 + signal);
                             exit(0x80 + signal);
                         }
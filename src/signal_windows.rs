@@ -6,6 +6,19 @@ use crate::error::*;
 
 // ---
 
+/// Registers a function called before the signal handler exits the process, until the returned guard is dropped.
+pub fn on_exit<F>(_: F) -> ExitHook
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    ExitHook {}
+}
+
+/// Keeps the function registered by [`on_exit`] until dropped.
+pub struct ExitHook {}
+
+// ---
+
 pub struct SignalHandler {}
 
 impl SignalHandler {
@@ -0,0 +1,204 @@
+// std imports
+use std::{
+    collections::HashMap,
+    io::{Result, Write},
+};
+
+// third-party imports
+use chrono::{DateTime, FixedOffset};
+use enum_map::EnumMap;
+
+// local imports
+use crate::{
+    datefmt::DateTimeFormatter,
+    model::{FieldValueExtractor, Level, Record},
+};
+
+// ---
+
+/// Summary contains statistical information collected over entries.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub entries_valid: u64,
+    pub entries_invalid: u64,
+    pub levels: EnumMap<Level, u64>,
+    pub levels_unknown: u64,
+    pub loggers: HashMap<String, u64>,
+    pub fields: Vec<HashMap<String, u64>>,
+    pub time_span: Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)>,
+}
+
+impl Summary {
+    /// Returns a new Summary collecting values of the given number of fields.
+    pub fn new(fields: usize) -> Self {
+        Self {
+            fields: (0..fields).map(|_| HashMap::new()).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds information about a single valid entry.
+    ///
+    /// Values of the fields are extracted with the given `fields` extractors in the same order as in [`Summary::new`].
+    pub fn add_valid(&mut self, rec: &Record, fields: &[FieldValueExtractor]) {
+        self.entries_valid += 1;
+
+        match rec.level {
+            Some(level) => self.levels[level] += 1,
+            None => self.levels_unknown += 1,
+        }
+
+        if let Some(logger) = rec.logger {
+            Self::count(&mut self.loggers, logger);
+        }
+
+        for (extractor, values) in fields.iter().zip(self.fields.iter_mut()) {
+            if let Some(value) = extractor.extract(rec) {
+                *values.entry(value).or_default() += 1;
+            }
+        }
+
+        if let Some(ts) = rec.ts.as_ref().and_then(|ts| ts.parse()) {
+            self.time_span = Some(match self.time_span {
+                Some((min, max)) => (min.min(ts), max.max(ts)),
+                None => (ts, ts),
+            });
+        }
+    }
+
+    /// Counts invalid entries.
+    pub fn add_invalid(&mut self, n: u64) {
+        self.entries_invalid += n;
+    }
+
+    /// Merges with other Summary.
+    pub fn merge(&mut self, other: Self) {
+        self.entries_valid += other.entries_valid;
+        self.entries_invalid += other.entries_invalid;
+        for (level, n) in other.levels {
+            self.levels[level] += n;
+        }
+        self.levels_unknown += other.levels_unknown;
+        Self::merge_counts(&mut self.loggers, other.loggers);
+        for (values, other) in self.fields.iter_mut().zip(other.fields) {
+            Self::merge_counts(values, other);
+        }
+        self.time_span = match (self.time_span, other.time_span) {
+            (Some((min1, max1)), Some((min2, max2))) => Some((min1.min(min2), max1.max(max2))),
+            (span, None) | (None, span) => span,
+        };
+    }
+
+    #[inline]
+    fn count(counts: &mut HashMap<String, u64>, value: &str) {
+        match counts.get_mut(value) {
+            Some(n) => *n += 1,
+            None => {
+                counts.insert(value.to_owned(), 1);
+            }
+        }
+    }
+
+    fn merge_counts(counts: &mut HashMap<String, u64>, other: HashMap<String, u64>) {
+        for (value, n) in other {
+            *counts.entry(value).or_default() += n;
+        }
+    }
+}
+
+// ---
+
+/// SummaryFormatter writes a Summary as a human-readable report.
+pub struct SummaryFormatter<'a> {
+    ts_formatter: DateTimeFormatter,
+    top: usize,
+    fields: &'a [String],
+}
+
+impl<'a> SummaryFormatter<'a> {
+    /// Returns a new SummaryFormatter showing at most `top` loggers and values of each of the `fields`.
+    pub fn new(ts_formatter: DateTimeFormatter, top: usize, fields: &'a [String]) -> Self {
+        Self {
+            ts_formatter,
+            top,
+            fields,
+        }
+    }
+
+    pub fn format<W: Write>(&self, out: &mut W, summary: &Summary) -> Result<()> {
+        let total = summary.entries_valid + summary.entries_invalid;
+        Self::section(
+            out,
+            "Entries",
+            [
+                ("total".to_owned(), total.to_string()),
+                ("valid".to_owned(), summary.entries_valid.to_string()),
+                ("invalid".to_owned(), summary.entries_invalid.to_string()),
+            ],
+        )?;
+
+        if let Some((first, last)) = summary.time_span {
+            Self::section(
+                out,
+                "Time span",
+                [
+                    ("first".to_owned(), self.format_ts(first)),
+                    ("last".to_owned(), self.format_ts(last)),
+                ],
+            )?;
+        }
+
+        let unknown = (summary.levels_unknown != 0).then(|| ("(none)".to_owned(), summary.levels_unknown.to_string()));
+        Self::section(
+            out,
+            "Levels",
+            summary
+                .levels
+                .iter()
+                .map(|(level, n)| (level.as_ref().to_owned(), n.to_string()))
+                .chain(unknown),
+        )?;
+
+        Self::section(out, "Top loggers", self.top_values(&summary.loggers))?;
+
+        for (key, values) in self.fields.iter().zip(summary.fields.iter()) {
+            Self::section(out, &format!("Top values of {key:?}"), self.top_values(values))?;
+        }
+
+        Ok(())
+    }
+
+    fn format_ts(&self, ts: DateTime<FixedOffset>) -> String {
+        let mut buf = Vec::new();
+        self.ts_formatter.format(&mut buf, ts);
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+
+    fn top_values(&self, counts: &HashMap<String, u64>) -> Vec<(String, String)> {
+        let mut counts = counts.iter().collect::<Vec<_>>();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        counts
+            .into_iter()
+            .take(self.top)
+            .map(|(value, n)| (value.clone(), n.to_string()))
+            .collect()
+    }
+
+    fn section<W, I>(out: &mut W, title: &str, rows: I) -> Result<()>
+    where
+        W: Write,
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let rows = rows.into_iter().collect::<Vec<_>>();
+        let width = rows.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+
+        writeln!(out, "{title}")?;
+        for (name, value) in rows {
+            writeln!(out, "  {name:width$}  {value}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
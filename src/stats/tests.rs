@@ -0,0 +1,89 @@
+use super::*;
+
+use chrono::{Offset, Utc};
+
+use crate::{
+    datefmt::LinuxDateFormat,
+    model::{RawValue, RecordFields},
+    timestamp::Timestamp,
+    timezone::Tz,
+};
+
+use encstr::EncodedString;
+
+fn record<'a>(ts: &'a str, level: Option<Level>, logger: Option<&'a str>, service: &'a str) -> Record<'a> {
+    Record {
+        ts: Some(Timestamp::new(ts)),
+        level,
+        logger,
+        fields: RecordFields::from_slice(&[("service", RawValue::String(EncodedString::raw(service)))]),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_summary() {
+    let fields = [FieldValueExtractor::new("service")];
+
+    let mut s1 = Summary::new(1);
+    s1.add_valid(
+        &record("2024-01-25T18:00:02Z", Some(Level::Info), Some("a"), "x"),
+        &fields,
+    );
+    s1.add_valid(
+        &record("2024-01-25T18:00:05Z", Some(Level::Error), Some("b"), "y"),
+        &fields,
+    );
+    s1.add_invalid(1);
+
+    let mut s2 = Summary::new(1);
+    s2.add_valid(
+        &record("2024-01-25T18:00:01Z", Some(Level::Info), Some("a"), "x"),
+        &fields,
+    );
+    s2.add_valid(&record("2024-01-25T18:00:03Z", None, None, "x"), &fields);
+
+    s1.merge(s2);
+
+    assert_eq!(s1.entries_valid, 4);
+    assert_eq!(s1.entries_invalid, 1);
+    assert_eq!(s1.levels[Level::Info], 2);
+    assert_eq!(s1.levels[Level::Error], 1);
+    assert_eq!(s1.levels[Level::Debug], 0);
+    assert_eq!(s1.levels_unknown, 1);
+    assert_eq!(s1.loggers.get("a"), Some(&2));
+    assert_eq!(s1.loggers.get("b"), Some(&1));
+    assert_eq!(s1.fields[0].get("x"), Some(&3));
+
+    let fields = vec!["service".to_owned()];
+    let formatter = SummaryFormatter::new(
+        DateTimeFormatter::new(LinuxDateFormat::new("%T").compile(), Tz::FixedOffset(Utc.fix())),
+        1,
+        &fields,
+    );
+    let mut buf = Vec::new();
+    formatter.format(&mut buf, &s1).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&buf).unwrap(),
+        concat!(
+            "Entries\n",
+            "  total    5\n",
+            "  valid    4\n",
+            "  invalid  1\n",
+            "Time span\n",
+            "  first  18:00:01\n",
+            "  last   18:00:05\n",
+            "Levels\n",
+            "  error    1\n",
+            "  warning  0\n",
+            "  info     2\n",
+            "  debug    0\n",
+            "  trace    0\n",
+            "  (none)   1\n",
+            "Top loggers\n",
+            "  a  2\n",
+            "Top values of \"service\"\n",
+            "  x  3\n",
+        )
+    );
+}
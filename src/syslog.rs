@@ -0,0 +1,336 @@
+// std imports
+use std::sync::LazyLock;
+
+// third-party imports
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone};
+use thiserror::Error;
+
+// local imports
+use crate::level::Level;
+
+// ---
+
+/// Current time in the local time zone, used to complete timestamps having no year.
+pub static NOW: LazyLock<DateTime<Local>> = LazyLock::new(Local::now);
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// ---
+
+/// Error is an error which may occur when parsing a syslog message.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    #[error("invalid utf-8 sequence")]
+    InvalidUnicode,
+    #[error("invalid priority")]
+    InvalidPriority,
+    #[error("missing header")]
+    MissingHeader,
+    #[error("invalid header")]
+    InvalidHeader,
+    #[error("invalid structured data")]
+    InvalidStructuredData,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// ---
+
+/// Message is a syslog message in RFC 5424 or RFC 3164 format split into parts.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Message<'a> {
+    pub priority: Option<u8>,
+    pub timestamp: Option<Timestamp<'a>>,
+    pub hostname: Option<&'a str>,
+    pub app_name: Option<&'a str>,
+    pub proc_id: Option<&'a str>,
+    pub msg_id: Option<&'a str>,
+    pub params: Vec<Param<'a>>,
+    pub msg: &'a str,
+}
+
+impl<'a> Message<'a> {
+    /// Parses a syslog message.
+    ///
+    /// Messages starting with a priority and a version are parsed as RFC 5424 messages, others as RFC 3164 messages.
+    /// Priority is optional for RFC 3164 messages, as it is usually omitted in log files, but the timestamp is required then.
+    pub fn parse(line: &'a [u8]) -> Result<Self> {
+        let line = std::str::from_utf8(line).map_err(|_| Error::InvalidUnicode)?;
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        let (priority, rest) = match line.strip_prefix('<') {
+            Some(rest) => {
+                let end = rest.find('>').ok_or(Error::InvalidPriority)?;
+                let priority = match &rest[..end] {
+                    s if (1..=3).contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit()) => s.parse::<u8>().ok(),
+                    _ => None,
+                };
+                (Some(priority.ok_or(Error::InvalidPriority)?), &rest[end + 1..])
+            }
+            None => (None, line),
+        };
+
+        let mut message = Self {
+            priority,
+            ..Default::default()
+        };
+
+        if let Some(rest) = priority.and_then(|_| Self::strip_version(rest)) {
+            message.parse_rfc5424(rest)?;
+        } else {
+            message.parse_rfc3164(rest)?;
+        }
+
+        Ok(message)
+    }
+
+    /// Returns the level corresponding to the severity part of the priority.
+    pub fn level(&self) -> Option<Level> {
+        self.priority.map(|priority| match priority & 7 {
+            0..=3 => Level::Error,
+            4 | 5 => Level::Warning,
+            6 => Level::Info,
+            _ => Level::Debug,
+        })
+    }
+
+    fn strip_version(s: &str) -> Option<&str> {
+        let end = s.find(' ')?;
+        let version = &s[..end];
+        if (1..=2).contains(&version.len()) && version.bytes().all(|b| b.is_ascii_digit()) && version != "0" {
+            Some(&s[end + 1..])
+        } else {
+            None
+        }
+    }
+
+    fn parse_rfc5424(&mut self, s: &'a str) -> Result<()> {
+        let mut rest = s;
+        let mut next = || -> Result<Option<&'a str>> {
+            let (token, tail) = rest.split_once(' ').ok_or(Error::InvalidHeader)?;
+            rest = tail;
+            Ok(Some(token).filter(|&token| token != "-"))
+        };
+
+        self.timestamp = next()?.map(Timestamp::Rfc3339);
+        self.hostname = next()?;
+        self.app_name = next()?;
+        self.proc_id = next()?;
+        self.msg_id = next()?;
+
+        let rest = if let Some(tail) = rest.strip_prefix('-') {
+            tail
+        } else {
+            self.parse_structured_data(rest)?
+        };
+
+        self.msg = match rest {
+            "" => "",
+            _ => rest
+                .strip_prefix(' ')
+                .ok_or(Error::InvalidStructuredData)?
+                .trim_start_matches('\u{feff}'),
+        };
+
+        Ok(())
+    }
+
+    fn parse_structured_data(&mut self, s: &'a str) -> Result<&'a str> {
+        let mut rest = s;
+        if !rest.starts_with('[') {
+            return Err(Error::InvalidStructuredData);
+        }
+
+        while let Some(tail) = rest.strip_prefix('[') {
+            let end = tail.find([' ', ']']).ok_or(Error::InvalidStructuredData)?;
+            if end == 0 {
+                return Err(Error::InvalidStructuredData);
+            }
+            rest = &tail[end..];
+
+            while let Some(tail) = rest.strip_prefix(' ') {
+                let (name, value) = tail.split_once('=').ok_or(Error::InvalidStructuredData)?;
+                if name.is_empty() || name.contains([' ', ']', '"']) || !value.starts_with('"') {
+                    return Err(Error::InvalidStructuredData);
+                }
+
+                let mut escaped = false;
+                let mut json = true;
+                let mut end = None;
+                let mut bytes = value.bytes().enumerate().skip(1);
+                while let Some((i, b)) = bytes.next() {
+                    match b {
+                        b'\\' => {
+                            escaped = true;
+                            if !matches!(bytes.next(), Some((_, b'"' | b'\\'))) {
+                                json = false;
+                            }
+                        }
+                        b'"' => {
+                            end = Some(i);
+                            break;
+                        }
+                        ..=b'\x1f' => json = false,
+                        _ => {}
+                    }
+                }
+                let end = end.ok_or(Error::InvalidStructuredData)?;
+
+                self.params.push(Param {
+                    name,
+                    quoted: &value[..end + 1],
+                    escaping: match (escaped, json) {
+                        (false, _) => Escaping::None,
+                        (true, true) => Escaping::JsonCompatible,
+                        (true, false) => Escaping::Other,
+                    },
+                });
+                rest = &value[end + 1..];
+            }
+
+            rest = rest.strip_prefix(']').ok_or(Error::InvalidStructuredData)?;
+        }
+
+        Ok(rest)
+    }
+
+    fn parse_rfc3164(&mut self, s: &'a str) -> Result<()> {
+        let (timestamp, rest) = Self::parse_bsd_timestamp(s)
+            .or_else(|| Self::parse_rfc3339_timestamp(s))
+            .ok_or(if self.priority.is_some() {
+                Error::InvalidHeader
+            } else {
+                Error::MissingHeader
+            })?;
+        self.timestamp = Some(timestamp);
+
+        let mut rest = rest.trim_start_matches(' ');
+
+        let (token, tail) = rest.split_once(' ').unwrap_or((rest, ""));
+        if !token.is_empty() && !token.ends_with(':') && !token.contains('[') {
+            self.hostname = Some(token);
+            rest = tail;
+        }
+
+        if let Some(end) = rest.find([' ', '[', ':']) {
+            let (app_name, tail) = rest.split_at(end);
+            let (proc_id, tail) = match tail.strip_prefix('[').and_then(|tail| tail.split_once(']')) {
+                Some((proc_id, tail)) => (Some(proc_id), tail),
+                None => (None, tail),
+            };
+            let (colon, tail) = match tail.strip_prefix(':') {
+                Some(tail) => (true, tail),
+                None => (false, tail),
+            };
+            if !app_name.is_empty() && (colon || proc_id.is_some()) {
+                self.app_name = Some(app_name);
+                self.proc_id = proc_id;
+                rest = tail.trim_start_matches(' ');
+            }
+        }
+
+        self.msg = rest;
+
+        Ok(())
+    }
+
+    fn parse_bsd_timestamp(s: &'a str) -> Option<(Timestamp<'a>, &'a str)> {
+        let raw = s.get(..15)?;
+        let b = raw.as_bytes();
+        if !raw.is_ascii() || b[3] != b' ' || b[6] != b' ' || b[9] != b':' || b[12] != b':' {
+            return None;
+        }
+
+        let month = MONTHS.iter().position(|&m| m == &raw[..3])? as u32 + 1;
+        let day = raw[4..6].trim_start().parse().ok()?;
+        let time = NaiveTime::parse_from_str(&raw[7..], "%H:%M:%S").ok()?;
+
+        Some((Timestamp::Bsd { raw, month, day, time }, &s[15..]))
+    }
+
+    fn parse_rfc3339_timestamp(s: &'a str) -> Option<(Timestamp<'a>, &'a str)> {
+        let (raw, rest) = s.split_once(' ')?;
+        DateTime::parse_from_rfc3339(raw).ok()?;
+        Some((Timestamp::Rfc3339(raw), rest))
+    }
+}
+
+// ---
+
+/// Timestamp is a timestamp of a syslog message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timestamp<'a> {
+    /// Timestamp in RFC 3339 format.
+    Rfc3339(&'a str),
+    /// Timestamp in `Mmm dd hh:mm:ss` format, having no year and no time zone.
+    Bsd {
+        raw: &'a str,
+        month: u32,
+        day: u32,
+        time: NaiveTime,
+    },
+}
+
+impl<'a> Timestamp<'a> {
+    pub fn raw(&self) -> &'a str {
+        match self {
+            Self::Rfc3339(raw) => raw,
+            Self::Bsd { raw, .. } => raw,
+        }
+    }
+
+    /// Returns the timestamp as a date and time.
+    ///
+    /// Timestamps having no year are considered to be in the time zone of `now`
+    /// and to belong to the latest year in which they are not more than a day later than `now`.
+    pub fn resolve<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> Option<DateTime<FixedOffset>> {
+        match *self {
+            Self::Rfc3339(raw) => DateTime::parse_from_rfc3339(raw).ok(),
+            Self::Bsd { month, day, time, .. } => {
+                let resolve = |year| {
+                    let ts = NaiveDate::from_ymd_opt(year, month, day)?.and_time(time);
+                    let ts = now.timezone().from_local_datetime(&ts).earliest()?;
+                    Some(ts.fixed_offset())
+                };
+                match resolve(now.year()) {
+                    Some(ts) if ts <= now.fixed_offset() + TimeDelta::days(1) => Some(ts),
+                    _ => resolve(now.year() - 1),
+                }
+            }
+        }
+    }
+}
+
+// ---
+
+/// Param is a parameter of an element of structured data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param<'a> {
+    pub name: &'a str,
+    /// Value including the enclosing quotes, with escape sequences kept as is.
+    pub quoted: &'a str,
+    pub escaping: Escaping,
+}
+
+impl<'a> Param<'a> {
+    /// Returns the value without the enclosing quotes, with escape sequences kept as is.
+    pub fn value(&self) -> &'a str {
+        &self.quoted[1..self.quoted.len() - 1]
+    }
+}
+
+/// Escaping tells what kind of escape sequences a parameter value contains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Escaping {
+    /// No escape sequences.
+    None,
+    /// Only escape sequences also valid in JSON strings, so the quoted value is a valid JSON string.
+    JsonCompatible,
+    /// Escape sequences not valid in JSON strings.
+    Other,
+}
+
+#[cfg(test)]
+mod tests;
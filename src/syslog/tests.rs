@@ -0,0 +1,153 @@
+use super::*;
+
+use chrono::Utc;
+
+fn ts(s: &str) -> Timestamp<'_> {
+    Message::parse(s.as_bytes()).unwrap().timestamp.unwrap()
+}
+
+#[test]
+fn test_rfc5424() {
+    let message = Message::parse(
+        br#"<165>1 2003-10-11T22:14:15.003Z mymachine.example.com evntslog 8710 ID47 [exampleSDID@32473 iut="3" eventSource="Application"] An application event"#,
+    )
+    .unwrap();
+    assert_eq!(message.priority, Some(165));
+    assert_eq!(message.level(), Some(Level::Warning));
+    assert_eq!(message.timestamp, Some(Timestamp::Rfc3339("2003-10-11T22:14:15.003Z")));
+    assert_eq!(message.hostname, Some("mymachine.example.com"));
+    assert_eq!(message.app_name, Some("evntslog"));
+    assert_eq!(message.proc_id, Some("8710"));
+    assert_eq!(message.msg_id, Some("ID47"));
+    assert_eq!(
+        message.params,
+        vec![
+            Param {
+                name: "iut",
+                quoted: r#""3""#,
+                escaping: Escaping::None,
+            },
+            Param {
+                name: "eventSource",
+                quoted: r#""Application""#,
+                escaping: Escaping::None,
+            },
+        ]
+    );
+    assert_eq!(message.msg, "An application event");
+}
+
+#[test]
+fn test_rfc5424_nil() {
+    let message = Message::parse(b"<34>1 - - - - - -").unwrap();
+    assert_eq!(message.level(), Some(Level::Error));
+    assert_eq!(message.timestamp, None);
+    assert_eq!(message.hostname, None);
+    assert_eq!(message.app_name, None);
+    assert_eq!(message.params, vec![]);
+    assert_eq!(message.msg, "");
+
+    let message = Message::parse("<15>1 - host app - - - \u{feff}msg\n".as_bytes()).unwrap();
+    assert_eq!(message.level(), Some(Level::Debug));
+    assert_eq!(message.hostname, Some("host"));
+    assert_eq!(message.msg, "msg");
+}
+
+#[test]
+fn test_rfc5424_escaping() {
+    let message = Message::parse(br#"<14>1 - - - - - [a x="\"q\"" y="a\]b" z="\\"]"#).unwrap();
+    let params = message
+        .params
+        .iter()
+        .map(|p| (p.name, p.value(), p.escaping))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        params,
+        vec![
+            ("x", r#"\"q\""#, Escaping::JsonCompatible),
+            ("y", r#"a\]b"#, Escaping::Other),
+            ("z", r#"\\"#, Escaping::JsonCompatible),
+        ]
+    );
+}
+
+#[test]
+fn test_rfc5424_invalid() {
+    assert_eq!(Message::parse(b"<14>1 - - -"), Err(Error::InvalidHeader));
+    assert_eq!(Message::parse(b"<14>1 - - - - - x"), Err(Error::InvalidStructuredData));
+    assert_eq!(
+        Message::parse(br#"<14>1 - - - - - [a x="1"#),
+        Err(Error::InvalidStructuredData)
+    );
+    assert_eq!(
+        Message::parse(br#"<14>1 - - - - - [a x=1]"#),
+        Err(Error::InvalidStructuredData)
+    );
+    assert_eq!(Message::parse(b"<1000>1 - - - - - -"), Err(Error::InvalidPriority));
+    assert_eq!(Message::parse(b"<x>1 - - - - - -"), Err(Error::InvalidPriority));
+}
+
+#[test]
+fn test_rfc3164() {
+    let message =
+        Message::parse(b"<34>Oct 11 22:14:15 mymachine su: 'su root' failed for lonvick on /dev/pts/8").unwrap();
+    assert_eq!(message.level(), Some(Level::Error));
+    assert_eq!(message.timestamp.map(|ts| ts.raw()), Some("Oct 11 22:14:15"));
+    assert_eq!(message.hostname, Some("mymachine"));
+    assert_eq!(message.app_name, Some("su"));
+    assert_eq!(message.proc_id, None);
+    assert_eq!(message.msg, "'su root' failed for lonvick on /dev/pts/8");
+
+    let message = Message::parse(b"Jan  5 08:00:01 host1 systemd-logind[812]: New session 3 of user root.").unwrap();
+    assert_eq!(message.priority, None);
+    assert_eq!(message.level(), None);
+    assert_eq!(message.hostname, Some("host1"));
+    assert_eq!(message.app_name, Some("systemd-logind"));
+    assert_eq!(message.proc_id, Some("812"));
+    assert_eq!(message.msg, "New session 3 of user root.");
+
+    let message = Message::parse(b"2024-01-25T18:00:01.123+01:00 host1 kernel: [ 0.000000] Linux version").unwrap();
+    assert_eq!(
+        message.timestamp,
+        Some(Timestamp::Rfc3339("2024-01-25T18:00:01.123+01:00"))
+    );
+    assert_eq!(message.hostname, Some("host1"));
+    assert_eq!(message.app_name, Some("kernel"));
+    assert_eq!(message.msg, "[ 0.000000] Linux version");
+
+    let message = Message::parse(b"<13>Jan 25 18:00:01 app[7]: no hostname").unwrap();
+    assert_eq!(message.hostname, None);
+    assert_eq!(message.app_name, Some("app"));
+    assert_eq!(message.proc_id, Some("7"));
+    assert_eq!(message.msg, "no hostname");
+
+    let message = Message::parse(b"Jan 25 18:00:01 host1 no tag here").unwrap();
+    assert_eq!(message.hostname, Some("host1"));
+    assert_eq!(message.app_name, None);
+    assert_eq!(message.msg, "no tag here");
+}
+
+#[test]
+fn test_rfc3164_invalid() {
+    assert_eq!(Message::parse(b"plain text"), Err(Error::MissingHeader));
+    assert_eq!(Message::parse(b"<13>plain text"), Err(Error::InvalidHeader));
+    assert_eq!(
+        Message::parse(b"Foo 25 18:00:01 host1 app: x"),
+        Err(Error::MissingHeader)
+    );
+    assert_eq!(Message::parse(b"\xffJan 25 18:00:01"), Err(Error::InvalidUnicode));
+}
+
+#[test]
+fn test_timestamp_resolve() {
+    let now = Utc.with_ymd_and_hms(2024, 1, 25, 12, 0, 0).unwrap();
+
+    let resolved = ts("Jan 25 18:00:01 x").resolve(&now).unwrap();
+    assert_eq!(resolved.to_rfc3339(), "2024-01-25T18:00:01+00:00");
+
+    let resolved = ts("Dec 31 23:59:59 x").resolve(&now).unwrap();
+    assert_eq!(resolved.to_rfc3339(), "2023-12-31T23:59:59+00:00");
+
+    let resolved = ts("2024-01-25T18:00:01Z x").resolve(&now).unwrap();
+    assert_eq!(resolved.to_rfc3339(), "2024-01-25T18:00:01+00:00");
+}
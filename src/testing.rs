@@ -1,5 +1,13 @@
 //! Testing utilities for internal use.
 
+// std imports
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+// ---
+
 /// Trait that provides a method to generate a sample instance.
 ///
 /// Types implementing this trait can create representative examples
@@ -11,3 +19,29 @@ pub trait Sample {
     /// This is useful for testing where a representative example of the type is needed.
     fn sample() -> Self;
 }
+
+// ---
+
+/// Temporary directory for tests, removed with all its contents when dropped, even if the test fails.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates an empty directory, the name has to be unique among the tests.
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("hl-test-{}-{}", name, std::process::id()));
+        fs::remove_dir_all(&path).ok();
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    /// Returns the path of the directory.
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.0).ok();
+    }
+}
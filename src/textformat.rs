@@ -0,0 +1,109 @@
+// third-party imports
+use chrono::{DateTime, FixedOffset, NaiveDateTime};
+use regex::Regex;
+
+// other local crates
+use encstr::EncodedString;
+
+// local imports
+use crate::{
+    error::{Error, Result},
+    model::{RawRecordFields, RawValue, looks_like_number},
+    settings::TextInputFormat,
+    timestamp::Timestamp,
+};
+
+// ---
+
+/// TextFormat is a compiled user-defined format of text input lines.
+#[derive(Debug)]
+pub struct TextFormat {
+    definition: TextInputFormat,
+    regex: Regex,
+    fields: Vec<(usize, String)>,
+    timestamp: Option<(usize, String)>,
+}
+
+impl TextFormat {
+    /// Compiles the format definition.
+    ///
+    /// If the definition has a timestamp format, it is used to parse the value of the first capture group
+    /// named as any of the given time field names.
+    pub fn new(definition: &TextInputFormat, time_names: &[String]) -> Result<Self> {
+        let regex = Regex::new(&definition.regex).map_err(|source| Error::WrongInputFormatRegularExpression {
+            name: definition.name.clone(),
+            source,
+        })?;
+
+        let mut fields = Vec::new();
+        let mut timestamp = None;
+        for (i, name) in regex.capture_names().enumerate() {
+            let Some(name) = name else {
+                continue;
+            };
+            match &definition.timestamp_format {
+                Some(format) if timestamp.is_none() && time_names.iter().any(|n| n == name) => {
+                    timestamp = Some((i, format.clone()));
+                }
+                _ => fields.push((i, name.to_owned())),
+            }
+        }
+
+        Ok(Self {
+            definition: definition.clone(),
+            regex,
+            fields,
+            timestamp,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    pub fn definition(&self) -> &TextInputFormat {
+        &self.definition
+    }
+
+    /// Parses the line if it matches the format, returning the timestamp and the fields.
+    ///
+    /// Capture groups that do not participate in the match provide no fields.
+    pub(crate) fn parse<'a>(&'a self, line: &'a str) -> Option<(Option<Timestamp<'a>>, RawRecordFields<'a>)> {
+        let captures = self.regex.captures(line)?;
+
+        let ts = self.timestamp.as_ref().and_then(|(i, format)| {
+            let raw = captures.get(*i)?.as_str();
+            Some(match parse_timestamp(raw, format) {
+                Some(value) => Timestamp::with_value(raw, value),
+                None => Timestamp::new(raw),
+            })
+        });
+
+        let mut fields = RawRecordFields::default();
+        for (i, name) in &self.fields {
+            if let Some(value) = captures.get(*i) {
+                let value = value.as_str();
+                fields.push((
+                    name,
+                    if looks_like_number(value.as_bytes()) {
+                        RawValue::Number(value)
+                    } else {
+                        RawValue::String(EncodedString::raw(value))
+                    },
+                ));
+            }
+        }
+
+        Some((ts, fields))
+    }
+}
+
+/// Parses the timestamp using the format, timestamps without time zone are considered to be in UTC.
+fn parse_timestamp(value: &str, format: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_str(value, format)
+        .ok()
+        .or_else(|| Some(NaiveDateTime::parse_from_str(value, format).ok()?.and_utc().into()))
+}
+
+#[cfg(test)]
+mod tests;
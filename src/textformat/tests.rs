@@ -0,0 +1,82 @@
+use super::*;
+
+fn compile(regex: &str, timestamp_format: Option<&str>) -> TextFormat {
+    TextFormat::new(
+        &TextInputFormat {
+            name: "test".into(),
+            regex: regex.into(),
+            timestamp_format: timestamp_format.map(|s| s.into()),
+        },
+        &["time".into(), "ts".into()],
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_parse() {
+    let format = compile(
+        r"^(?P<time>\S+ \S+) \[(?P<level>\w+)\] (?:(?P<pid>\d+)#\d+: )?(?P<msg>.*)$",
+        None,
+    );
+    assert_eq!(format.name(), "test");
+
+    let (ts, fields) = format.parse("2024/01/25 18:00:01 [error] 42#0: open() failed").unwrap();
+    assert!(ts.is_none());
+    assert_eq!(
+        fields.iter().copied().collect::<Vec<_>>(),
+        vec![
+            ("time", RawValue::String(EncodedString::raw("2024/01/25 18:00:01"))),
+            ("level", RawValue::String(EncodedString::raw("error"))),
+            ("pid", RawValue::Number("42")),
+            ("msg", RawValue::String(EncodedString::raw("open() failed"))),
+        ]
+    );
+
+    let (_, fields) = format.parse("2024/01/25 18:00:01 [info] started").unwrap();
+    assert_eq!(
+        fields.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec!["time", "level", "msg"]
+    );
+
+    assert!(format.parse("key=value").is_none());
+}
+
+#[test]
+fn test_timestamp_format() {
+    let format = compile(r"^(?P<ts>\S+ \S+) (?P<msg>.*)$", Some("%Y/%m/%d %H:%M:%S"));
+
+    let (ts, fields) = format.parse("2024/01/25 18:00:01 started").unwrap();
+    let ts = ts.unwrap();
+    assert_eq!(ts.raw(), "2024/01/25 18:00:01");
+    assert_eq!(
+        ts.parse().map(|ts| ts.to_rfc3339()),
+        Some("2024-01-25T18:00:01+00:00".into())
+    );
+    assert_eq!(fields.iter().map(|(k, _)| *k).collect::<Vec<_>>(), vec!["msg"]);
+
+    let (ts, _) = format.parse("2024/01/25 18:00:01+0300 started").unwrap();
+    assert_eq!(ts.unwrap().parse(), None);
+
+    let format = compile(r"^(?P<ts>\S+ \S+ \S+) (?P<msg>.*)$", Some("%Y/%m/%d %H:%M:%S %z"));
+    let (ts, _) = format.parse("2024/01/25 18:00:01 +0300 started").unwrap();
+    assert_eq!(
+        ts.unwrap().parse().map(|ts| ts.to_rfc3339()),
+        Some("2024-01-25T18:00:01+03:00".into())
+    );
+}
+
+#[test]
+fn test_invalid_regex() {
+    let result = TextFormat::new(
+        &TextInputFormat {
+            name: "broken".into(),
+            regex: "(?P<x>".into(),
+            timestamp_format: None,
+        },
+        &[],
+    );
+    assert!(matches!(
+        result,
+        Err(Error::WrongInputFormatRegularExpression { name, .. }) if name == "broken"
+    ));
+}
@@ -34,7 +34,7 @@ pub trait StylingPush<B: Push<u8>> {
     fn reset(&mut self);
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 struct LevelStyles {
     known: EnumMap<Level, StylePack>,
     unknown: StylePack,
@@ -42,9 +42,10 @@ struct LevelStyles {
 
 // ---
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Theme {
     levels: LevelStyles,
+    faded: LevelStyles,
     pub indicators: IndicatorPack,
     pub expanded_value_prefix: ExpandedValuePrefix,
     pub expanded_value_suffix: ExpandedValueSuffix,
@@ -57,19 +58,9 @@ impl Theme {
 
     fn new(cfg: impl Borrow<themecfg::Theme>) -> Self {
         let cfg = cfg.borrow();
-        let mut levels = LevelStyles {
-            unknown: StylePack::load(&cfg.elements),
-            ..Default::default()
-        };
-        for (level, pack) in &cfg.levels {
-            if let Some(level) = level {
-                levels.known[*level] = StylePack::load(pack);
-            } else {
-                levels.unknown = StylePack::load(pack);
-            }
-        }
         Self {
-            levels,
+            levels: LevelStyles::load(cfg, |s| s.into()),
+            faded: LevelStyles::load(cfg, Style::faded),
             indicators: IndicatorPack::new(&cfg.indicators),
             expanded_value_prefix: cfg
                 .elements
@@ -100,6 +91,14 @@ impl Theme {
         Ok(themecfg::Theme::list(dirs)?)
     }
 
+    /// Returns a variant of the theme with faded styles, used for context entries.
+    pub fn faded(&self) -> Self {
+        Self {
+            levels: self.faded.clone(),
+            ..self.clone()
+        }
+    }
+
     pub fn apply<'a, B: Push<u8>, F: FnOnce(&mut Styler<'a, B>)>(
         &'a self,
         buf: &'a mut B,
@@ -139,6 +138,23 @@ impl Sample for Arc<Theme> {
     }
 }
 
+impl LevelStyles {
+    fn load(cfg: &themecfg::Theme, style: impl Fn(&themecfg::Style) -> Style + Copy) -> Self {
+        let mut result = Self {
+            unknown: StylePack::load(&cfg.elements, style),
+            ..Default::default()
+        };
+        for (level, pack) in &cfg.levels {
+            if let Some(level) = level {
+                result.known[*level] = StylePack::load(pack, style);
+            } else {
+                result.unknown = StylePack::load(pack, style);
+            }
+        }
+        result
+    }
+}
+
 // ---
 
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
@@ -165,6 +181,13 @@ impl Style {
         Sequence::reset().into()
     }
 
+    fn faded(style: &themecfg::Style) -> Self {
+        let mut style = style.clone();
+        style.modes.adds.remove(themecfg::Mode::Bold);
+        style.modes.adds.insert(themecfg::Mode::Faint);
+        Self::from(&style)
+    }
+
     fn convert_color(color: &themecfg::Color) -> ColorCode {
         match color {
             themecfg::Color::Plain(color) => match color {
@@ -320,7 +343,7 @@ impl<'a, B: Push<u8>> StylingPush<B> for Styler<'a, B> {
 
 // ---
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 struct StylePack {
     elements: EnumMap<Element, Option<usize>>,
     reset: Option<usize>,
@@ -339,7 +362,7 @@ impl StylePack {
         self.elements[element] = Some(pos);
     }
 
-    fn load(s: &themecfg::StylePack) -> Self {
+    fn load(s: &themecfg::StylePack, style: impl Fn(&themecfg::Style) -> Style) -> Self {
         let mut result = Self::default();
 
         if !s.is_empty() {
@@ -347,8 +370,8 @@ impl StylePack {
             result.reset = Some(0);
         }
 
-        for (&element, style) in s.iter() {
-            result.add(element, &Style::from(style));
+        for (&element, item) in s.iter() {
+            result.add(element, &style(item));
         }
 
         result
@@ -357,7 +380,7 @@ impl StylePack {
 
 // ---
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct IndicatorPack {
     pub sync: SyncIndicatorPack,
 }
@@ -372,7 +395,7 @@ impl IndicatorPack {
 
 // ---
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct SyncIndicatorPack {
     pub synced: Indicator,
     pub failed: Indicator,
@@ -389,7 +412,7 @@ impl SyncIndicatorPack {
 
 // ---
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Indicator {
     pub value: String,
     pub width: usize,
@@ -423,6 +446,7 @@ impl Indicator {
 
 // ---
 
+#[derive(Clone)]
 pub struct ExpandedValuePrefix {
     pub value: String,
 }
@@ -445,6 +469,7 @@ impl Default for ExpandedValuePrefix {
 
 // ---
 
+#[derive(Clone)]
 pub struct ExpandedValueSuffix {
     pub value: String,
 }
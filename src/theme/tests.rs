@@ -48,6 +48,26 @@ fn test_style_from_rgb_color() {
     assert_ne!(style.0, Sequence::reset());
 }
 
+#[test]
+fn test_style_faded() {
+    let style = themecfg::Style::new()
+        .modes(themecfg::Mode::Bold.into())
+        .foreground(Some(Color::Plain(PlainColor::Green)));
+
+    assert_eq!(Style::from(&style).0.data(), b"\x1b[0;1;32m");
+    assert_eq!(Style::faded(&style).0.data(), b"\x1b[0;2;32m");
+}
+
+#[test]
+fn test_faded_theme() {
+    let theme = Theme::none().faded();
+    let mut buf = Vec::new();
+    theme.apply(&mut buf, &Some(Level::Debug), |s| {
+        s.element(Element::Message, |s| s.batch(|buf| buf.extend_from_slice(b"hello!")));
+    });
+    assert_eq!(buf, b"hello!");
+}
+
 #[test]
 fn test_style_from_background_color() {
     let style = themecfg::Style {
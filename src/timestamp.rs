@@ -9,7 +9,7 @@ use crate::app::UnixTimestampUnit;
 
 // ---
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Timestamp<'a> {
     raw: &'a str,
     parsed: OnceCell<Option<DateTime<FixedOffset>>>,
@@ -25,6 +25,15 @@ impl<'a> Timestamp<'a> {
         }
     }
 
+    /// Returns a new Timestamp with the value already known, for raw values in formats not supported by the parser.
+    pub fn with_value(raw: &'a str, value: DateTime<FixedOffset>) -> Self {
+        Self {
+            raw,
+            parsed: OnceCell::from(Some(value)),
+            unix_unit: None,
+        }
+    }
+
     pub fn raw(&self) -> &'a str {
         self.raw
     }
@@ -3,6 +3,7 @@ use std::{
     fs,
     io::{self, Read, Seek, Write},
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
 // third-party imports
@@ -19,6 +20,8 @@ pub trait FileSystem {
     fn exists(&self, path: &Path) -> io::Result<bool>;
     fn open(&self, path: &Path) -> io::Result<Box<dyn FileRead<Metadata = Self::Metadata> + Send + Sync>>;
     fn create(&self, path: &Path) -> io::Result<Box<dyn FileReadWrite<Metadata = Self::Metadata> + Send + Sync>>;
+    /// Sets modification time of the file to the current time.
+    fn touch(&self, path: &Path) -> io::Result<()>;
 }
 
 #[cfg(test)]
@@ -33,6 +36,7 @@ mock! {
         fn exists(&self, path: &Path) -> io::Result<bool>;
         fn open(&self, path: &Path) -> io::Result<Box<dyn FileRead<Metadata=M> + Send + Sync>>;
         fn create(&self, path: &Path) -> io::Result<Box<dyn FileReadWrite<Metadata=M> + Send + Sync>>;
+        fn touch(&self, path: &Path) -> io::Result<()>;
     }
 }
 
@@ -62,6 +66,11 @@ macro_rules! delegate_fs_methods {
         fn create(&self, path: &Path) -> io::Result<Box<dyn FileReadWrite<Metadata = Self::Metadata> + Send + Sync>> {
             (**self).create(path)
         }
+
+        #[inline]
+        fn touch(&self, path: &Path) -> io::Result<()> {
+            (**self).touch(path)
+        }
     };
 }
 
@@ -201,6 +210,14 @@ impl FileSystem for LocalFileSystem {
     fn create(&self, path: &Path) -> io::Result<Box<dyn FileReadWrite<Metadata = Self::Metadata> + Send + Sync>> {
         Ok(Box::new(fs::File::create(path)?))
     }
+
+    #[inline]
+    fn touch(&self, path: &Path) -> io::Result<()> {
+        fs::File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(SystemTime::now())
+    }
 }
 
 // ---
@@ -403,6 +420,17 @@ pub mod mem {
             files.insert(path.clone(), file.clone());
             Ok(Box::new(FileCursor::new(file)))
         }
+
+        fn touch(&self, path: &Path) -> io::Result<()> {
+            let path = self.canonicalize(path)?;
+            let files = self.files.read().unwrap();
+            if let Some(file) = files.get(&path) {
+                file.write().unwrap().meta.modified = SystemTime::now();
+                Ok(())
+            } else {
+                Err(io::Error::new(io::ErrorKind::NotFound, "file not found"))
+            }
+        }
     }
 }
 
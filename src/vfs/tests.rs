@@ -39,6 +39,13 @@ fn test_filesystem() {
     let res = fs.open(Path::new("nonexistent.txt"));
     assert!(res.is_err());
     assert_eq!(res.err().map(|e| e.kind()), Some(io::ErrorKind::NotFound));
+
+    let modified = meta.modified;
+    fs.touch(path).unwrap();
+    assert!(fs.metadata(path).unwrap().modified >= modified);
+
+    let res = fs.touch(Path::new("nonexistent.txt"));
+    assert_eq!(res.err().map(|e| e.kind()), Some(io::ErrorKind::NotFound));
 }
 
 #[test]
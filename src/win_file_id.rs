@@ -33,8 +33,8 @@ unsafe extern "system" {
 
 #[derive(Clone, PartialEq)]
 pub struct FileId {
-    file_index: u64,
-    volume_serial_number: u32,
+    pub file_index: u64,
+    pub volume_serial_number: u32,
 }
 
 pub struct FileInfo {